@@ -133,3 +133,28 @@ pub fn bdd_closet_bench(c: &mut Criterion) {
                      ),
     );
 }
+
+fn families_10_items_1000() -> (Closet, Vec<Item>) {
+    let closet_builder = (0..10)
+        .flat_map(|family| (0..100).map(move |item| (family, item)))
+        .fold(ClosetBuilder::new(), |closet_builder, (family, item)| {
+            let family_id = Family::new(format!("family{}", family));
+            let item_id = Item::new(format!("family{}:{}", family, item));
+
+            closet_builder.add_item(&family_id, &item_id)
+        });
+
+    let selections = (0..10)
+        .map(|family| Item::new(format!("family{}:{}", family, family * 7)))
+        .collect();
+
+    (closet_builder.must_build(), selections)
+}
+
+pub fn bdd_closet_validation_bench(c: &mut Criterion) {
+    let (closet, selections) = families_10_items_1000();
+
+    c.bench_function("bdd complete_outfit(10 families, 1000 items, 10 selections)",
+                     move |b| b.iter(|| closet.complete_outfit(selections.clone())),
+    );
+}
@@ -8,6 +8,7 @@ mod closet_bench_bdd;
 
 criterion_group!(
     benches,
-    closet_bench_bdd::bdd_closet_bench
+    closet_bench_bdd::bdd_closet_bench,
+    closet_bench_bdd::bdd_closet_validation_bench
 );
 criterion_main!(benches);
\ No newline at end of file
@@ -0,0 +1,174 @@
+use bdd::closet::Closet;
+use bdd::node::Node;
+use core::Family;
+use core::Item;
+use std::collections::BTreeMap;
+
+impl Closet {
+    /// Lists, for each family that can still take an item, the unselected items that some completion of the selections wears.
+    /// A family drops out once it has as many selections as its cardinality allows.
+    /// Incompatible or unknown selections leave nothing compatible.
+    pub fn compatible_items(&self, selections: &[Item]) -> BTreeMap<Family, Vec<Item>> {
+        if selections.iter().any(|selection| self.get_family(selection).is_none()) {
+            return BTreeMap::new();
+        }
+
+        let root: Node = selections.iter()
+            .fold(self.root().clone(), |new_root, selection| Node::restrict(&new_root, selection, true));
+
+        let selected_families = selections.iter()
+            .filter_map(|selection| self.get_family(selection))
+            .fold(BTreeMap::new(), |mut selected: BTreeMap<&Family, usize>, family| {
+                *selected.entry(family).or_insert(0) += 1;
+                selected
+            });
+        let is_full = |family: &Family| selected_families.get(family)
+            .is_some_and(|&selected| selected >= self.family_cardinality(family).bounds().1);
+
+        self.item_index().iter()
+            .filter(|&(item, family)| !is_full(family) && !selections.contains(item))
+            .filter(|&(item, _)| Node::restrict(&root, item, true) != Node::FALSE_LEAF)
+            .fold(BTreeMap::new(), |mut compatible, (item, family)| {
+                compatible.entry(family.clone()).or_insert_with(Vec::new).push(item.clone());
+                compatible
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet_builder::ClosetBuilder;
+    use core::CardinalitySpec;
+    use core::Family;
+    use core::Item;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn no_selections_returns_every_item() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .add_exclusion_rule(&blue, &jeans);
+
+        let closet = closet_builder.must_build();
+
+        let expected = {
+            let mut expected = BTreeMap::new();
+            expected.insert(shirts, vec![blue, red]);
+            expected.insert(pants, vec![jeans, slacks]);
+            expected
+        };
+        assert_eq!(expected, closet.compatible_items(&[]));
+    }
+
+    #[test]
+    fn exclusion_rule_removes_excluded_item() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .add_exclusion_rule(&blue, &jeans);
+
+        let closet = closet_builder.must_build();
+
+        let expected = {
+            let mut expected = BTreeMap::new();
+            expected.insert(pants, vec![slacks]);
+            expected
+        };
+        assert_eq!(expected, closet.compatible_items(&[blue]));
+    }
+
+    #[test]
+    fn conflicting_selections_returns_nothing() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let socks = Item::new("socks:black");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+        let feet = Family::new("feet");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .add_item(&feet, &socks)
+            .add_exclusion_rule(&blue, &jeans);
+
+        let closet = closet_builder.must_build();
+
+        assert_eq!(BTreeMap::new(), closet.compatible_items(&[blue, jeans]));
+    }
+
+    #[test]
+    fn unknown_selection_returns_nothing() {
+        let closet = ClosetBuilder::new()
+            .add_items(&Family::new("shirts"), &[Item::new("shirts:blue"), Item::new("shirts:red")])
+            .must_build();
+
+        let hat = Item::new("hats:fedora");
+        assert_eq!(BTreeMap::new(), closet.compatible_items(&[hat]));
+    }
+
+    #[test]
+    fn family_with_room_for_more_items_stays_listed() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let cap = Item::new("hats:cap");
+        let fedora = Item::new("hats:fedora");
+        let beanie = Item::new("hats:beanie");
+
+        let shirts = Family::new("shirts");
+        let hats = Family::new("hats");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&shirts, &[blue.clone(), red.clone()])
+            .add_items(&hats, &[cap.clone(), fedora.clone(), beanie.clone()])
+            .set_family_cardinality(&hats, CardinalitySpec::Range(0, 2))
+            .add_exclusion_rule(&cap, &beanie)
+            .must_build();
+
+        let expected = {
+            let mut expected = BTreeMap::new();
+            expected.insert(shirts, vec![blue.clone(), red.clone()]);
+            expected.insert(hats.clone(), vec![fedora.clone()]);
+            expected
+        };
+        assert_eq!(expected, closet.compatible_items(std::slice::from_ref(&cap)));
+
+        let expected = {
+            let mut expected = BTreeMap::new();
+            expected.insert(Family::new("shirts"), vec![blue, red]);
+            expected
+        };
+        assert_eq!(expected, closet.compatible_items(&[cap, fedora]));
+    }
+}
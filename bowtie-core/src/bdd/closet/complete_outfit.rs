@@ -1,51 +1,302 @@
 use bdd::closet::Closet;
 use bdd::node::Node;
+use bdd::node::NodeId;
 use core::Family;
 use core::Item;
+use core::ItemComparison;
 use core::Outfit;
 use core::OutfitError;
+use core::OutfitError::DuplicateSelection;
 use core::OutfitError::IncompatibleSelections;
 use core::OutfitError::MultipleItemsPerFamily;
+use core::OutfitError::NoValidCompletion;
 use core::OutfitError::UnknownItems;
+use core::TieBreak;
+use itertools::Itertools;
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
 
 impl Closet {
+    /// Completes the outfit from the selections. The selections are applied all at once,
+    /// so the outfit doesn't depend on their order.
+    /// An empty closet completes to an empty outfit, and rejects any selection as unknown.
+    /// The outfit's items carry the metadata they were added to the closet with.
     pub fn complete_outfit(&self, selections: Vec<Item>) -> Result<Outfit, OutfitError> {
         validate(self, &selections)?;
 
-        let mut root: Node = selections.iter()
-            .fold(
-                self.root().clone(),
-                |new_root, selection| Node::restrict(&new_root, selection, true));
+        Ok(complete(self, self.root(), selections))
+    }
+
+    /// Like `complete_outfit`, but borrows the selections so they can be reused across calls.
+    pub fn complete_outfit_ref(&self, selections: &[Item]) -> Result<Outfit, OutfitError> {
+        validate(self, selections)?;
+
+        Ok(complete(self, self.root(), selections.to_vec()))
+    }
+
+    /// Like `complete_outfit`, but never picks the `forbidden` items.
+    /// Fails with `NoValidCompletion` when no outfit is left without them, e.g. when every item of a family is forbidden.
+    pub fn complete_outfit_excluding(&self, selections: Vec<Item>, forbidden: Vec<Item>) -> Result<Outfit, OutfitError> {
+        validate(self, &selections)?;
+        if let Some(items) = find_unknown_items(self, &forbidden) {
+            return Err(UnknownItems(items));
+        }
+
+        let assignments: BTreeMap<Item, bool> = forbidden.iter()
+            .map(|item| (item.clone(), false))
+            .collect();
+        let root = self.root().restrict_all(&assignments);
+
+        let forbidden_selection = selections.iter().any(|selection| assignments.contains_key(selection));
+        let remaining = selections.iter()
+            .fold(root.clone(), |new_root, selection| Node::restrict(&new_root, selection, true));
+
+        if forbidden_selection || remaining == Node::FALSE_LEAF {
+            let mut forbidden = forbidden;
+            forbidden.sort();
+            return Err(NoValidCompletion(forbidden));
+        }
+
+        Ok(complete(self, &root, selections))
+    }
+
+    /// Like `complete_outfit`, but `tie_break` decides which item to take wherever more than one would complete the outfit.
+    pub fn complete_outfit_with(&self, selections: Vec<Item>, tie_break: &TieBreak) -> Result<Outfit, OutfitError> {
+        validate(self, &selections)?;
+
+        if let TieBreak::Lexicographic = tie_break {
+            return Ok(first_in_sorted_order(self, selections));
+        }
+
+        let assignments: BTreeMap<Item, bool> = selections.iter()
+            .map(|selection| (selection.clone(), true))
+            .collect();
+        let mut root: Node = self.root().restrict_all(&assignments);
+
+        let mut decided: BTreeSet<Item> = selections.iter()
+            .cloned()
+            .collect();
+        let mut outfit_items = selections;
+        while let Node::Branch(id, low, high) = root {
+            let high = Node::from(high);
+            let low = Node::from(low);
+
+            let take_high = match (&high, &low) {
+                (Node::Leaf(false), _) => false,
+                (_, Node::Leaf(false)) => true,
+                _ => takes_item(self, &id, &low, &decided, tie_break),
+            };
+
+            decided.insert(id.clone());
+            if take_high {
+                outfit_items.push(id);
+                root = high;
+            } else {
+                root = low;
+            }
+        }
 
+        Ok(outfit(self, outfit_items))
+    }
+
+    /// Like `complete_outfit`, but keys the chosen item by its family.
+    /// Fails with `MultipleItemsPerFamily` when a family allows several items and more than one is chosen; see `complete_outfit_grouped`.
+    pub fn complete_outfit_by_family(&self, selections: Vec<Item>) -> Result<BTreeMap<Family, Item>, OutfitError> {
+        let grouped = self.complete_outfit_grouped(selections)?;
+
+        let several: BTreeMap<Family, Vec<Item>> = grouped.iter()
+            .filter(|(_, items)| items.len() > 1)
+            .map(|(family, items)| (family.clone(), items.clone()))
+            .collect();
+        if !several.is_empty() {
+            return Err(MultipleItemsPerFamily(several));
+        }
+
+        Ok(grouped.into_iter()
+            .filter_map(|(family, mut items)| items.pop().map(|item| (family, item)))
+            .collect())
+    }
+
+    /// Like `complete_outfit`, but groups the chosen items by their family. A family that allows
+    /// several items, e.g. `CardinalitySpec::Exactly(2)`, lists every one chosen from it.
+    pub fn complete_outfit_grouped(&self, selections: Vec<Item>) -> Result<BTreeMap<Family, Vec<Item>>, OutfitError> {
+        let outfit = self.complete_outfit(selections)?;
+        let family_map = self.family_map();
+
+        Ok(outfit.into_iter()
+            .filter_map(|item| family_map.get(item).map(|family| (family, item)))
+            .fold(BTreeMap::new(), |mut by_family: BTreeMap<Family, Vec<Item>>, (family, item)| {
+                by_family.entry(family.clone()).or_default().push(item.clone());
+                by_family
+            }))
+    }
+
+    /// Like `complete_outfit`, but reports every validation failure instead of only the first.
+    /// Incompatible selections are only reported when no family has multiple selections, since those always conflict.
+    pub fn complete_outfit_validated(&self, selections: Vec<Item>) -> Result<Outfit, Vec<OutfitError>> {
+        let errors = validate_all(self, &selections);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(complete(self, self.root(), selections))
+    }
+
+    /// Like `complete_outfit`, but picks the completion with the highest total priority.
+    /// Items without a priority count as zero; ties fall back to the default completion.
+    pub fn complete_outfit_preferred(&self, selections: Vec<Item>, priorities: &BTreeMap<Item, u32>) -> Result<Outfit, OutfitError> {
+        validate(self, &selections)?;
+
+        let assignments: BTreeMap<Item, bool> = selections.iter()
+            .map(|selection| (selection.clone(), true))
+            .collect();
+        let mut root: Node = self.root().restrict_all(&assignments);
+
+        let mut scores = HashMap::new();
         let mut outfit_items = selections;
-        loop {
-            match root {
-                Node::Branch(id, low, high) => {
-                    let high = Node::from(high);
-                    let low = Node::from(low);
-
-                    match high {
-                        Node::Leaf(false) => root = low,
-                        _ => {
-                            outfit_items.push(id);
-                            root = high;
-                        }
+        while let Node::Branch(id, low, high) = root {
+            let high = Node::from(high);
+            let low = Node::from(low);
+
+            let high_score = best_score(&high, priorities, &mut scores)
+                .map(|score| score + u64::from(priorities.get(&id).cloned().unwrap_or(0)));
+            let low_score = best_score(&low, priorities, &mut scores);
+
+            if high_score.is_some() && high_score >= low_score {
+                outfit_items.push(id);
+                root = high;
+            } else {
+                root = low;
+            }
+        }
+
+        Ok(outfit(self, outfit_items))
+    }
+}
+
+fn best_score(node: &Node, priorities: &BTreeMap<Item, u32>, scores: &mut HashMap<NodeId, Option<u64>>) -> Option<u64> {
+    match node {
+        Node::Leaf(true) => Some(0),
+        Node::Leaf(false) => None,
+        Node::Branch(id, low, high) => {
+            if let Some(score) = scores.get(&NodeId::from(node)) {
+                return *score;
+            }
+
+            let high_score = best_score(&Node::from(high), priorities, scores)
+                .map(|score| score + u64::from(priorities.get(id).cloned().unwrap_or(0)));
+            let low_score = best_score(&Node::from(low), priorities, scores);
+
+            let score = high_score.max(low_score);
+            scores.insert(NodeId::from(node), score);
+            score
+        }
+    }
+}
+
+/// Whether `tie_break` takes `item` over the undecided items of its family that `low` could still complete with.
+fn takes_item(closet: &Closet, item: &Item, low: &Node, decided: &BTreeSet<Item>, tie_break: &TieBreak) -> bool {
+    let compare: &ItemComparison = match tie_break {
+        TieBreak::HighBranch => return true,
+        TieBreak::LowBranch => return false,
+        // Lexicographic completions don't walk the diagram, see `first_in_sorted_order`
+        TieBreak::Lexicographic => return true,
+        TieBreak::Custom(compare) => compare.as_ref(),
+    };
+
+    let family = closet.get_family(item);
+    closet.item_index.iter()
+        .filter(|&(other, other_family)| other != item && Some(other_family) == family && !decided.contains(other))
+        .filter(|&(other, _)| Node::restrict(low, other, true) != Node::FALSE_LEAF)
+        .all(|(other, _)| compare(item, other) != Ordering::Greater)
+}
+
+/// The completion whose sorted items compare first, found by deciding the closet's items in their own order.
+/// Every decision restricts the diagram rather than following its branches, so the order the diagram tests
+/// items in doesn't matter. Stopping short of an item compares before taking it, so once the outfit can end
+/// without any more items, the remaining items are left out.
+fn first_in_sorted_order(closet: &Closet, selections: Vec<Item>) -> Outfit {
+    let assignments: BTreeMap<Item, bool> = selections.iter()
+        .map(|selection| (selection.clone(), true))
+        .collect();
+    let mut root: Node = closet.root().restrict_all(&assignments);
+
+    let undecided = closet.item_index.keys()
+        .filter(|item| !assignments.contains_key(item))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut outfit_items = selections;
+    for (index, item) in undecided.iter().enumerate() {
+        let selected_later = outfit_items.iter().any(|selection| selection > item);
+        if !selected_later {
+            let rest: BTreeMap<Item, bool> = undecided[index..].iter()
+                .map(|item| (item.clone(), false))
+                .collect();
+            if root.restrict_all(&rest) == Node::TRUE_LEAF {
+                break;
+            }
+        }
+
+        let high = Node::restrict(&root, item, true);
+        if high != Node::FALSE_LEAF {
+            outfit_items.push(item.clone());
+            root = high;
+        } else {
+            root = Node::restrict(&root, item, false);
+        }
+    }
+
+    outfit(closet, outfit_items)
+}
+
+pub(super) fn complete(closet: &Closet, root: &Node, selections: Vec<Item>) -> Outfit {
+    let assignments: BTreeMap<Item, bool> = selections.iter()
+        .map(|selection| (selection.clone(), true))
+        .collect();
+    let mut root: Node = root.restrict_all(&assignments);
+
+    let mut outfit_items = selections;
+    loop {
+        match root {
+            Node::Branch(id, low, high) => {
+                let high = Node::from(high);
+                let low = Node::from(low);
+
+                match high {
+                    Node::Leaf(false) => root = low,
+                    _ => {
+                        outfit_items.push(id);
+                        root = high;
                     }
                 }
-                Node::Leaf(_val) => {
-                    outfit_items.sort();
-                    return Ok(Outfit::new(outfit_items));
-                }
             }
+            Node::Leaf(_val) => return outfit(closet, outfit_items),
         }
     }
 }
 
-fn validate(closet: &Closet, selections: &[Item]) -> Result<(), OutfitError> {
+/// Sorts the items into an outfit, taking each item as the closet stored it so that its metadata is kept.
+pub(super) fn outfit(closet: &Closet, mut outfit_items: Vec<Item>) -> Outfit {
+    outfit_items.sort();
+
+    Outfit::new(outfit_items.into_iter()
+        .map(|item| match closet.item_index.get_key_value(&item) {
+            Some((stored, _)) => stored.clone(),
+            None => item,
+        })
+        .collect())
+}
+
+pub(super) fn validate(closet: &Closet, selections: &[Item]) -> Result<(), OutfitError> {
     if let Some(items) = find_unknown_items(&closet, &selections) {
         return Err(UnknownItems(items));
     }
+    if let Some(item) = find_repeated_selection(selections) {
+        return Err(DuplicateSelection(item));
+    }
     if let Some(items) = find_duplicate_items(&closet, &selections) {
         return Err(MultipleItemsPerFamily(items));
     }
@@ -56,9 +307,36 @@ fn validate(closet: &Closet, selections: &[Item]) -> Result<(), OutfitError> {
     Ok(())
 }
 
+fn validate_all(closet: &Closet, selections: &[Item]) -> Vec<OutfitError> {
+    let mut errors = vec![];
+
+    if let Some(items) = find_unknown_items(closet, selections) {
+        errors.push(UnknownItems(items));
+    }
+
+    if let Some(item) = find_repeated_selection(selections) {
+        errors.push(DuplicateSelection(item));
+    }
+
+    let known_selections = selections.iter()
+        .filter(|item| closet.family_map().contains_key(item))
+        .unique()
+        .cloned()
+        .collect::<Vec<Item>>();
+
+    if let Some(items) = find_duplicate_items(closet, &known_selections) {
+        errors.push(MultipleItemsPerFamily(items));
+    } else if let Some(items) = find_conflicting_items(closet, &known_selections) {
+        errors.push(IncompatibleSelections(items));
+    }
+
+    errors
+}
+
 fn find_unknown_items(closet: &Closet, selections: &[Item]) -> Option<Vec<Item>> {
+    let family_map = closet.family_map();
     let unknown_items = selections.iter()
-        .filter(|ref item| closet.get_family(item).is_none())
+        .filter(|item| !family_map.contains_key(item))
         .cloned()
         .collect::<Vec<Item>>();
 
@@ -69,16 +347,24 @@ fn find_unknown_items(closet: &Closet, selections: &[Item]) -> Option<Vec<Item>>
     }
 }
 
+fn find_repeated_selection(selections: &[Item]) -> Option<Item> {
+    let mut seen = BTreeSet::new();
+
+    selections.iter()
+        .find(|item| !seen.insert(*item))
+        .cloned()
+}
+
 fn find_duplicate_items(closet: &Closet, selections: &[Item]) -> Option<BTreeMap<Family, Vec<Item>>> {
+    let family_map = closet.family_map();
     let duplicates: BTreeMap<Family, Vec<Item>> = selections.iter()
-        .map(|item| (closet.get_family(item), item))
-        .map(|(family, item): (Option<&Family>, &Item)| (family.unwrap(), item))
+        .filter_map(|item| family_map.get(item).map(|family| (family, item)))
         .fold(BTreeMap::new(), |mut duplicates: BTreeMap<Family, Vec<Item>>, (family, item): (&Family, &Item)| {
             duplicates.entry(family.clone()).or_insert_with(|| vec![]).push(item.clone());
             duplicates
         })
         .iter()
-        .filter(|&(_, items)| items.len() > 1)
+        .filter(|&(family, items)| items.len() > closet.family_cardinality(family).bounds().1)
         .map(|(family, items)| (family.clone(), items.clone()))
         .collect();
 
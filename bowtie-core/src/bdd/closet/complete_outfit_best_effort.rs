@@ -0,0 +1,166 @@
+use bdd::closet::Closet;
+use bdd::closet::complete_outfit::complete;
+use bdd::closet_builder::ClosetBuilder;
+use bdd::node::BoolOp;
+use bdd::node::Node;
+use core::Family;
+use core::Item;
+use core::Outfit;
+use std::collections::BTreeMap;
+
+impl Closet {
+    /// Completes an outfit, skipping families that cannot be satisfied instead of failing.
+    /// Unknown selections, and selections that conflict with earlier ones, are ignored.
+    /// Returns the outfit along with the skipped families; a skipped family keeps only its selected items.
+    /// Closets made by `Closet::new` don't know their rules apart from their families, so they never skip a family.
+    pub fn complete_outfit_best_effort(&self, selections: Vec<Item>) -> (Outfit, Vec<Family>) {
+        let and = |node: Node, other: Node| Node::apply_in(&node, &other, BoolOp::And, &self.order);
+
+        let mut root = self.rules.clone().unwrap_or_else(|| self.root.clone());
+        let mut selected_families: BTreeMap<&Family, usize> = BTreeMap::new();
+        let mut kept_selections = vec![];
+
+        for selection in selections {
+            let family = match self.get_family(&selection) {
+                None => continue,
+                Some(family) => family,
+            };
+            let selected = selected_families.get(family).copied().unwrap_or(0);
+            if selected >= self.family_cardinality(family).bounds().1 || kept_selections.contains(&selection) {
+                continue;
+            }
+
+            let candidate = and(root.clone(), Node::positive_branch(&selection));
+            if candidate != Node::FALSE_LEAF {
+                root = candidate;
+                selected_families.insert(family, selected + 1);
+                kept_selections.push(selection);
+            }
+        }
+
+        // Families with selections go first, so that no other family's choice can take their place.
+        let mut families = self.families();
+        families.sort_by_key(|family| !selected_families.contains_key(family));
+
+        let mut skipped = vec![];
+        for family in families {
+            let items = self.items_in_family(family).into_iter()
+                .cloned()
+                .collect::<Vec<_>>();
+
+            let family_relationship = ClosetBuilder::family_relationship(&items, self.family_cardinality(family));
+            let candidate = and(root.clone(), self.in_diagram_order(family_relationship));
+            if candidate != Node::FALSE_LEAF {
+                root = candidate;
+            } else {
+                root = items.iter()
+                    .filter(|item| !kept_selections.contains(item))
+                    .fold(root, |node, item| and(node, Node::negative_branch(item)));
+                skipped.push(family.clone());
+            }
+        }
+
+        (complete(self, &root, kept_selections), skipped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet::Closet;
+    use bdd::closet_builder::ClosetBuilder;
+    use core::CardinalitySpec;
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+
+    fn closet() -> Closet {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+        let shoes = Family::new("shoes");
+
+        ClosetBuilder::new()
+            .add_item(&shirts, &Item::new("shirts:red"))
+            .add_item(&shirts, &Item::new("shirts:blue"))
+            .add_item(&pants, &Item::new("pants:slacks"))
+            .add_item(&pants, &Item::new("pants:jeans"))
+            .add_item(&shoes, &Item::new("shoes:boots"))
+            .add_item(&shoes, &Item::new("shoes:sneakers"))
+            .add_exclusion_rules(&Item::new("shirts:red"), &[Item::new("pants:slacks"), Item::new("pants:jeans")])
+            .must_build()
+    }
+
+    #[test]
+    fn family_excluded_by_selection_is_skipped() {
+        let red = Item::new("shirts:red");
+        let boots = Item::new("shoes:boots");
+
+        let (outfit, skipped) = closet().complete_outfit_best_effort(vec![red.clone()]);
+
+        assert_eq!(Outfit::new(vec![red, boots]), outfit);
+        assert_eq!(vec![Family::new("pants")], skipped);
+    }
+
+    #[test]
+    fn satisfiable_selection_matches_complete_outfit() {
+        let closet = closet();
+        let sneakers = Item::new("shoes:sneakers");
+
+        let (outfit, skipped) = closet.complete_outfit_best_effort(vec![sneakers.clone()]);
+
+        assert_eq!(closet.complete_outfit(vec![sneakers]), Ok(outfit));
+        assert_eq!(Vec::<Family>::new(), skipped);
+    }
+
+    #[test]
+    fn unknown_and_conflicting_selections_are_ignored() {
+        let red = Item::new("shirts:red");
+        let blue = Item::new("shirts:blue");
+        let jeans = Item::new("pants:jeans");
+        let boots = Item::new("shoes:boots");
+
+        let (outfit, skipped) = closet().complete_outfit_best_effort(vec![
+            blue.clone(),
+            Item::new("hats:fedora"),
+            red.clone(),
+            jeans.clone(),
+        ]);
+
+        assert_eq!(Outfit::new(vec![jeans, blue, boots]), outfit);
+        assert_eq!(Vec::<Family>::new(), skipped);
+    }
+
+    #[test]
+    fn closet_without_rules_skips_nothing() {
+        let built = closet();
+        let closet = Closet::new(built.item_index().clone(), built.root().clone());
+
+        let (outfit, skipped) = closet.complete_outfit_best_effort(vec![Item::new("shirts:red")]);
+
+        assert_eq!(closet.complete_outfit(vec![]), Ok(outfit));
+        assert_eq!(Vec::<Family>::new(), skipped);
+    }
+
+    #[test]
+    fn family_of_several_items_is_filled_to_its_cardinality() {
+        let accessories = Family::new("accessories");
+        let hat = Item::new("accessories:hat");
+        let scarf = Item::new("accessories:scarf");
+        let watch = Item::new("accessories:watch");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&accessories, &[hat.clone(), scarf.clone(), watch.clone()])
+            .add_items(&Family::new("shirts"), &[Item::new("shirts:blue"), Item::new("shirts:red")])
+            .set_family_cardinality(&accessories, CardinalitySpec::Exactly(2))
+            .add_exclusion_rule(&hat, &scarf)
+            .must_build();
+
+        let (outfit, skipped) = closet.complete_outfit_best_effort(vec![hat.clone()]);
+
+        assert_eq!(Outfit::new(vec![hat.clone(), watch.clone(), Item::new("shirts:blue")]), outfit);
+        assert!(closet.is_valid_outfit(&outfit));
+        assert_eq!(Vec::<Family>::new(), skipped);
+
+        let (outfit, _) = closet.complete_outfit_best_effort(vec![hat.clone(), watch.clone(), scarf]);
+        assert_eq!(Outfit::new(vec![hat, watch, Item::new("shirts:blue")]), outfit);
+    }
+}
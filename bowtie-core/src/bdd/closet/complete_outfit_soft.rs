@@ -0,0 +1,221 @@
+use bdd::closet::Closet;
+use bdd::closet::complete_outfit::outfit;
+use bdd::closet::complete_outfit::validate;
+use bdd::node::Node;
+use bdd::node::NodeId;
+use core::Item;
+use core::Outfit;
+use core::OutfitError;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+
+impl Closet {
+    /// Like `complete_outfit`, but among the valid completions picks the one breaking soft exclusions
+    /// with the lowest total penalty. Ties fall back to the default completion.
+    pub fn complete_outfit_soft(&self, selections: Vec<Item>) -> Result<Outfit, OutfitError> {
+        if self.soft_exclusions.is_empty() {
+            return self.complete_outfit(selections);
+        }
+        validate(self, &selections)?;
+
+        let assignments: BTreeMap<Item, bool> = selections.iter()
+            .map(|selection| (selection.clone(), true))
+            .collect();
+        let mut root: Node = self.root().restrict_all(&assignments);
+
+        let penalties = Penalties::new(self);
+        let mut worn = selections.iter()
+            .fold(BTreeSet::new(), |worn, selection| penalties.wear(worn, selection));
+
+        let mut cache = HashMap::new();
+        let mut outfit_items = selections;
+        while let Node::Branch(id, low, high) = root {
+            let high = Node::from(high);
+            let low = Node::from(low);
+
+            let worn_high = penalties.wear(worn.clone(), &id);
+            let high_penalty = penalties.min_penalty(&high, &worn_high, &mut cache)
+                .map(|penalty| penalty + penalties.penalty(&id, &worn));
+            let low_penalty = penalties.min_penalty(&low, &worn, &mut cache);
+
+            let take_high = match (high_penalty, low_penalty) {
+                (Some(high_penalty), Some(low_penalty)) => high_penalty <= low_penalty,
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+
+            if take_high {
+                outfit_items.push(id);
+                worn = worn_high;
+                root = high;
+            } else {
+                root = low;
+            }
+        }
+
+        Ok(outfit(self, outfit_items))
+    }
+}
+
+/// The soft exclusions by item, each listing the items it is penalized alongside.
+struct Penalties {
+    partners: HashMap<Item, Vec<(Item, i64)>>,
+}
+
+impl Penalties {
+    fn new(closet: &Closet) -> Penalties {
+        let mut partners = HashMap::new();
+        for (selection, exclusion, penalty) in &closet.soft_exclusions {
+            partners.entry(selection.clone()).or_insert_with(Vec::new).push((exclusion.clone(), *penalty));
+            partners.entry(exclusion.clone()).or_insert_with(Vec::new).push((selection.clone(), *penalty));
+        }
+
+        Penalties { partners }
+    }
+
+    /// Adds `item` to the worn items, which only keep the items some soft exclusion mentions.
+    fn wear(&self, mut worn: BTreeSet<Item>, item: &Item) -> BTreeSet<Item> {
+        if self.partners.contains_key(item) {
+            worn.insert(item.clone());
+        }
+        worn
+    }
+
+    /// The penalty of wearing `item` alongside the `worn` items.
+    fn penalty(&self, item: &Item, worn: &BTreeSet<Item>) -> i64 {
+        self.partners.get(item)
+            .map(|partners| partners.iter()
+                .filter(|(partner, _)| worn.contains(partner))
+                .map(|(_, penalty)| penalty)
+                .sum())
+            .unwrap_or(0)
+    }
+
+    /// The lowest penalty of completing below `node` once the `worn` items are on, or `None` if nothing completes.
+    fn min_penalty(&self, node: &Node, worn: &BTreeSet<Item>, cache: &mut HashMap<(NodeId, BTreeSet<Item>), Option<i64>>) -> Option<i64> {
+        match node {
+            Node::Leaf(true) => Some(0),
+            Node::Leaf(false) => None,
+            Node::Branch(id, low, high) => {
+                let key = (NodeId::from(node), worn.clone());
+                if let Some(penalty) = cache.get(&key) {
+                    return *penalty;
+                }
+
+                let worn_high = self.wear(worn.clone(), id);
+                let high_penalty = self.min_penalty(&Node::from(high), &worn_high, cache)
+                    .map(|penalty| penalty + self.penalty(id, worn));
+                let low_penalty = self.min_penalty(&Node::from(low), worn, cache);
+
+                let penalty = match (high_penalty, low_penalty) {
+                    (Some(high_penalty), Some(low_penalty)) => Some(high_penalty.min(low_penalty)),
+                    (high_penalty, low_penalty) => high_penalty.or(low_penalty),
+                };
+                cache.insert(key, penalty);
+                penalty
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet::Closet;
+    use bdd::closet_builder::ClosetBuilder;
+    use bdd::fixtures::shirts_and_pants;
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+    use core::OutfitError;
+
+    fn closet() -> Closet {
+        shirts_and_pants()
+            .add_exclusion_rule(&Item::new("shirts:red"), &Item::new("pants:jeans"))
+            .add_soft_exclusion(&Item::new("shirts:blue"), &Item::new("pants:jeans"), 5)
+            .must_build()
+    }
+
+    #[test]
+    fn soft_exclusion_is_honored_when_an_alternative_exists() {
+        let closet = closet();
+
+        assert_eq!(
+            Ok(Outfit::new(vec![Item::new("pants:jeans"), Item::new("shirts:blue")])),
+            closet.complete_outfit(vec![Item::new("shirts:blue")])
+        );
+        assert_eq!(
+            Ok(Outfit::new(vec![Item::new("pants:slacks"), Item::new("shirts:blue")])),
+            closet.complete_outfit_soft(vec![Item::new("shirts:blue")])
+        );
+    }
+
+    #[test]
+    fn soft_exclusion_is_broken_when_forced() {
+        assert_eq!(
+            Ok(Outfit::new(vec![Item::new("pants:jeans"), Item::new("shirts:blue")])),
+            closet().complete_outfit_soft(vec![Item::new("pants:jeans")])
+        );
+    }
+
+    #[test]
+    fn lowest_total_penalty_wins() {
+        let closet = ClosetBuilder::new()
+            .add_items(&Family::new("shirts"), &[Item::new("shirts:blue"), Item::new("shirts:red")])
+            .add_items(&Family::new("pants"), &[Item::new("pants:jeans"), Item::new("pants:slacks")])
+            .add_soft_exclusion(&Item::new("shirts:blue"), &Item::new("pants:jeans"), 5)
+            .add_soft_exclusion(&Item::new("shirts:blue"), &Item::new("pants:slacks"), 2)
+            .add_soft_exclusion(&Item::new("shirts:red"), &Item::new("pants:jeans"), 3)
+            .add_soft_exclusion(&Item::new("shirts:red"), &Item::new("pants:slacks"), 4)
+            .must_build();
+
+        assert_eq!(
+            Ok(Outfit::new(vec![Item::new("pants:slacks"), Item::new("shirts:blue")])),
+            closet.complete_outfit_soft(vec![])
+        );
+    }
+
+    #[test]
+    fn penalties_across_families_are_minimized_together() {
+        let items = |family: &str| (1..4)
+            .map(|index| Item::new(format!("{}:{}", family, index)))
+            .collect::<Vec<_>>();
+        let (hats, pants, shirts) = (items("hats"), items("pants"), items("shirts"));
+
+        let soft_exclusions = [
+            (hats[0].clone(), pants[0].clone(), 4),
+            (hats[0].clone(), shirts[0].clone(), 4),
+            (pants[0].clone(), shirts[0].clone(), 4),
+            (hats[1].clone(), shirts[2].clone(), 3),
+            (pants[1].clone(), shirts[2].clone(), 1),
+            (hats[2].clone(), pants[2].clone(), 2),
+            (pants[2].clone(), shirts[1].clone(), 6),
+        ];
+        let closet = soft_exclusions.iter()
+            .fold(ClosetBuilder::new(), |closet_builder, (selection, exclusion, penalty)| closet_builder.add_soft_exclusion(selection, exclusion, *penalty))
+            .add_items(&Family::new("hats"), &hats)
+            .add_items(&Family::new("pants"), &pants)
+            .add_items(&Family::new("shirts"), &shirts)
+            .must_build();
+        let penalty = |outfit: &Outfit| soft_exclusions.iter()
+            .filter(|(selection, exclusion, _)| outfit.contains(selection) && outfit.contains(exclusion))
+            .map(|(_, _, penalty)| penalty)
+            .sum::<i64>();
+
+        for selections in [vec![], vec![hats[0].clone()], vec![shirts[2].clone()], vec![pants[2].clone()]] {
+            let lowest = closet.completions(selections.clone()).unwrap()
+                .map(|completion| penalty(&completion))
+                .min();
+
+            assert_eq!(lowest, closet.complete_outfit_soft(selections).ok().map(|outfit| penalty(&outfit)));
+        }
+    }
+
+    #[test]
+    fn hard_rules_are_still_enforced() {
+        assert_eq!(
+            Err(OutfitError::IncompatibleSelections(vec![Item::new("pants:jeans"), Item::new("shirts:red")])),
+            closet().complete_outfit_soft(vec![Item::new("shirts:red"), Item::new("pants:jeans")])
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use bdd::closet::Closet;
+use bdd::closet::complete_outfit::outfit;
+use bdd::closet::complete_outfit::validate;
+use bdd::node::Node;
+use core::Item;
+use core::Outfit;
+use core::OutfitError;
+use std::collections::BTreeMap;
+
+impl Closet {
+    /// Lazily yields every outfit that completes the selections, starting with the one `complete_outfit` returns.
+    /// Items the diagram skips are yielded both worn and not.
+    pub fn completions<'a>(&'a self, selections: Vec<Item>) -> Result<impl Iterator<Item=Outfit> + 'a, OutfitError> {
+        validate(self, &selections)?;
+
+        let assignments: BTreeMap<Item, bool> = selections.iter()
+            .map(|selection| (selection.clone(), true))
+            .collect();
+        let root = self.root().restrict_all(&assignments);
+        let items = self.items_in_diagram_order().into_iter()
+            .filter(|item| !assignments.contains_key(item))
+            .cloned()
+            .collect();
+
+        Ok(Completions { closet: self, items, stack: vec![(root, 0, selections)] })
+    }
+}
+
+struct Completions<'a> {
+    closet: &'a Closet,
+    /// The unselected items, in the order the diagram tests them
+    items: Vec<Item>,
+    stack: Vec<(Node, usize, Vec<Item>)>,
+}
+
+impl<'a> Iterator for Completions<'a> {
+    type Item = Outfit;
+
+    fn next(&mut self) -> Option<Outfit> {
+        while let Some((node, level, mut outfit_items)) = self.stack.pop() {
+            if node == Node::FALSE_LEAF {
+                continue;
+            }
+            if level == self.items.len() {
+                return Some(outfit(self.closet, outfit_items));
+            }
+
+            let item = &self.items[level];
+            match node.clone() {
+                Node::Branch(id, low, high) if id == *item => {
+                    self.stack.push((Node::from(low), level + 1, outfit_items.clone()));
+
+                    outfit_items.push(id);
+                    self.stack.push((Node::from(high), level + 1, outfit_items));
+                }
+                _ => {
+                    // Without the skipped item first, as `complete_outfit` leaves it out.
+                    let mut with_item = outfit_items.clone();
+                    with_item.push(item.clone());
+                    self.stack.push((node.clone(), level + 1, with_item));
+
+                    self.stack.push((node, level + 1, outfit_items));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet_builder::ClosetBuilder;
+    use core::CardinalitySpec;
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+    use core::OutfitError;
+
+    #[test]
+    fn completions_yield_every_outfit_in_order() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &slacks)
+            .add_item(&pants, &jeans)
+            .add_exclusion_rule(&red, &jeans)
+            .must_build();
+
+        let first_two = closet.completions(vec![]).unwrap()
+            .take(2)
+            .collect::<Vec<Outfit>>();
+        assert_eq!(
+            vec![
+                Outfit::new(vec![jeans.clone(), blue.clone()]),
+                Outfit::new(vec![slacks.clone(), blue.clone()]),
+            ],
+            first_two
+        );
+        assert_eq!(Ok(first_two[0].clone()), closet.complete_outfit(vec![]));
+
+        let all = closet.completions(vec![]).unwrap().collect::<Vec<Outfit>>();
+        assert_eq!(closet.outfit_count(), all.len() as u64);
+        assert!(all.contains(&Outfit::new(vec![slacks.clone(), red.clone()])));
+    }
+
+    #[test]
+    fn completions_respect_selections() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &slacks)
+            .add_item(&pants, &jeans)
+            .must_build();
+
+        let completions = closet.completions(vec![red.clone()]).unwrap().collect::<Vec<Outfit>>();
+        assert_eq!(
+            vec![
+                Outfit::new(vec![jeans.clone(), red.clone()]),
+                Outfit::new(vec![slacks.clone(), red.clone()]),
+            ],
+            completions
+        );
+
+        let hat = Item::new("hats:fedora");
+        assert_eq!(
+            Some(OutfitError::UnknownItems(vec![hat.clone()])),
+            closet.completions(vec![hat]).err()
+        );
+    }
+
+    #[test]
+    fn completions_include_items_the_diagram_skips() {
+        let shirts = Family::new("shirts");
+        let hats = Family::new("hats");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&shirts, &[Item::new("shirts:blue"), Item::new("shirts:red")])
+            .add_items(&hats, &[Item::new("hats:cap"), Item::new("hats:fedora")])
+            .set_family_cardinality(&hats, CardinalitySpec::Range(0, 2))
+            .must_build();
+
+        let completions = closet.completions(vec![]).unwrap().collect::<Vec<Outfit>>();
+
+        assert_eq!(closet.outfit_count(), completions.len() as u64);
+        assert_eq!(8, completions.len());
+        assert_eq!(Ok(completions[0].clone()), closet.complete_outfit(vec![]));
+        assert!(completions.contains(&Outfit::new(vec![Item::new("hats:cap"), Item::new("hats:fedora"), Item::new("shirts:red")])));
+    }
+}
@@ -0,0 +1,187 @@
+use bdd::closet::Closet;
+use bdd::closet::complete_outfit::outfit;
+use bdd::node::BoolOp;
+use bdd::node::Node;
+use core::DiffError;
+use core::Item;
+use core::Outfit;
+use std::collections::BTreeSet;
+
+impl Closet {
+    /// Returns the complete outfits that `other` adds and removes relative to this closet.
+    /// Both closets must have the same families, though the items within them may differ.
+    /// When the closets test items in different orders, `other` is compared in this closet's order.
+    pub fn diff(&self, other: &Closet) -> Result<(Vec<Outfit>, Vec<Outfit>), DiffError> {
+        let families = self.families();
+        let other_families = other.families();
+        if families != other_families {
+            return Err(DiffError::MismatchedFamilies(
+                families.into_iter().cloned().collect(),
+                other_families.into_iter().cloned().collect(),
+            ));
+        }
+
+        let reordered;
+        let other = if other.order == self.order {
+            other
+        } else {
+            reordered = other.in_order(&self.order);
+            &reordered
+        };
+
+        let mut items = self.items().into_iter()
+            .chain(other.items())
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        items.sort_by(|item, other| self.order.compare(item, other));
+
+        let added = outfits(other, &difference(other, self), &items);
+        let removed = outfits(self, &difference(self, other), &items);
+
+        Ok((added, removed))
+    }
+}
+
+/// Outfits of `closet` that `other` doesn't allow, with items only `other` knows left out.
+/// Both closets test items in the same order.
+fn difference(closet: &Closet, other: &Closet) -> Node {
+    let not_other = other.root().not();
+
+    other.items().into_iter()
+        .filter(|item| closet.get_family(item).is_none())
+        .fold(Node::apply_in(closet.root(), &not_other, BoolOp::And, &closet.order), |node, item| {
+            Node::apply_in(&node, &Node::negative_branch(item), BoolOp::And, &closet.order)
+        })
+}
+
+/// Every outfit over `items`, given in the order `root` tests them, that `root` allows, with items as `closet` stored them.
+/// Items the diagram skips are listed both worn and not.
+fn outfits(closet: &Closet, root: &Node, items: &[Item]) -> Vec<Outfit> {
+    let mut outfits = vec![];
+    let mut stack = vec![(root.clone(), 0, vec![])];
+
+    while let Some((node, level, mut outfit_items)) = stack.pop() {
+        if node == Node::FALSE_LEAF {
+            continue;
+        }
+        if level == items.len() {
+            outfits.push(outfit(closet, outfit_items));
+            continue;
+        }
+
+        let item = &items[level];
+        let (low, high) = match node.clone() {
+            Node::Branch(id, low, high) if id == *item => (Node::from(low), Node::from(high)),
+            _ => (node.clone(), node),
+        };
+
+        stack.push((low, level + 1, outfit_items.clone()));
+
+        outfit_items.push(item.clone());
+        stack.push((high, level + 1, outfit_items));
+    }
+
+    outfits
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet_builder::ClosetBuilder;
+    use bdd::fixtures::shirts_and_pants;
+    use core::CardinalitySpec;
+    use core::DiffError;
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+
+    #[test]
+    fn exclusion_rule_removes_outfit() {
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+
+        let closet = shirts_and_pants().must_build();
+        let updated = shirts_and_pants()
+            .add_exclusion_rule(&red, &jeans)
+            .must_build();
+
+        assert_eq!(
+            Ok((vec![], vec![Outfit::new(vec![jeans.clone(), red.clone()])])),
+            closet.diff(&updated)
+        );
+        assert_eq!(
+            Ok((vec![Outfit::new(vec![jeans, red])], vec![])),
+            updated.diff(&closet)
+        );
+        assert_eq!(Ok((vec![], vec![])), closet.diff(&closet));
+    }
+
+    #[test]
+    fn new_item_adds_outfits() {
+        let grey = Item::new("shirts:grey");
+
+        let closet = shirts_and_pants().must_build();
+        let updated = shirts_and_pants()
+            .add_item(&Family::new("shirts"), &grey)
+            .must_build();
+
+        assert_eq!(
+            Ok((
+                vec![
+                    Outfit::new(vec![Item::new("pants:jeans"), grey.clone()]),
+                    Outfit::new(vec![Item::new("pants:slacks"), grey]),
+                ],
+                vec![]
+            )),
+            closet.diff(&updated)
+        );
+    }
+
+    #[test]
+    fn outfits_differing_in_untested_items_are_all_listed() {
+        let hats = Family::new("hats");
+        let cap = Item::new("hats:cap");
+        let fedora = Item::new("hats:fedora");
+        let red = Item::new("shirts:red");
+
+        let with_hats = || ClosetBuilder::new()
+            .add_items(&Family::new("shirts"), &[Item::new("shirts:blue"), red.clone()])
+            .add_items(&hats, &[cap.clone(), fedora.clone()])
+            .set_family_cardinality(&hats, CardinalitySpec::Range(0, 2));
+
+        let closet = with_hats().must_build();
+        let updated = with_hats()
+            .add_exclusion_rule(&red, &cap)
+            .must_build();
+
+        assert_eq!(
+            Ok((
+                vec![],
+                vec![
+                    Outfit::new(vec![cap.clone(), fedora, red.clone()]),
+                    Outfit::new(vec![cap, red]),
+                ]
+            )),
+            closet.diff(&updated)
+        );
+    }
+
+    #[test]
+    fn mismatched_families_are_rejected() {
+        let shoes = Family::new("shoes");
+
+        let closet = shirts_and_pants().must_build();
+        let updated = shirts_and_pants()
+            .add_item(&shoes, &Item::new("shoes:boots"))
+            .must_build();
+
+        assert_eq!(
+            Err(DiffError::MismatchedFamilies(
+                vec![Family::new("pants"), Family::new("shirts")],
+                vec![Family::new("pants"), Family::new("shirts"), shoes],
+            )),
+            closet.diff(&updated)
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use bdd::closet::Closet;
+use bdd::closet::complete_outfit::outfit;
+use bdd::closet::complete_outfit::validate;
+use bdd::node::Node;
+use core::BranchDecision;
+use core::DecisionReason;
+use core::Item;
+use core::Outfit;
+use core::OutfitError;
+use std::collections::BTreeMap;
+
+impl Closet {
+    /// Completes the outfit like `complete_outfit`, also returning why each item was chosen.
+    /// The selections come first, in the order given, followed by the items the walk picked in variable order.
+    pub fn explain_outfit(&self, selections: Vec<Item>) -> Result<(Outfit, Vec<BranchDecision>), OutfitError> {
+        validate(self, &selections)?;
+
+        let assignments: BTreeMap<Item, bool> = selections.iter()
+            .map(|selection| (selection.clone(), true))
+            .collect();
+        let mut root: Node = self.root().restrict_all(&assignments);
+
+        let mut decisions = selections.iter()
+            .map(|selection| BranchDecision::new(selection.clone(), DecisionReason::Selected))
+            .collect::<Vec<_>>();
+
+        let mut outfit_items = selections;
+        while let Node::Branch(id, low, high) = root {
+            let high = Node::from(high);
+            let low = Node::from(low);
+
+            if high == Node::FALSE_LEAF {
+                root = low;
+                continue;
+            }
+
+            let reason = match low {
+                Node::Leaf(false) => DecisionReason::OnlyOption,
+                _ => DecisionReason::Default,
+            };
+            decisions.push(BranchDecision::new(id.clone(), reason));
+            outfit_items.push(id);
+            root = high;
+        }
+
+        Ok((outfit(self, outfit_items), decisions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet::Closet;
+    use bdd::fixtures::shirts_and_pants;
+    use core::BranchDecision;
+    use core::DecisionReason;
+    use core::Item;
+    use core::OutfitError;
+
+    fn closet() -> Closet {
+        shirts_and_pants()
+            .add_exclusion_rule(&Item::new("shirts:red"), &Item::new("pants:jeans"))
+            .must_build()
+    }
+
+    #[test]
+    fn excluded_item_leaves_only_option() {
+        let red = Item::new("shirts:red");
+        let slacks = Item::new("pants:slacks");
+
+        let closet = closet();
+        let (outfit, decisions) = closet.explain_outfit(vec![red.clone()]).unwrap();
+
+        assert_eq!(closet.complete_outfit(vec![red.clone()]).unwrap(), outfit);
+        assert_eq!(
+            vec![
+                BranchDecision::new(red, DecisionReason::Selected),
+                BranchDecision::new(slacks, DecisionReason::OnlyOption),
+            ],
+            decisions
+        );
+    }
+
+    #[test]
+    fn unconstrained_items_are_taken_by_default() {
+        let blue = Item::new("shirts:blue");
+        let jeans = Item::new("pants:jeans");
+
+        let closet = closet();
+        let (outfit, decisions) = closet.explain_outfit(vec![]).unwrap();
+
+        assert_eq!(closet.complete_outfit(vec![]).unwrap(), outfit);
+        assert_eq!(
+            vec![
+                BranchDecision::new(jeans, DecisionReason::Default),
+                BranchDecision::new(blue, DecisionReason::OnlyOption),
+            ],
+            decisions
+        );
+    }
+
+    #[test]
+    fn invalid_selections_are_rejected() {
+        let closet = closet();
+
+        assert_eq!(
+            Err(OutfitError::IncompatibleSelections(vec![Item::new("pants:jeans"), Item::new("shirts:red")])),
+            closet.explain_outfit(vec![Item::new("shirts:red"), Item::new("pants:jeans")])
+        );
+    }
+}
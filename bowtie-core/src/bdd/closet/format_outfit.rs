@@ -0,0 +1,71 @@
+use bdd::closet::Closet;
+use core::Outfit;
+use itertools::Itertools;
+
+impl Closet {
+    /// Renders an outfit as `family: item` pairs, sorted by family.
+    /// Items unknown to this closet are listed last without a family.
+    pub fn format_outfit(&self, outfit: &Outfit) -> String {
+        outfit.items().iter()
+            .map(|item| (self.get_family(item), item))
+            .sorted_by(|(family_1, item_1), (family_2, item_2)| {
+                family_1.is_none().cmp(&family_2.is_none())
+                    .then_with(|| family_1.cmp(family_2))
+                    .then_with(|| item_1.cmp(item_2))
+            })
+            .map(|(family, item)| match family {
+                Some(family) => format!("{}: {}", String::from(family.clone()), String::from(item.clone())),
+                None => String::from(item.clone()),
+            })
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet_builder::ClosetBuilder;
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+
+    #[test]
+    fn format_outfit_sorts_items_by_family() {
+        let blue = Item::new("blue");
+        let red = Item::new("red");
+
+        let jeans = Item::new("jeans");
+        let slacks = Item::new("slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &slacks)
+            .add_item(&pants, &jeans)
+            .must_build();
+
+        let outfit = Outfit::new(vec![blue.clone(), jeans.clone()]);
+        assert_eq!("pants: jeans, shirts: blue", closet.format_outfit(&outfit));
+
+        let outfit = Outfit::new(vec![Item::new("hat"), blue.clone()]);
+        assert_eq!("shirts: blue, hat", closet.format_outfit(&outfit));
+
+        assert_eq!("", closet.format_outfit(&Outfit::new(vec![])));
+    }
+
+    #[test]
+    fn format_outfit_prints_scoped_items_by_name() {
+        let shirts = Family::new("shirts");
+        let shoes = Family::new("shoes");
+
+        let closet = ClosetBuilder::new()
+            .add_scoped_item(&shirts, &Item::new("black"))
+            .add_scoped_item(&shoes, &Item::new("black"))
+            .must_build();
+
+        let outfit = Outfit::new(vec![Item::scoped(&shirts, "black"), Item::scoped(&shoes, "black")]);
+        assert_eq!("shirts: black, shoes: black", closet.format_outfit(&outfit));
+    }
+}
@@ -0,0 +1,148 @@
+use bdd::closet::Closet;
+use bdd::node::Node;
+use core::Item;
+use core::Outfit;
+use std::collections::BTreeMap;
+
+impl Closet {
+    /// Checks whether the outfit is a full valid assignment: its items worn, every other item left out.
+    pub fn is_valid_outfit(&self, outfit: &Outfit) -> bool {
+        if outfit.items().iter().any(|item| self.get_family(item).is_none()) {
+            return false;
+        }
+
+        let assignments: BTreeMap<Item, bool> = self.item_index.keys()
+            .map(|item| (item.clone(), outfit.contains(item)))
+            .collect();
+
+        self.root.restrict_all(&assignments) == Node::TRUE_LEAF
+    }
+
+    /// Whether `selections` already make up a whole outfit: no item is selected twice, and once the
+    /// selections are worn the diagram forces every other item out, leaving nothing left to choose.
+    pub fn is_complete(&self, selections: &[Item]) -> bool {
+        if selections.iter().any(|item| self.get_family(item).is_none()) {
+            return false;
+        }
+
+        let assignments: BTreeMap<Item, bool> = selections.iter()
+            .map(|item| (item.clone(), true))
+            .collect();
+        if assignments.len() != selections.len() {
+            return false;
+        }
+
+        let remaining = self.items_in_diagram_order().into_iter()
+            .filter(|item| !assignments.contains_key(item));
+
+        let mut node = self.root.restrict_all(&assignments);
+        for item in remaining {
+            node = match node {
+                Node::Branch(id, low, high) if id == *item && Node::from(high) == Node::FALSE_LEAF => Node::from(low),
+                _ => return false,
+            };
+        }
+
+        node == Node::TRUE_LEAF
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet::Closet;
+    use bdd::closet_builder::ClosetBuilder;
+    use bdd::fixtures::shirts_and_pants;
+    use core::CardinalitySpec;
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+
+    fn closet() -> Closet {
+        shirts_and_pants()
+            .add_exclusion_rule(&Item::new("shirts:red"), &Item::new("pants:jeans"))
+            .must_build()
+    }
+
+    #[test]
+    fn outfit_with_one_item_per_family_is_valid() {
+        let outfit = Outfit::new(vec![Item::new("pants:jeans"), Item::new("shirts:blue")]);
+
+        assert!(closet().is_valid_outfit(&outfit));
+    }
+
+    #[test]
+    fn outfit_missing_a_family_is_invalid() {
+        let outfit = Outfit::new(vec![Item::new("shirts:blue")]);
+
+        assert!(!closet().is_valid_outfit(&outfit));
+    }
+
+    #[test]
+    fn outfit_with_two_items_in_a_family_is_invalid() {
+        let outfit = Outfit::new(vec![Item::new("pants:slacks"), Item::new("shirts:blue"), Item::new("shirts:red")]);
+
+        assert!(!closet().is_valid_outfit(&outfit));
+    }
+
+    #[test]
+    fn outfit_violating_exclusion_rule_is_invalid() {
+        let outfit = Outfit::new(vec![Item::new("pants:jeans"), Item::new("shirts:red")]);
+
+        assert!(!closet().is_valid_outfit(&outfit));
+    }
+
+    #[test]
+    fn outfit_with_unknown_item_is_invalid() {
+        let outfit = Outfit::new(vec![Item::new("pants:jeans"), Item::new("shirts:blue"), Item::new("hats:fedora")]);
+
+        assert!(!closet().is_valid_outfit(&outfit));
+    }
+
+    #[test]
+    fn fully_specified_valid_selection_is_complete() {
+        assert!(closet().is_complete(&[Item::new("shirts:blue"), Item::new("pants:jeans")]));
+    }
+
+    #[test]
+    fn partial_selection_is_not_complete() {
+        assert!(!closet().is_complete(&[Item::new("shirts:blue")]));
+    }
+
+    #[test]
+    fn conflicting_full_selection_is_not_complete() {
+        assert!(!closet().is_complete(&[Item::new("shirts:red"), Item::new("pants:jeans")]));
+    }
+
+    #[test]
+    fn selection_leaving_an_optional_family_open_is_not_complete() {
+        let hats = Family::new("hats");
+        let closet = ClosetBuilder::new()
+            .add_items(&Family::new("shirts"), &[Item::new("shirts:blue"), Item::new("shirts:red")])
+            .add_items(&hats, &[Item::new("hats:cap")])
+            .set_family_cardinality(&hats, CardinalitySpec::Range(0, 1))
+            .must_build();
+
+        assert!(!closet.is_complete(&[Item::new("shirts:blue")]));
+        assert!(closet.is_complete(&[Item::new("hats:cap"), Item::new("shirts:blue")]));
+    }
+
+    #[test]
+    fn selection_whose_rules_force_the_rest_out_is_complete() {
+        let hats = Family::new("hats");
+        let cap = Item::new("hats:cap");
+        let blue = Item::new("shirts:blue");
+        let closet = ClosetBuilder::new()
+            .add_items(&Family::new("shirts"), &[blue.clone(), Item::new("shirts:red")])
+            .add_item(&hats, &cap)
+            .set_family_cardinality(&hats, CardinalitySpec::Range(0, 1))
+            .add_exclusion_rule(&blue, &cap)
+            .must_build();
+
+        assert!(closet.is_complete(&[blue]));
+    }
+
+    #[test]
+    fn repeated_selection_is_not_complete() {
+        assert!(!closet().is_complete(&[Item::new("shirts:blue"), Item::new("pants:jeans"), Item::new("pants:jeans")]));
+    }
+}
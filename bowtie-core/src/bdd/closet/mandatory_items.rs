@@ -0,0 +1,80 @@
+use bdd::closet::Closet;
+use bdd::node::Node;
+use core::Item;
+
+impl Closet {
+    /// Items that are part of every valid outfit, such as the only item in a family
+    /// or an item forced by inclusion rules.
+    /// A closet without any valid outfit has none.
+    pub fn mandatory_items(&self) -> Vec<Item> {
+        if self.root == Node::FALSE_LEAF {
+            return Vec::new();
+        }
+
+        self.item_index.keys()
+            .filter(|item| Node::restrict(&self.root, item, false) == Node::FALSE_LEAF)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet::Closet;
+    use bdd::closet_builder::ClosetBuilder;
+    use bdd::node::Node;
+    use core::Family;
+    use core::Item;
+
+    #[test]
+    fn single_item_family_and_included_item_are_mandatory() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let belt = Item::new("belts:black");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+        let belts = Family::new("belts");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &blue)
+            .add_item(&shirts, &red)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .add_item(&belts, &belt)
+            .add_inclusion_rule(&blue, &jeans)
+            .add_inclusion_rule(&red, &jeans)
+            .must_build();
+
+        assert_eq!(vec![belt, jeans], closet.mandatory_items());
+    }
+
+    #[test]
+    fn closet_without_rules_has_no_mandatory_items() {
+        let shirts = Family::new("shirts");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &Item::new("shirts:blue"))
+            .add_item(&shirts, &Item::new("shirts:red"))
+            .must_build();
+
+        assert_eq!(Vec::<Item>::new(), closet.mandatory_items());
+    }
+
+    #[test]
+    fn unsatisfiable_closet_has_no_mandatory_items() {
+        let shirts = Family::new("shirts");
+
+        let built = ClosetBuilder::new()
+            .add_item(&shirts, &Item::new("shirts:blue"))
+            .add_item(&shirts, &Item::new("shirts:red"))
+            .must_build();
+        let closet = Closet::new(built.item_index().clone(), Node::FALSE_LEAF);
+
+        assert_eq!(Vec::<Item>::new(), closet.mandatory_items());
+    }
+}
@@ -1,19 +1,132 @@
+use bdd::closet_builder::ClosetBuilder;
 use bdd::node::Node;
+use bdd::node::VariableOrder;
+use core::CardinalitySpec;
 use core::Family;
 use core::Item;
 use core::ItemStatus;
+use itertools::Itertools;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
 use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::hash::Hasher;
 
 mod categorize;
+mod compatible_items;
+mod completions;
+mod diff;
 mod complete_outfit;
+mod complete_outfit_best_effort;
+mod complete_outfit_soft;
+mod explain_outfit;
+mod format_outfit;
+mod is_valid_outfit;
+mod mandatory_items;
+mod neighbors;
 mod node_count;
+mod outfit_cost;
+mod reconcile_selections;
+mod recomplete;
+mod restrict_to;
+mod sample_outfits;
 mod select;
+mod truth_table;
+mod unreachable_items;
+mod variable_order;
+mod with_item_added;
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "StoredCloset")]
 pub struct Closet {
+    #[serde(serialize_with = "serialize_item_index")]
     item_index: BTreeMap<Item, Family>,
+    /// A hashed view of `item_index`, rebuilt rather than stored
+    #[serde(skip)]
+    family_map: HashMap<Item, Family>,
     summary: Vec<ItemStatus>,
     root: Node,
+    /// The rules without the family constraints, unknown for closets made by `Closet::new`
+    rules: Option<Node>,
+    cardinalities: BTreeMap<Family, CardinalitySpec>,
+    soft_exclusions: Vec<(Item, Item, i64)>,
+    /// The order `root` and `rules` test items in, natural unless the closet was reordered
+    #[serde(skip_serializing_if = "VariableOrder::is_natural")]
+    order: VariableOrder,
+}
+
+/// The stored form of a `Closet`, without the views that are derived from it on load
+#[derive(Deserialize)]
+struct StoredCloset {
+    #[serde(deserialize_with = "deserialize_item_index")]
+    item_index: BTreeMap<Item, Family>,
+    summary: Vec<ItemStatus>,
+    root: Node,
+    #[serde(default)]
+    rules: Option<Node>,
+    #[serde(default)]
+    cardinalities: BTreeMap<Family, CardinalitySpec>,
+    #[serde(default)]
+    soft_exclusions: Vec<(Item, Item, i64)>,
+    #[serde(default)]
+    order: VariableOrder,
+}
+
+/// Scoped items can't be map keys in formats like JSON, so an index holding any is stored as a list of pairs
+fn serialize_item_index<S: Serializer>(item_index: &BTreeMap<Item, Family>, serializer: S) -> Result<S::Ok, S::Error> {
+    if item_index.keys().any(|item| item.scope().is_some()) {
+        serializer.collect_seq(item_index.iter())
+    } else {
+        item_index.serialize(serializer)
+    }
+}
+
+fn deserialize_item_index<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BTreeMap<Item, Family>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StoredItemIndex {
+        Map(BTreeMap<Item, Family>),
+        Pairs(Vec<(Item, Family)>),
+    }
+
+    StoredItemIndex::deserialize(deserializer)
+        .map(|stored| match stored {
+            StoredItemIndex::Map(item_index) => item_index,
+            StoredItemIndex::Pairs(pairs) => pairs.into_iter().collect(),
+        })
+}
+
+impl From<StoredCloset> for Closet {
+    fn from(stored: StoredCloset) -> Self {
+        Closet {
+            family_map: family_map(&stored.item_index),
+            item_index: stored.item_index,
+            summary: stored.summary,
+            root: stored.root,
+            rules: stored.rules,
+            cardinalities: stored.cardinalities,
+            soft_exclusions: stored.soft_exclusions,
+            order: stored.order,
+        }
+    }
+}
+
+impl PartialEq for Closet {
+    fn eq(&self, other: &Closet) -> bool {
+        self.item_index == other.item_index && self.natural_root() == other.natural_root()
+    }
+}
+
+impl Eq for Closet {}
+
+impl Hash for Closet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.item_index.hash(state);
+        self.natural_root().hash(state);
+    }
 }
 
 impl Closet {
@@ -22,23 +135,362 @@ impl Closet {
         root: Node,
     ) -> Closet {
         Closet {
+            family_map: family_map(&item_index),
             item_index,
             summary: Node::summarize(&root),
+            rules: None,
             root,
+            cardinalities: BTreeMap::new(),
+            soft_exclusions: Vec::new(),
+            order: VariableOrder::default(),
         }
     }
 
+    /// Builds a closet with no rules from `(family, item)` pairs, so every outfit with one item per family is valid.
+    /// Panics if an item is listed under two families.
+    pub fn universal(items: &[(Family, Item)]) -> Closet {
+        ClosetBuilder::new()
+            .extend(items.iter().cloned())
+            .must_build()
+    }
+
+    pub(crate) fn with_rules(
+        item_index: BTreeMap<Item, Family>,
+        root: Node,
+        rules: Option<Node>,
+    ) -> Closet {
+        Closet {
+            family_map: family_map(&item_index),
+            item_index,
+            summary: Node::summarize(&root),
+            root,
+            rules,
+            cardinalities: BTreeMap::new(),
+            soft_exclusions: Vec::new(),
+            order: VariableOrder::default(),
+        }
+    }
+
+    pub(crate) fn with_cardinalities(mut self, cardinalities: BTreeMap<Family, CardinalitySpec>) -> Closet {
+        self.cardinalities = cardinalities;
+        self
+    }
+
+    pub(crate) fn with_soft_exclusions(mut self, soft_exclusions: Vec<(Item, Item, i64)>) -> Closet {
+        self.soft_exclusions = soft_exclusions;
+        self
+    }
+
+    pub(crate) fn with_order(mut self, order: VariableOrder) -> Closet {
+        self.order = order;
+        self
+    }
+
     pub fn root(&self) -> &Node {
         &self.root
     }
 
+    /// The order the diagram tests items in, see `reorder`.
+    pub fn variable_order(&self) -> &VariableOrder {
+        &self.order
+    }
+
     pub fn item_index(&self) -> &BTreeMap<Item, Family> {
         &self.item_index
     }
 
+    /// Hashed view of `item_index` for callers that look up many items at once.
+    pub fn family_map(&self) -> &HashMap<Item, Family> {
+        &self.family_map
+    }
+
     pub fn summary(&self) -> &Vec<ItemStatus> { &self.summary}
 
     pub fn get_family(&self, item: &Item) -> Option<&Family> {
         self.item_index.get(item)
     }
+
+    pub fn family_cardinality(&self, family: &Family) -> CardinalitySpec {
+        self.cardinalities.get(family).copied().unwrap_or_default()
+    }
+
+    pub fn families(&self) -> Vec<&Family> {
+        self.item_index.values()
+            .unique()
+            .sorted()
+            .collect()
+    }
+
+    pub fn items(&self) -> Vec<&Item> {
+        self.item_index.keys()
+            .collect()
+    }
+
+    /// The items in the order the diagram tests them, for walks that need to know which items a path skips.
+    fn items_in_diagram_order(&self) -> Vec<&Item> {
+        let mut items = self.items();
+        if !self.order.is_natural() {
+            items.sort_by(|item, other| self.order.compare(item, other));
+        }
+        items
+    }
+
+    /// The diagram rebuilt to test items in their natural order, so that reordered closets compare by their outfits.
+    fn natural_root(&self) -> Node {
+        if self.order.is_natural() {
+            return self.root.clone();
+        }
+        self.root.reordered(&VariableOrder::default())
+    }
+
+    /// Rebuilds `node`, which tests items in their natural order, to test them in the diagram's order.
+    fn in_diagram_order(&self, node: Node) -> Node {
+        if self.order.is_natural() {
+            return node;
+        }
+        node.reordered(&self.order)
+    }
+
+    pub fn items_in_family(&self, family: &Family) -> Vec<&Item> {
+        self.item_index.iter()
+            .filter(|&(_, item_family)| item_family == family)
+            .map(|(item, _)| item)
+            .collect()
+    }
+
+    /// How many items `family` has, or 0 for a family the closet doesn't know.
+    pub fn family_size(&self, family: &Family) -> usize {
+        self.item_index.values()
+            .filter(|item_family| *item_family == family)
+            .count()
+    }
+
+    /// Every item of the closet keyed by its family. Families and the items in each are sorted.
+    pub fn grouped(&self) -> BTreeMap<Family, Vec<Item>> {
+        self.item_index.iter()
+            .fold(BTreeMap::new(), |mut grouped: BTreeMap<Family, Vec<Item>>, (item, family)| {
+                grouped.entry(family.clone()).or_default().push(item.clone());
+                grouped
+            })
+    }
+}
+
+fn family_map(item_index: &BTreeMap<Item, Family>) -> HashMap<Item, Family> {
+    item_index.iter()
+        .map(|(item, family)| (item.clone(), family.clone()))
+        .collect()
+}
+
+/// The closet's items in the order the diagram tests them, so that walks can find the items a path skips.
+/// The rules don't constrain an item a path skips, so it may be worn or not and either way makes an outfit.
+struct Levels {
+    items: Vec<Item>,
+    index: HashMap<Item, usize>,
+}
+
+impl Levels {
+    fn new(closet: &Closet) -> Levels {
+        let items = closet.items_in_diagram_order().into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        let index = items.iter()
+            .enumerate()
+            .map(|(level, item)| (item.clone(), level))
+            .collect();
+
+        Levels { items, index }
+    }
+
+    /// The level `node` tests its item at, with leaves below the last item.
+    fn level(&self, node: &Node) -> usize {
+        match node {
+            Node::Leaf(_) => self.items.len(),
+            Node::Branch(id, _, _) => self.index[id],
+        }
+    }
+
+    /// The items a path skips between `level` and `node`.
+    fn skipped(&self, level: usize, node: &Node) -> &[Item] {
+        &self.items[level..self.level(node)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet::Closet;
+    use bdd::closet_builder::ClosetBuilder;
+    use core::Family;
+    use core::Item;
+    use std::collections::BTreeMap;
+    use std::collections::HashSet;
+
+    #[test]
+    fn families_items_and_items_in_family_are_sorted() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+        let shoes = Family::new("shoes");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &slacks)
+            .add_item(&pants, &jeans);
+
+        let closet = closet_builder.must_build();
+
+        assert_eq!(vec![&pants, &shirts], closet.families());
+        assert_eq!(vec![&jeans, &slacks, &blue, &red], closet.items());
+        assert_eq!(vec![&blue, &red], closet.items_in_family(&shirts));
+        assert_eq!(vec![&jeans, &slacks], closet.items_in_family(&pants));
+        assert_eq!(Vec::<&Item>::new(), closet.items_in_family(&shoes));
+    }
+
+    #[test]
+    fn family_size_counts_items_in_family() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&shirts, &[Item::new("shirts:blue"), Item::new("shirts:red"), Item::new("shirts:grey")])
+            .add_items(&pants, &[Item::new("pants:jeans"), Item::new("pants:slacks")])
+            .must_build();
+
+        assert_eq!(3, closet.family_size(&shirts));
+        assert_eq!(2, closet.family_size(&pants));
+        assert_eq!(0, closet.family_size(&Family::new("shoes")));
+    }
+
+    #[test]
+    fn family_map_matches_item_index() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&shirts, &[Item::new("shirts:blue"), Item::new("shirts:red")])
+            .add_items(&pants, &[Item::new("pants:jeans")])
+            .must_build();
+
+        assert_eq!(closet.item_index().len(), closet.family_map().len());
+        for (item, family) in closet.item_index() {
+            assert_eq!(Some(family), closet.family_map().get(item));
+        }
+        assert_eq!(None, closet.family_map().get(&Item::new("hats:fedora")));
+
+        let selected = closet.select_item(&Item::new("shirts:blue")).unwrap();
+        assert_eq!(closet.family_map(), selected.family_map());
+    }
+
+    #[test]
+    fn grouped_keys_sorted_items_by_family() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &slacks)
+            .add_item(&pants, &jeans)
+            .must_build();
+
+        let mut expected = BTreeMap::new();
+        expected.insert(pants.clone(), vec![jeans, slacks]);
+        expected.insert(shirts.clone(), vec![blue, red]);
+
+        assert_eq!(expected, closet.grouped());
+        assert_eq!(vec![&pants, &shirts], closet.grouped().keys().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn closets_with_equivalent_rules_are_equal() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet1 = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &slacks)
+            .add_item(&pants, &jeans)
+            .add_exclusion_rule(&red, &jeans)
+            .add_coupling_rule(&blue, &slacks)
+            .must_build();
+
+        let closet2 = ClosetBuilder::new()
+            .add_item(&pants, &jeans)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &slacks)
+            .add_item(&shirts, &red)
+            .add_inclusion_rule(&slacks, &blue)
+            .add_exclusion_rule(&jeans, &red)
+            .add_inclusion_rule(&blue, &slacks)
+            .must_build();
+
+        assert_eq!(closet1, closet2);
+
+        let mut closets = HashSet::new();
+        closets.insert(closet1.clone());
+        closets.insert(closet2.clone());
+        assert_eq!(1, closets.len());
+
+        let closet3 = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &slacks)
+            .add_item(&pants, &jeans)
+            .add_exclusion_rule(&red, &jeans)
+            .must_build();
+
+        assert_ne!(closet1, closet3);
+    }
+
+    #[test]
+    fn universal_matches_builder_without_rules() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = Closet::universal(&[
+            (shirts.clone(), red.clone()),
+            (pants.clone(), jeans.clone()),
+            (shirts.clone(), blue.clone()),
+        ]);
+
+        let expected = ClosetBuilder::new()
+            .add_items(&shirts, &[red, blue])
+            .add_item(&pants, &jeans)
+            .must_build();
+
+        assert_eq!(expected, closet);
+    }
+
+    #[test]
+    #[should_panic(expected = "ConflictingFamilies")]
+    fn universal_panics_on_item_listed_under_two_families() {
+        let blue = Item::new("blue");
+
+        Closet::universal(&[
+            (Family::new("shirts"), blue.clone()),
+            (Family::new("pants"), blue),
+        ]);
+    }
 }
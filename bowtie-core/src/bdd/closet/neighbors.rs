@@ -0,0 +1,81 @@
+use bdd::closet::Closet;
+use bdd::closet::complete_outfit;
+use core::Outfit;
+
+impl Closet {
+    /// The valid outfits that swap exactly one item of `outfit` for another item of the same family.
+    /// Outfits are ordered by the item swapped out, then by the item swapped in.
+    pub fn neighbors(&self, outfit: &Outfit) -> Vec<Outfit> {
+        let mut neighbors: Vec<Outfit> = vec![];
+
+        for current in outfit.items() {
+            let family = match self.get_family(current) {
+                Some(family) => family,
+                None => continue,
+            };
+
+            for alternative in self.items_in_family(family) {
+                if outfit.contains(alternative) {
+                    continue;
+                }
+
+                let items = outfit.items().iter()
+                    .filter(|item| *item != current)
+                    .cloned()
+                    .chain(Some(alternative.clone()))
+                    .collect::<Vec<_>>();
+
+                let neighbor = complete_outfit::outfit(self, items);
+                if self.is_valid_outfit(&neighbor) && !neighbors.contains(&neighbor) {
+                    neighbors.push(neighbor);
+                }
+            }
+        }
+
+        neighbors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet::Closet;
+    use bdd::fixtures::shirts_and_pants;
+    use core::Item;
+    use core::Outfit;
+
+    fn closet() -> Closet {
+        shirts_and_pants()
+            .add_exclusion_rule(&Item::new("shirts:red"), &Item::new("pants:jeans"))
+            .must_build()
+    }
+
+    #[test]
+    fn every_single_swap_is_a_neighbor_without_conflicts() {
+        let outfit = Outfit::new(vec![Item::new("pants:slacks"), Item::new("shirts:blue")]);
+
+        assert_eq!(
+            vec![
+                Outfit::new(vec![Item::new("pants:jeans"), Item::new("shirts:blue")]),
+                Outfit::new(vec![Item::new("pants:slacks"), Item::new("shirts:red")]),
+            ],
+            closet().neighbors(&outfit)
+        );
+    }
+
+    #[test]
+    fn swaps_violating_rules_are_skipped() {
+        let outfit = Outfit::new(vec![Item::new("pants:jeans"), Item::new("shirts:blue")]);
+
+        let neighbors = closet().neighbors(&outfit);
+
+        assert_eq!(1, neighbors.len());
+        assert_eq!(vec![Outfit::new(vec![Item::new("pants:slacks"), Item::new("shirts:blue")])], neighbors);
+    }
+
+    #[test]
+    fn unknown_items_are_not_swapped() {
+        let outfit = Outfit::new(vec![Item::new("hats:fedora")]);
+
+        assert!(closet().neighbors(&outfit).is_empty());
+    }
+}
@@ -1,22 +1,42 @@
 use bdd::closet::Closet;
 use bdd::node::Node;
+use bdd::node::NodeId;
+use core::Family;
+use core::Item;
 use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::mem;
 
 impl Closet {
-    pub fn node_count(&self) -> u64 {
-        Closet::node_count_internal(self.root())
-    }
-
-    fn node_count_internal(node: &Node) -> u64 {
-        match node {
-            Node::Leaf(_val) => 1,
-            Node::Branch(_id, low, high) => {
-                let low = Node::from(low);
-                let high = Node::from(high);
+    /// Counts the distinct nodes of the diagram, leaves included.
+    /// Shared sub-diagrams are only counted once.
+    pub fn node_count(&self) -> usize {
+        let mut visited = BTreeSet::new();
+
+        let mut queue = vec![self.root().clone()];
+        while let Some(node) = queue.pop() {
+            if !visited.insert(NodeId::from(&node)) {
+                continue;
+            }
 
-                1 + Closet::node_count_internal(&low) + Closet::node_count_internal(&high)
+            if let Node::Branch(_id, low, high) = node {
+                queue.push(Node::from(low));
+                queue.push(Node::from(high));
             }
         }
+
+        visited.len()
+    }
+
+    /// A rough estimate of the heap memory held by this closet, in bytes.
+    /// Counts the diagram's nodes and the item lookups; the rules diagram and allocator overhead are left out.
+    pub fn heap_bytes(&self) -> usize {
+        let nodes = self.node_count() * mem::size_of::<Node>();
+        let items = self.item_index.iter()
+            .map(|(item, family)| mem::size_of::<(Item, Family)>() + item.key().len() + family.key().len())
+            .sum::<usize>();
+
+        nodes + 2 * items
     }
 
     pub fn leaf_count(&self) -> u64 {
@@ -35,20 +55,14 @@ impl Closet {
         }
     }
 
+    /// Counts the complete outfits, including those that differ only in items the diagram never tests,
+    /// as in families that allow several items. Saturates at `u64::MAX`.
     pub fn outfit_count(&self) -> u64 {
-        Closet::outfit_count_internal(self.root())
-    }
+        let items = self.items().into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
 
-    fn outfit_count_internal(node: &Node) -> u64 {
-        match node {
-            Node::Leaf(val) => if *val { 1 } else { 0 },
-            Node::Branch(_id, low, high) => {
-                let low = Node::from(low);
-                let high = Node::from(high);
-
-                Closet::outfit_count_internal(&low) + Closet::outfit_count_internal(&high)
-            }
-        }
+        self.root.count_satisfying_in(&items, &self.order).unwrap_or(u64::MAX)
     }
 
     pub fn depth(&self) -> u64 {
@@ -77,6 +91,7 @@ impl Closet {
 #[cfg(test)]
 mod tests {
     use bdd::closet_builder::ClosetBuilder;
+    use core::CardinalitySpec;
     use core::Family;
     use core::Item;
 
@@ -99,7 +114,8 @@ mod tests {
 
         let closet = closet_builder.must_build();
 
-        assert_eq!(19, closet.node_count());
+        // pants:1, both pants:2 nodes, shirts:1, both shirts:2 nodes and the two leaves
+        assert_eq!(8, closet.node_count());
         assert_eq!(10, closet.leaf_count());
         assert_eq!(4, closet.outfit_count());
         assert_eq!(5, closet.depth());
@@ -125,7 +141,7 @@ mod tests {
 
         let closet = closet_builder.must_build();
 
-        assert_eq!(17, closet.node_count());
+        assert_eq!(9, closet.node_count());
         assert_eq!(9, closet.leaf_count());
         assert_eq!(3, closet.outfit_count());
         assert_eq!(5, closet.depth());
@@ -174,7 +190,7 @@ mod tests {
 
         let closet = closet_builder.must_build();
 
-        assert_eq!(649, closet.node_count());
+        assert_eq!(32, closet.node_count());
         assert_eq!(325, closet.leaf_count());
         assert_eq!(64, closet.outfit_count());
         assert_eq!(17, closet.depth());
@@ -227,9 +243,43 @@ mod tests {
 
         let closet = closet_builder.must_build();
 
-        assert_eq!(1701, closet.node_count());
+        assert_eq!(30, closet.node_count());
         assert_eq!(851, closet.leaf_count());
         assert_eq!(256, closet.outfit_count());
         assert_eq!(17, closet.depth());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn outfit_count_includes_untested_items() {
+        let shirts = Family::new("shirts");
+        let hats = Family::new("hats");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&shirts, &[Item::new("shirts:1"), Item::new("shirts:2")])
+            .add_items(&hats, &[Item::new("hats:1"), Item::new("hats:2"), Item::new("hats:3")])
+            .set_family_cardinality(&hats, CardinalitySpec::Range(0, 3))
+            .must_build();
+
+        assert_eq!(16, closet.outfit_count());
+    }
+
+    #[test]
+    fn heap_bytes_grow_with_closet() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let small = ClosetBuilder::new()
+            .add_item(&shirts, &Item::new("shirts:1"))
+            .add_item(&pants, &Item::new("pants:1"))
+            .must_build();
+        let large = ClosetBuilder::new()
+            .add_item(&shirts, &Item::new("shirts:1"))
+            .add_item(&shirts, &Item::new("shirts:2"))
+            .add_item(&pants, &Item::new("pants:1"))
+            .add_item(&pants, &Item::new("pants:2"))
+            .must_build();
+
+        assert!(0 < small.heap_bytes());
+        assert!(small.heap_bytes() < large.heap_bytes());
+    }
+}
@@ -0,0 +1,63 @@
+use bdd::closet::Closet;
+use core::Item;
+use core::Outfit;
+use core::OutfitError;
+use std::collections::BTreeMap;
+
+impl Closet {
+    /// Sums the weights of the outfit's items, counting items without a weight as zero.
+    pub fn outfit_cost(&self, outfit: &Outfit, weights: &BTreeMap<Item, i64>) -> Result<i64, OutfitError> {
+        let unknown_items = outfit.items().iter()
+            .filter(|item| self.get_family(item).is_none())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if !unknown_items.is_empty() {
+            return Err(OutfitError::UnknownItems(unknown_items));
+        }
+
+        Ok(outfit.items().iter()
+            .map(|item| weights.get(item).cloned().unwrap_or(0))
+            .sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet_builder::ClosetBuilder;
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+    use core::OutfitError;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn outfit_cost_sums_item_weights() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&shirts, &[blue.clone(), red.clone()])
+            .add_items(&pants, &[jeans.clone(), slacks.clone()])
+            .must_build();
+
+        let mut weights = BTreeMap::new();
+        weights.insert(red.clone(), 30);
+        weights.insert(slacks.clone(), -5);
+
+        assert_eq!(Ok(25), closet.outfit_cost(&Outfit::new(vec![slacks.clone(), red.clone()]), &weights));
+        assert_eq!(Ok(-5), closet.outfit_cost(&Outfit::new(vec![slacks.clone(), blue.clone()]), &weights));
+
+        let hat = Item::new("hats:fedora");
+        assert_eq!(
+            Err(OutfitError::UnknownItems(vec![hat.clone()])),
+            closet.outfit_cost(&Outfit::new(vec![hat, red]), &weights)
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use bdd::closet::Closet;
+use bdd::closet::Levels;
+use bdd::closet::complete_outfit::outfit;
+use bdd::closet::complete_outfit::validate;
+use bdd::node::Node;
+use bdd::node::NodeId;
+use core::Item;
+use core::Outfit;
+use core::OutfitError;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+impl Closet {
+    /// Completes the outfit around the `locked` selections, changing as few items of `previous` as possible.
+    /// A change is an item of `previous` left out or an item added that `previous` didn't have;
+    /// ties fall back to the default completion. Items the diagram skips are left out of the outfit,
+    /// so each one `previous` had counts as a change.
+    pub fn recomplete(&self, locked: &[Item], previous: &Outfit) -> Result<Outfit, OutfitError> {
+        validate(self, locked)?;
+
+        let assignments: BTreeMap<Item, bool> = locked.iter()
+            .map(|selection| (selection.clone(), true))
+            .collect();
+        let mut root: Node = self.root().restrict_all(&assignments);
+
+        let changes = Changes::new(self, locked, previous);
+        let mut costs = HashMap::new();
+        let mut outfit_items = locked.to_vec();
+        while let Node::Branch(id, low, high) = root.clone() {
+            let high = Node::from(high);
+            let low = Node::from(low);
+
+            let high_cost = changes.change_cost_below(&root, &high, previous, &mut costs)
+                .map(|cost| cost + u64::from(!previous.contains(&id)));
+            let low_cost = changes.change_cost_below(&root, &low, previous, &mut costs)
+                .map(|cost| cost + u64::from(previous.contains(&id)));
+
+            let take_high = match (high_cost, low_cost) {
+                (Some(high_cost), Some(low_cost)) => high_cost <= low_cost,
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+
+            if take_high {
+                outfit_items.push(id);
+                root = high;
+            } else {
+                root = low;
+            }
+        }
+
+        Ok(outfit(self, outfit_items))
+    }
+}
+
+/// The diagram's levels, with a running count of the items `previous` had that leaving out would change.
+/// Locked items are always worn, so they never count.
+struct Changes {
+    levels: Levels,
+    dropped: Vec<u64>,
+}
+
+impl Changes {
+    fn new(closet: &Closet, locked: &[Item], previous: &Outfit) -> Changes {
+        let levels = Levels::new(closet);
+        let mut dropped = vec![0];
+        for item in &levels.items {
+            let before = dropped[dropped.len() - 1];
+            dropped.push(before + u64::from(previous.contains(item) && !locked.contains(item)));
+        }
+
+        Changes { levels, dropped }
+    }
+
+    /// The changes from taking `child` below `parent`: the items skipped in between plus those below `child`.
+    fn change_cost_below(&self, parent: &Node, child: &Node, previous: &Outfit, costs: &mut HashMap<NodeId, Option<u64>>) -> Option<u64> {
+        let skipped = self.dropped[self.levels.level(child)] - self.dropped[self.levels.level(parent) + 1];
+
+        self.change_cost(child, previous, costs)
+            .map(|cost| cost + skipped)
+    }
+
+    fn change_cost(&self, node: &Node, previous: &Outfit, costs: &mut HashMap<NodeId, Option<u64>>) -> Option<u64> {
+        match node {
+            Node::Leaf(true) => Some(0),
+            Node::Leaf(false) => None,
+            Node::Branch(id, low, high) => {
+                if let Some(cost) = costs.get(&NodeId::from(node)) {
+                    return *cost;
+                }
+
+                let high_cost = self.change_cost_below(node, &Node::from(high), previous, costs)
+                    .map(|cost| cost + u64::from(!previous.contains(id)));
+                let low_cost = self.change_cost_below(node, &Node::from(low), previous, costs)
+                    .map(|cost| cost + u64::from(previous.contains(id)));
+
+                let cost = match (high_cost, low_cost) {
+                    (Some(high_cost), Some(low_cost)) => Some(high_cost.min(low_cost)),
+                    (high_cost, low_cost) => high_cost.or(low_cost),
+                };
+                costs.insert(NodeId::from(node), cost);
+                cost
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet::Closet;
+    use bdd::closet_builder::ClosetBuilder;
+    use core::CardinalitySpec;
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+    use core::OutfitError;
+
+    fn closet() -> Closet {
+        ClosetBuilder::new()
+            .add_items(&Family::new("shirts"), &[Item::new("shirts:blue"), Item::new("shirts:red")])
+            .add_items(&Family::new("pants"), &[Item::new("pants:jeans"), Item::new("pants:slacks")])
+            .add_items(&Family::new("shoes"), &[Item::new("shoes:boots"), Item::new("shoes:sneakers")])
+            .add_exclusion_rule(&Item::new("shirts:red"), &Item::new("pants:jeans"))
+            .must_build()
+    }
+
+    #[test]
+    fn new_selection_forces_exactly_one_change() {
+        let previous = Outfit::new(vec![Item::new("pants:jeans"), Item::new("shirts:blue"), Item::new("shoes:sneakers")]);
+
+        let outfit = closet().recomplete(&[Item::new("shirts:red")], &previous).unwrap();
+
+        assert_eq!(
+            Outfit::new(vec![Item::new("pants:slacks"), Item::new("shirts:red"), Item::new("shoes:sneakers")]),
+            outfit
+        );
+
+        let changed = previous.items().iter()
+            .filter(|item| **item != Item::new("shirts:blue"))
+            .filter(|item| !outfit.contains(item))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![&Item::new("pants:jeans")], changed);
+    }
+
+    #[test]
+    fn compatible_selection_keeps_previous_outfit() {
+        let previous = Outfit::new(vec![Item::new("pants:slacks"), Item::new("shirts:blue"), Item::new("shoes:sneakers")]);
+
+        assert_eq!(Ok(previous.clone()), closet().recomplete(&[Item::new("shirts:blue")], &previous));
+    }
+
+    #[test]
+    fn empty_previous_outfit_completes_by_default() {
+        let closet = closet();
+        let selections = vec![Item::new("shirts:red")];
+
+        assert_eq!(closet.complete_outfit(selections.clone()), closet.recomplete(&selections, &Outfit::new(vec![])));
+    }
+
+    #[test]
+    fn previous_items_the_diagram_skips_count_as_changes() {
+        let pants = Family::new("pants");
+        let shoes = Family::new("shoes");
+        let socks = Family::new("socks");
+        let jeans = Item::new("pants:jeans");
+        let boots = Item::new("shoes:boots");
+        let wool = Item::new("socks:wool");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&pants, &jeans)
+            .add_item(&shoes, &boots)
+            .add_item(&socks, &wool)
+            .set_family_cardinality(&pants, CardinalitySpec::Range(0, 1))
+            .set_family_cardinality(&shoes, CardinalitySpec::Range(0, 1))
+            .set_family_cardinality(&socks, CardinalitySpec::Range(0, 1))
+            .add_inclusion_rule(&jeans, &boots)
+            .add_inclusion_rule(&jeans, &wool)
+            .must_build();
+        let previous = Outfit::new(vec![boots.clone(), wool.clone()]);
+
+        assert_eq!(Ok(Outfit::new(vec![jeans, boots, wool])), closet.recomplete(&[], &previous));
+    }
+
+    #[test]
+    fn incompatible_locked_selections_are_rejected() {
+        let previous = Outfit::new(vec![]);
+
+        assert_eq!(
+            Err(OutfitError::IncompatibleSelections(vec![Item::new("pants:jeans"), Item::new("shirts:red")])),
+            closet().recomplete(&[Item::new("shirts:red"), Item::new("pants:jeans")], &previous)
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use bdd::closet::Closet;
+use bdd::node::Node;
+use core::Family;
+use core::Item;
+use std::collections::BTreeMap;
+
+impl Closet {
+    /// Splits previously saved selections into those still valid against this closet and those that are not.
+    /// A selection is invalid when its item is unknown or already selected, its family already holds as many
+    /// selections as it allows, or it cannot be completed alongside the earlier valid selections.
+    pub fn reconcile_selections(&self, selections: Vec<Item>) -> (Vec<Item>, Vec<Item>) {
+        let family_map = self.family_map();
+        let mut root = self.root.clone();
+        let mut selected_families: BTreeMap<&Family, usize> = BTreeMap::new();
+        let mut valid = vec![];
+        let mut invalid = vec![];
+
+        for selection in selections {
+            let family = match family_map.get(&selection) {
+                Some(family) if !valid.contains(&selection) => family,
+                _ => {
+                    invalid.push(selection);
+                    continue;
+                }
+            };
+            let selected = selected_families.get(family).copied().unwrap_or(0);
+            if selected >= self.family_cardinality(family).bounds().1 {
+                invalid.push(selection);
+                continue;
+            }
+
+            let candidate = Node::restrict(&root, &selection, true);
+            if candidate != Node::FALSE_LEAF {
+                root = candidate;
+                selected_families.insert(family, selected + 1);
+                valid.push(selection);
+            } else {
+                invalid.push(selection);
+            }
+        }
+
+        (valid, invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::fixtures::shirts_and_pants;
+    use core::CardinalitySpec;
+    use core::Family;
+    use core::Item;
+
+    #[test]
+    fn selections_from_dropped_family_are_invalid() {
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+        let boots = Item::new("shoes:boots");
+
+        let shoes = Family::new("shoes");
+        let saved = shirts_and_pants()
+            .add_item(&shoes, &boots)
+            .add_item(&shoes, &Item::new("shoes:sneakers"))
+            .must_build();
+
+        let selections = vec![red.clone(), boots.clone(), jeans.clone()];
+        assert_eq!((selections.clone(), vec![]), saved.reconcile_selections(selections.clone()));
+
+        let changed = shirts_and_pants().must_build();
+        assert_eq!((vec![red, jeans], vec![boots]), changed.reconcile_selections(selections));
+    }
+
+    #[test]
+    fn selections_conflicting_with_new_rules_are_invalid() {
+        let red = Item::new("shirts:red");
+        let blue = Item::new("shirts:blue");
+        let jeans = Item::new("pants:jeans");
+
+        let closet = shirts_and_pants()
+            .add_exclusion_rule(&red, &jeans)
+            .must_build();
+
+        assert_eq!(
+            (vec![red.clone()], vec![jeans.clone(), blue.clone()]),
+            closet.reconcile_selections(vec![red, jeans, blue])
+        );
+    }
+
+    #[test]
+    fn family_takes_as_many_selections_as_it_allows() {
+        let shoes = Family::new("shoes");
+        let boots = Item::new("shoes:boots");
+        let loafers = Item::new("shoes:loafers");
+        let sneakers = Item::new("shoes:sneakers");
+
+        let closet = shirts_and_pants()
+            .add_items(&shoes, &[boots.clone(), loafers.clone(), sneakers.clone()])
+            .set_family_cardinality(&shoes, CardinalitySpec::Exactly(2))
+            .must_build();
+
+        assert_eq!(
+            (vec![boots.clone(), sneakers.clone()], vec![boots.clone(), loafers.clone()]),
+            closet.reconcile_selections(vec![boots.clone(), sneakers, boots, loafers])
+        );
+    }
+}
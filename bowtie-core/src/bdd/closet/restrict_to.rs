@@ -0,0 +1,120 @@
+use bdd::closet::Closet;
+use closet_builder::ClosetBuilderError;
+use core::Item;
+use std::collections::BTreeMap;
+
+impl Closet {
+    /// Limits the closet to the `allowed` items, fixing every other item to unselected.
+    /// Families left without items are dropped when they are optional, and reported as
+    /// `EmptyFamily` otherwise since no outfit could be completed.
+    pub fn restrict_to(&self, allowed: &[Item]) -> Result<Closet, ClosetBuilderError> {
+        let assignments: BTreeMap<Item, bool> = self.item_index.keys()
+            .filter(|item| !allowed.contains(item))
+            .map(|item| (item.clone(), false))
+            .collect();
+
+        let empty_families = self.families().into_iter()
+            .filter(|family| self.family_cardinality(family).bounds().0 > 0)
+            .filter(|family| self.items_in_family(family).iter().all(|item| assignments.contains_key(item)))
+            .map(|family| ClosetBuilderError::EmptyFamily(family.clone()))
+            .collect::<Vec<_>>();
+
+        match empty_families.len() {
+            0 => {}
+            1 => return Err(empty_families[0].clone()),
+            _ => return Err(ClosetBuilderError::CompoundError(empty_families)),
+        }
+
+        let item_index = self.item_index.iter()
+            .filter(|(item, _)| !assignments.contains_key(item))
+            .map(|(item, family)| (item.clone(), family.clone()))
+            .collect();
+
+        let cardinalities = self.cardinalities.iter()
+            .filter(|(family, _)| self.items_in_family(family).iter().any(|item| !assignments.contains_key(item)))
+            .map(|(family, cardinality)| (family.clone(), *cardinality))
+            .collect();
+
+        let soft_exclusions = self.soft_exclusions.iter()
+            .filter(|(selection, exclusion, _)| !assignments.contains_key(selection) && !assignments.contains_key(exclusion))
+            .cloned()
+            .collect();
+
+        let root = self.root.restrict_all(&assignments);
+        let rules = self.rules.as_ref().map(|rules| rules.restrict_all(&assignments));
+
+        Ok(Closet::with_rules(item_index, root, rules)
+            .with_cardinalities(cardinalities)
+            .with_soft_exclusions(soft_exclusions)
+            .with_order(self.order.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet_builder::ClosetBuilder;
+    use bdd::fixtures::shirts_and_pants;
+    use closet_builder::ClosetBuilderError;
+    use core::CardinalitySpec;
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+
+    #[test]
+    fn one_item_per_family_leaves_one_outfit() {
+        let red = Item::new("shirts:red");
+        let slacks = Item::new("pants:slacks");
+
+        let closet = shirts_and_pants().must_build()
+            .restrict_to(&[red.clone(), slacks.clone()])
+            .unwrap();
+
+        assert_eq!(vec![&slacks, &red], closet.items());
+        assert_eq!(1, closet.outfit_count());
+        assert_eq!(Ok(Outfit::new(vec![slacks, red])), closet.complete_outfit(vec![]));
+    }
+
+    #[test]
+    fn restricted_closet_matches_closet_built_from_allowed_items() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let restricted = shirts_and_pants()
+            .add_exclusion_rule(&blue, &slacks)
+            .must_build()
+            .restrict_to(&[blue.clone(), red.clone(), slacks.clone()])
+            .unwrap();
+
+        let expected = ClosetBuilder::new()
+            .add_items(&Family::new("shirts"), &[blue.clone(), red.clone()])
+            .add_item(&Family::new("pants"), &slacks)
+            .add_exclusion_rule(&blue, &slacks)
+            .must_build();
+
+        assert_eq!(expected, restricted);
+        assert_eq!(None, restricted.get_family(&jeans));
+    }
+
+    #[test]
+    fn family_without_allowed_items_is_rejected_unless_optional() {
+        let red = Item::new("shirts:red");
+        let pants = Family::new("pants");
+        let allowed = vec![red.clone()];
+
+        assert_eq!(
+            Err(ClosetBuilderError::EmptyFamily(pants.clone())),
+            shirts_and_pants().must_build().restrict_to(&allowed)
+        );
+
+        let closet = shirts_and_pants()
+            .set_family_cardinality(&pants, CardinalitySpec::AtMostOne)
+            .must_build()
+            .restrict_to(&allowed)
+            .unwrap();
+
+        assert_eq!(vec![&Family::new("shirts")], closet.families());
+        assert_eq!(Ok(Outfit::new(vec![red])), closet.complete_outfit(vec![]));
+    }
+}
@@ -0,0 +1,323 @@
+use bdd::closet::Closet;
+use bdd::closet::Levels;
+use bdd::closet::complete_outfit::outfit;
+use bdd::node::Node;
+use bdd::node::NodeId;
+use core::Item;
+use core::Outfit;
+use rand::Rng;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+
+impl Closet {
+    /// Samples up to `n` distinct outfits, each drawn uniformly from the outfits of the closet.
+    /// Closets with fewer than `n` outfits return every outfit. The same seeded `rng` gives the same outfits.
+    /// Items the diagram skips are worn with even odds.
+    pub fn sample_outfits<R: Rng>(&self, n: usize, rng: &mut R) -> Vec<Outfit> {
+        let levels = Levels::new(self);
+        let mut counts = HashMap::new();
+        let total = levels.count_from(self.root(), 0, &mut counts);
+        let n = if total < n as u128 { total as usize } else { n };
+
+        let mut seen = BTreeSet::new();
+        let mut outfits = vec![];
+        while outfits.len() < n {
+            let outfit = sample(self, &levels, &mut counts, rng);
+            if seen.insert(outfit.items().to_vec()) {
+                outfits.push(outfit);
+            }
+        }
+
+        outfits
+    }
+
+    /// Samples one outfit, with each outfit drawn in proportion to the product of its items' weights.
+    /// Weights only matter relative to each other, so they don't need to be normalized; items without
+    /// a weight count as `1.0`. Items weighted zero or less are never picked, and `None` is returned
+    /// when every outfit contains such an item.
+    pub fn weighted_sample<R: Rng>(&self, weights: &BTreeMap<Item, f64>, rng: &mut R) -> Option<Outfit> {
+        let weight = |item: &Item| weights.get(item).cloned().unwrap_or(1.0).max(0.0);
+
+        let levels = Levels::new(self);
+        let mut masses = HashMap::new();
+        if levels.mass_from(self.root(), 0, &weight, &mut masses) <= 0.0 {
+            return None;
+        }
+
+        let mut outfit_items = vec![];
+        let mut node = self.root().clone();
+        let mut level = 0;
+        loop {
+            for item in levels.skipped(level, &node) {
+                let item_weight = weight(item);
+                if rng.gen::<f64>() * (1.0 + item_weight) < item_weight {
+                    outfit_items.push(item.clone());
+                }
+            }
+
+            let (id, low, high) = match node.clone() {
+                Node::Leaf(_) => break,
+                Node::Branch(id, low, high) => (id, Node::from(low), Node::from(high)),
+            };
+            level = levels.level(&node) + 1;
+
+            let low_mass = levels.mass_from(&low, level, &weight, &mut masses);
+            let high_mass = weight(&id) * levels.mass_from(&high, level, &weight, &mut masses);
+            if rng.gen::<f64>() * (low_mass + high_mass) < high_mass {
+                outfit_items.push(id);
+                node = high;
+            } else {
+                node = low;
+            }
+        }
+
+        Some(outfit(self, outfit_items))
+    }
+}
+
+impl Levels {
+    /// Counts the outfits below `node` over the items from `level` on, saturating at `u128::MAX`.
+    fn count_from(&self, node: &Node, level: usize, counts: &mut HashMap<NodeId, u128>) -> u128 {
+        let skipped = (self.level(node) - level) as u32;
+        let count = match node {
+            Node::Leaf(false) => return 0,
+            Node::Leaf(true) => 1,
+            Node::Branch(_id, low, high) => match counts.get(&NodeId::from(node)) {
+                Some(count) => *count,
+                None => {
+                    let below = self.level(node) + 1;
+                    let count = self.count_from(&Node::from(low), below, counts)
+                        .saturating_add(self.count_from(&Node::from(high), below, counts));
+                    counts.insert(NodeId::from(node), count);
+                    count
+                }
+            },
+        };
+
+        1u128.checked_shl(skipped)
+            .and_then(|free| count.checked_mul(free))
+            .unwrap_or(u128::MAX)
+    }
+
+    /// Like `count_from`, but each outfit counts as the product of its items' weights.
+    fn mass_from<F: Fn(&Item) -> f64>(&self, node: &Node, level: usize, weight: &F, masses: &mut HashMap<NodeId, f64>) -> f64 {
+        let mass = match node {
+            Node::Leaf(false) => return 0.0,
+            Node::Leaf(true) => 1.0,
+            Node::Branch(id, low, high) => match masses.get(&NodeId::from(node)) {
+                Some(mass) => *mass,
+                None => {
+                    let below = self.level(node) + 1;
+                    let mass = self.mass_from(&Node::from(low), below, weight, masses)
+                        + weight(id) * self.mass_from(&Node::from(high), below, weight, masses);
+                    masses.insert(NodeId::from(node), mass);
+                    mass
+                }
+            },
+        };
+
+        self.skipped(level, node).iter()
+            .fold(mass, |mass, item| mass * (1.0 + weight(item)))
+    }
+}
+
+fn sample<R: Rng>(closet: &Closet, levels: &Levels, counts: &mut HashMap<NodeId, u128>, rng: &mut R) -> Outfit {
+    let mut outfit_items = vec![];
+    let mut node = closet.root().clone();
+    let mut level = 0;
+    loop {
+        for item in levels.skipped(level, &node) {
+            if rng.gen::<bool>() {
+                outfit_items.push(item.clone());
+            }
+        }
+
+        let (id, low, high) = match node.clone() {
+            Node::Leaf(_) => break,
+            Node::Branch(id, low, high) => (id, Node::from(low), Node::from(high)),
+        };
+        level = levels.level(&node) + 1;
+
+        let low_count = levels.count_from(&low, level, counts);
+        let high_count = levels.count_from(&high, level, counts);
+        if rng.gen_range(0, low_count.saturating_add(high_count)) < high_count {
+            outfit_items.push(id);
+            node = high;
+        } else {
+            node = low;
+        }
+    }
+
+    outfit(closet, outfit_items)
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet_builder::ClosetBuilder;
+    use core::CardinalitySpec;
+    use core::Family;
+    use core::Item;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256StarStar;
+    use std::collections::BTreeMap;
+
+    fn closet_builder() -> ClosetBuilder {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+        let shoes = Family::new("shoes");
+
+        ClosetBuilder::new()
+            .add_items(&shirts, &[Item::new("shirts:blue"), Item::new("shirts:red"), Item::new("shirts:grey")])
+            .add_items(&pants, &[Item::new("pants:jeans"), Item::new("pants:slacks")])
+            .add_items(&shoes, &[Item::new("shoes:boots"), Item::new("shoes:sneakers")])
+            .add_exclusion_rule(&Item::new("shirts:red"), &Item::new("pants:jeans"))
+            .add_inclusion_rule(&Item::new("pants:slacks"), &Item::new("shoes:boots"))
+    }
+
+    #[test]
+    fn samples_are_distinct_and_valid() {
+        let closet = closet_builder().must_build();
+        let mut rng = Xoshiro256StarStar::seed_from_u64(7);
+
+        let outfits = closet.sample_outfits(5, &mut rng);
+
+        assert_eq!(5, outfits.len());
+        for (index, outfit) in outfits.iter().enumerate() {
+            assert!(!outfits[index + 1..].contains(outfit));
+            assert!(closet.is_valid_outfit(outfit));
+        }
+    }
+
+    #[test]
+    fn samples_are_reproducible_with_seed() {
+        let closet = closet_builder().must_build();
+
+        let first = closet.sample_outfits(4, &mut Xoshiro256StarStar::seed_from_u64(42));
+        let second = closet.sample_outfits(4, &mut Xoshiro256StarStar::seed_from_u64(42));
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn samples_are_capped_at_outfit_count() {
+        let closet = closet_builder().must_build();
+        let mut rng = Xoshiro256StarStar::seed_from_u64(7);
+
+        let mut outfits = closet.sample_outfits(100, &mut rng);
+        outfits.sort_by_key(|outfit| outfit.items().to_vec());
+
+        let mut all_outfits = closet.completions(vec![]).unwrap().collect::<Vec<_>>();
+        all_outfits.sort_by_key(|outfit| outfit.items().to_vec());
+
+        assert_eq!(closet.outfit_count() as usize, outfits.len());
+        assert_eq!(all_outfits, outfits);
+    }
+
+    #[test]
+    fn samples_cover_items_the_diagram_skips() {
+        let hats = Family::new("hats");
+
+        let closet = closet_builder()
+            .add_items(&hats, &[Item::new("hats:cap"), Item::new("hats:fedora")])
+            .set_family_cardinality(&hats, CardinalitySpec::Range(0, 2))
+            .must_build();
+        let mut rng = Xoshiro256StarStar::seed_from_u64(7);
+
+        let outfits = closet.sample_outfits(100, &mut rng);
+
+        assert_eq!(28, closet.outfit_count());
+        assert_eq!(28, outfits.len());
+        assert!(outfits.iter().all(|outfit| closet.is_valid_outfit(outfit)));
+    }
+
+    #[test]
+    fn samples_are_drawn_uniformly() {
+        let closet = closet_builder().must_build();
+        let mut rng = Xoshiro256StarStar::seed_from_u64(7);
+
+        let mut tally: BTreeMap<Vec<Item>, usize> = BTreeMap::new();
+        for _ in 0..7000 {
+            let outfit = closet.sample_outfits(1, &mut rng).remove(0);
+            *tally.entry(outfit.items().to_vec()).or_default() += 1;
+        }
+
+        assert_eq!(7, tally.len());
+        assert!(tally.values().all(|&count| 850 < count && count < 1150), "{:?}", tally);
+    }
+
+    #[test]
+    fn weighted_sample_leans_toward_heavier_items() {
+        let closet = closet_builder().must_build();
+        let mut rng = Xoshiro256StarStar::seed_from_u64(7);
+
+        let grey = Item::new("shirts:grey");
+        let red = Item::new("shirts:red");
+
+        let mut weights = BTreeMap::new();
+        weights.insert(grey.clone(), 8.0);
+
+        let mut grey_count = 0;
+        let mut red_count = 0;
+        for _ in 0..1000 {
+            let outfit = closet.weighted_sample(&weights, &mut rng).unwrap();
+            assert!(closet.is_valid_outfit(&outfit));
+
+            if outfit.contains(&grey) {
+                grey_count += 1;
+            }
+            if outfit.contains(&red) {
+                red_count += 1;
+            }
+        }
+
+        assert!(grey_count > 4 * red_count, "grey: {}, red: {}", grey_count, red_count);
+    }
+
+    #[test]
+    fn weighted_sample_skips_zero_weight_items() {
+        let closet = closet_builder().must_build();
+        let mut rng = Xoshiro256StarStar::seed_from_u64(7);
+
+        let boots = Item::new("shoes:boots");
+        let sneakers = Item::new("shoes:sneakers");
+
+        let mut weights = BTreeMap::new();
+        weights.insert(boots.clone(), 0.0);
+
+        for _ in 0..100 {
+            let outfit = closet.weighted_sample(&weights, &mut rng).unwrap();
+            assert!(!outfit.contains(&boots));
+        }
+
+        weights.insert(sneakers, 0.0);
+        assert_eq!(None, closet.weighted_sample(&weights, &mut rng));
+    }
+
+    #[test]
+    fn weighted_sample_weighs_items_the_diagram_skips() {
+        let hats = Family::new("hats");
+        let cap = Item::new("hats:cap");
+
+        let closet = closet_builder()
+            .add_items(&hats, &[cap.clone(), Item::new("hats:fedora")])
+            .set_family_cardinality(&hats, CardinalitySpec::Range(0, 2))
+            .must_build();
+        let mut rng = Xoshiro256StarStar::seed_from_u64(7);
+
+        let mut weights = BTreeMap::new();
+        weights.insert(cap.clone(), 3.0);
+
+        let mut cap_count = 0;
+        for _ in 0..2000 {
+            let outfit = closet.weighted_sample(&weights, &mut rng).unwrap();
+            assert!(closet.is_valid_outfit(&outfit));
+
+            if outfit.contains(&cap) {
+                cap_count += 1;
+            }
+        }
+
+        assert!(1350 < cap_count && cap_count < 1650, "cap: {}", cap_count);
+    }
+}
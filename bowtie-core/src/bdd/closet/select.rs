@@ -12,7 +12,9 @@ impl Closet {
         validate_selection_not_excluded(&self.summary, item)?;
 
         let item_index = self.item_index.clone();
+        let family_map = self.family_map.clone();
         let root = Node::restrict(&self.root, item, true);
+        let rules = self.rules.as_ref().map(|rules| Node::restrict(rules, item, true));
         let summary = Node::summarize(&root);
 
         let summary = self.summary.iter()
@@ -24,7 +26,7 @@ impl Closet {
             .sorted()
             .collect_vec();
 
-        Ok(Closet { item_index, summary, root })
+        Ok(Closet { item_index, family_map, summary, root, rules, cardinalities: self.cardinalities.clone(), soft_exclusions: self.soft_exclusions.clone(), order: self.order.clone() })
     }
 }
 
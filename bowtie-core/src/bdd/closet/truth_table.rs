@@ -0,0 +1,96 @@
+use bdd::closet::Closet;
+use bdd::node::Node;
+use core::Item;
+use core::TruthTableError;
+use core::TruthTableRow;
+use std::collections::BTreeMap;
+
+impl Closet {
+    /// The most items `truth_table` enumerates, giving at most 65536 rows.
+    pub const TRUTH_TABLE_LIMIT: usize = 16;
+
+    /// Every assignment of the closet's items alongside whether it is a valid outfit, checked one by one against the diagram.
+    /// Rows count up in binary with the first item as the most significant bit, so the all-false row comes first.
+    /// Closets with more than `TRUTH_TABLE_LIMIT` items are rejected.
+    pub fn truth_table(&self) -> Result<Vec<TruthTableRow>, TruthTableError> {
+        let items = self.items();
+        if items.len() > Closet::TRUTH_TABLE_LIMIT {
+            return Err(TruthTableError::TooManyItems(items.len(), Closet::TRUTH_TABLE_LIMIT));
+        }
+
+        let rows = (0..1usize << items.len())
+            .map(|row| {
+                let assignments: BTreeMap<Item, bool> = items.iter()
+                    .enumerate()
+                    .map(|(index, item)| ((*item).clone(), row & (1 << (items.len() - 1 - index)) != 0))
+                    .collect();
+                let valid = self.root.restrict_all(&assignments) == Node::TRUE_LEAF;
+
+                (assignments, valid)
+            })
+            .collect();
+
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet::Closet;
+    use bdd::fixtures::shirts_and_pants;
+    use core::Family;
+    use core::Item;
+    use core::TruthTableError;
+
+    #[test]
+    fn two_by_two_closet_has_four_valid_rows() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let closet = shirts_and_pants().must_build();
+
+        let table = closet.truth_table().unwrap();
+        assert_eq!(16, table.len());
+
+        // Items are ordered jeans, slacks, blue, red, so the top two bits are pants and the bottom two shirts
+        let expected = (0..16)
+            .map(|row: usize| {
+                let pants = (row >> 2).count_ones() == 1;
+                let shirts = (row & 0b11).count_ones() == 1;
+                pants && shirts
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(expected, table.iter().map(|(_, valid)| *valid).collect::<Vec<_>>());
+
+        let valid = table.iter()
+            .filter(|(_, valid)| *valid)
+            .map(|(assignments, _)| (assignments[&jeans], assignments[&slacks], assignments[&blue], assignments[&red]))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (false, true, false, true),
+                (false, true, true, false),
+                (true, false, false, true),
+                (true, false, true, false),
+            ],
+            valid
+        );
+
+        let (first, _) = &table[0];
+        assert!(first.values().all(|selected| !selected));
+    }
+
+    #[test]
+    fn closet_over_the_limit_is_rejected() {
+        let items = (0..Closet::TRUTH_TABLE_LIMIT + 1)
+            .map(|index| (Family::new(format!("family{}", index)), Item::new(format!("item{}", index))))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            Err(TruthTableError::TooManyItems(17, 16)),
+            Closet::universal(&items).truth_table()
+        );
+    }
+}
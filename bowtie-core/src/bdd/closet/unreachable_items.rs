@@ -0,0 +1,75 @@
+use bdd::closet::Closet;
+use bdd::node::Node;
+use core::Item;
+
+impl Closet {
+    /// Items that are part of no valid outfit, usually because rules exclude them entirely.
+    /// A closet without any valid outfit has none.
+    pub fn unreachable_items(&self) -> Vec<Item> {
+        if self.root == Node::FALSE_LEAF {
+            return Vec::new();
+        }
+
+        self.item_index.keys()
+            .filter(|item| Node::restrict(&self.root, item, true) == Node::FALSE_LEAF)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet::Closet;
+    use bdd::closet_builder::ClosetBuilder;
+    use bdd::node::Node;
+    use core::Family;
+    use core::Item;
+
+    #[test]
+    fn item_excluded_by_every_item_of_another_family_is_unreachable() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &blue)
+            .add_item(&shirts, &red)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .add_exclusion_rules(&red, &[jeans.clone(), slacks.clone()])
+            .must_build();
+
+        assert_eq!(vec![red], closet.unreachable_items());
+        assert_eq!(vec![blue], closet.mandatory_items());
+    }
+
+    #[test]
+    fn closet_without_rules_has_no_unreachable_items() {
+        let shirts = Family::new("shirts");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &Item::new("shirts:blue"))
+            .add_item(&shirts, &Item::new("shirts:red"))
+            .must_build();
+
+        assert_eq!(Vec::<Item>::new(), closet.unreachable_items());
+    }
+
+    #[test]
+    fn unsatisfiable_closet_has_no_unreachable_items() {
+        let shirts = Family::new("shirts");
+
+        let built = ClosetBuilder::new()
+            .add_item(&shirts, &Item::new("shirts:blue"))
+            .add_item(&shirts, &Item::new("shirts:red"))
+            .must_build();
+        let closet = Closet::new(built.item_index().clone(), Node::FALSE_LEAF);
+
+        assert_eq!(Vec::<Item>::new(), closet.unreachable_items());
+    }
+}
@@ -0,0 +1,229 @@
+use bdd::closet::Closet;
+use bdd::node::VariableOrder;
+use core::Item;
+use itertools::Itertools;
+use std::collections::BTreeSet;
+
+impl Closet {
+    /// Rebuilds the diagram so that items are tested in the given order, which can make it much smaller.
+    /// Items of the closet missing from `order` are tested afterwards, in their natural order.
+    /// The closet keeps the same outfits, though walks that prefer the high branch, like `complete_outfit`,
+    /// meet the items in the new order and may settle on a different valid outfit.
+    pub fn reorder(&mut self, order: &[Item]) {
+        let order = complete_order(self, order);
+
+        *self = self.in_order(&order);
+    }
+
+    /// Repeatedly swaps adjacent items in the variable order, keeping every swap that shrinks the diagram.
+    /// Only the order finally chosen is registered.
+    pub fn sift(&mut self) {
+        let mut order: Vec<Item> = self.items_in_diagram_order().into_iter().cloned().collect();
+        let mut best = self.root.size();
+
+        let mut improved = true;
+        while improved {
+            improved = false;
+
+            for index in 1..order.len() {
+                order.swap(index - 1, index);
+
+                let candidate = self.root.reordered(&VariableOrder::unregistered(&order)).size();
+                if candidate < best {
+                    best = candidate;
+                    improved = true;
+                } else {
+                    order.swap(index - 1, index);
+                }
+            }
+        }
+
+        self.reorder(&order);
+    }
+
+    /// A copy of the closet whose diagrams test items in `order`.
+    pub(super) fn in_order(&self, order: &VariableOrder) -> Closet {
+        let mut closet = self.clone();
+        closet.root = self.root.reordered(order);
+        closet.rules = self.rules.as_ref().map(|rules| rules.reordered(order));
+        closet.order = order.clone();
+        closet
+    }
+}
+
+/// The closet's items in `order` followed by the rest in their natural order,
+/// or the natural order itself when that is what they come to.
+fn complete_order(closet: &Closet, order: &[Item]) -> VariableOrder {
+    let ordered: BTreeSet<&Item> = order.iter().collect();
+
+    let items = order.iter()
+        .filter(|item| closet.get_family(item).is_some())
+        .cloned()
+        .chain(closet.items().into_iter()
+            .filter(|item| !ordered.contains(item))
+            .cloned())
+        .unique()
+        .collect::<Vec<_>>();
+
+    if items.iter().tuple_windows().all(|(item, next)| item < next) {
+        return VariableOrder::default();
+    }
+    VariableOrder::new(&items)
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet::Closet;
+    use bdd::closet_builder::ClosetBuilder;
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256StarStar;
+    use std::collections::HashSet;
+
+    fn closet() -> Closet {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+        let shoes = Family::new("shoes");
+
+        ClosetBuilder::new()
+            .add_item(&shirts, &Item::new("shirts:blue"))
+            .add_item(&shirts, &Item::new("shirts:red"))
+            .add_item(&pants, &Item::new("pants:jeans"))
+            .add_item(&pants, &Item::new("pants:slacks"))
+            .add_item(&shoes, &Item::new("shoes:boots"))
+            .add_item(&shoes, &Item::new("shoes:sneakers"))
+            .add_exclusion_rule(&Item::new("shirts:red"), &Item::new("shoes:boots"))
+            .add_inclusion_rule(&Item::new("pants:slacks"), &Item::new("shoes:sneakers"))
+            .must_build()
+    }
+
+    fn interleaved() -> Vec<Item> {
+        vec![
+            Item::new("shirts:red"),
+            Item::new("shoes:sneakers"),
+            Item::new("pants:jeans"),
+            Item::new("shirts:blue"),
+            Item::new("shoes:boots"),
+            Item::new("pants:slacks"),
+        ]
+    }
+
+    fn all_outfits(closet: &Closet) -> Vec<Outfit> {
+        let mut outfits = closet.completions(vec![]).unwrap().collect::<Vec<_>>();
+        outfits.sort_by_key(|outfit| outfit.items().to_vec());
+        outfits
+    }
+
+    #[test]
+    fn reorder_preserves_outfits() {
+        let original = closet();
+
+        let mut reordered = original.clone();
+        reordered.reorder(&interleaved());
+
+        assert_ne!(original.root(), reordered.root());
+        assert_eq!(interleaved(), reordered.variable_order().items());
+        assert_eq!(original.outfit_count(), reordered.outfit_count());
+        assert_eq!(all_outfits(&original), all_outfits(&reordered));
+        assert_eq!(original, reordered);
+        assert_eq!(1, vec![original.clone(), reordered.clone()].into_iter().collect::<HashSet<Closet>>().len());
+
+        for item in original.items() {
+            assert_eq!(
+                original.select_item(item).map(|closet| closet.outfit_count()),
+                reordered.select_item(item).map(|closet| closet.outfit_count())
+            );
+        }
+    }
+
+    #[test]
+    fn reorder_to_natural_order_is_identity() {
+        let original = closet();
+
+        let mut reordered = original.clone();
+        reordered.reorder(&[Item::new("shoes:sneakers"), Item::new("pants:slacks")]);
+        reordered.reorder(&[]);
+
+        assert_eq!(original, reordered);
+        assert_eq!(1, vec![original.clone(), reordered.clone()].into_iter().collect::<HashSet<Closet>>().len());
+        assert!(reordered.variable_order().is_natural());
+    }
+
+    #[test]
+    fn sift_does_not_grow_diagram_and_preserves_outfits() {
+        let original = closet();
+
+        let mut reordered = original.clone();
+        reordered.reorder(&interleaved());
+        let before = reordered.root().size();
+
+        reordered.sift();
+
+        assert!(reordered.root().size() < before);
+        assert_eq!(original.outfit_count(), reordered.outfit_count());
+        assert_eq!(all_outfits(&original), all_outfits(&reordered));
+    }
+
+    #[test]
+    fn reordered_closet_answers_like_the_original() {
+        let original = closet();
+
+        let mut reordered = original.clone();
+        reordered.reorder(&interleaved());
+
+        let red = Item::new("shirts:red");
+        let slacks = Item::new("pants:slacks");
+        let boots = Item::new("shoes:boots");
+
+        let outfit = reordered.complete_outfit(vec![red.clone()]).unwrap();
+        assert!(original.is_valid_outfit(&outfit));
+        assert!(outfit.contains(&red));
+        assert_eq!(
+            original.complete_outfit(vec![red.clone(), slacks.clone()]),
+            reordered.complete_outfit(vec![red.clone(), slacks.clone()])
+        );
+        assert_eq!(original.is_complete(&[red.clone(), slacks.clone()]), reordered.is_complete(&[red.clone(), slacks.clone()]));
+        let incomplete = [red.clone(), Item::new("pants:jeans")];
+        assert_eq!(original.is_complete(&incomplete), reordered.is_complete(&incomplete));
+        assert_eq!(Ok((vec![], vec![])), reordered.diff(&original));
+        assert_eq!(Ok((vec![], vec![])), original.diff(&reordered));
+
+        let without_boots = original.restrict_to(&[red.clone(), Item::new("shirts:blue"), slacks.clone(), Item::new("pants:jeans"), Item::new("shoes:sneakers")]).unwrap();
+        assert_eq!(without_boots.diff(&original), without_boots.diff(&reordered));
+
+        assert_eq!(
+            original.complete_outfit_best_effort(vec![red.clone(), boots.clone()]),
+            reordered.complete_outfit_best_effort(vec![red.clone(), boots])
+        );
+
+        let previous = original.complete_outfit(vec![]).unwrap();
+        let locked = [slacks];
+        assert_eq!(original.recomplete(&locked, &previous), reordered.recomplete(&locked, &previous));
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(7);
+        let mut sampled = reordered.sample_outfits(100, &mut rng);
+        sampled.sort_by_key(|outfit| outfit.items().to_vec());
+        assert_eq!(all_outfits(&original), sampled);
+    }
+
+    #[test]
+    fn item_added_to_reordered_closet_is_tested_last() {
+        let original = closet();
+
+        let mut reordered = original.clone();
+        reordered.reorder(&interleaved());
+
+        let grey = Item::new("shirts:grey");
+        let hat = Item::new("hats:fedora");
+        for (family, item) in &[(Family::new("shirts"), grey), (Family::new("hats"), hat)] {
+            let expected = original.with_item_added(family, item).unwrap();
+            let actual = reordered.with_item_added(family, item).unwrap();
+
+            assert_eq!(reordered.variable_order(), actual.variable_order());
+            assert_eq!(expected.outfit_count(), actual.outfit_count());
+            assert_eq!(all_outfits(&expected), all_outfits(&actual));
+        }
+    }
+}
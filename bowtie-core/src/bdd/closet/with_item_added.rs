@@ -0,0 +1,153 @@
+use bdd::closet::Closet;
+use bdd::closet_builder::ClosetBuilder;
+use bdd::node::BoolOp;
+use bdd::node::Node;
+use closet_builder::ClosetBuilderError;
+use core::CardinalitySpec;
+use core::Family;
+use core::Item;
+use std::collections::BTreeMap;
+
+impl Closet {
+    /// Adds an item without rebuilding the rules of the closet.
+    /// Outfits without the new item are kept from the current root; outfits with it
+    /// combine the rules with the other families, which leave out every sibling of the item.
+    /// Families that allow other than exactly one item are re-encoded in full instead.
+    /// Adding to an existing family fails with `UnknownRules` when the closet came from `Closet::new`.
+    /// A reordered closet keeps its order, testing the new item after the items it already had.
+    pub fn with_item_added(&self, family: &Family, item: &Item) -> Result<Closet, ClosetBuilderError> {
+        match self.get_family(item) {
+            Some(item_family) if item_family == family => return Ok(self.clone()),
+            Some(item_family) => return Err(ClosetBuilderError::ConflictingFamilies(item.clone(), vec![item_family.clone(), family.clone()])),
+            None => {}
+        }
+
+        let mut item_index = self.item_index.clone();
+        item_index.insert(item.clone(), family.clone());
+
+        let and = |node: Node, other: Node| Node::apply_in(&node, &other, BoolOp::And, &self.order);
+
+        let siblings = self.items_in_family(family);
+        if siblings.is_empty() {
+            let root = and(self.root.clone(), Node::positive_branch(item));
+            return Ok(self.with_root(item_index, root));
+        }
+
+        let rules = match &self.rules {
+            Some(rules) => rules,
+            None => return Err(ClosetBuilderError::UnknownRules),
+        };
+
+        let family_relationship = |other: &Family, extra: Option<&Item>| {
+            let items = self.items_in_family(other).into_iter()
+                .chain(extra)
+                .cloned()
+                .collect::<Vec<_>>();
+
+            self.in_diagram_order(ClosetBuilder::family_relationship(&items, self.family_cardinality(other)))
+        };
+
+        if self.family_cardinality(family) != CardinalitySpec::ExactlyOne {
+            let root = self.families().into_iter()
+                .map(|other| family_relationship(other, Some(item).filter(|_| other == family)))
+                .fold(rules.clone(), and);
+            return Ok(self.with_root(item_index, root));
+        }
+
+        let other_families = self.families().into_iter()
+            .filter(|other| *other != family)
+            .map(|other| family_relationship(other, None))
+            .fold(Node::TRUE_LEAF, and);
+
+        let with_item = siblings.into_iter()
+            .fold(and(Node::positive_branch(item), rules.clone()), |node, sibling| and(node, Node::negative_branch(sibling)));
+        let with_item = and(with_item, other_families);
+        let without_item = and(self.root.clone(), Node::negative_branch(item));
+
+        Ok(self.with_root(item_index, Node::apply_in(&without_item, &with_item, BoolOp::Or, &self.order)))
+    }
+
+    fn with_root(&self, item_index: BTreeMap<Item, Family>, root: Node) -> Closet {
+        Closet::with_rules(item_index, root, self.rules.clone())
+            .with_cardinalities(self.cardinalities.clone())
+            .with_soft_exclusions(self.soft_exclusions.clone())
+            .with_order(self.order.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet::Closet;
+    use bdd::closet_builder::ClosetBuilder;
+    use bdd::fixtures::shirts_and_pants;
+    use closet_builder::ClosetBuilderError;
+    use core::Family;
+    use core::Item;
+
+    fn closet_builder() -> ClosetBuilder {
+        shirts_and_pants()
+            .add_exclusion_rule(&Item::new("shirts:red"), &Item::new("pants:jeans"))
+            .add_inclusion_rule(&Item::new("pants:slacks"), &Item::new("shirts:blue"))
+    }
+
+    #[test]
+    fn adding_item_to_existing_family_matches_rebuild() {
+        let shirts = Family::new("shirts");
+        let grey = Item::new("shirts:grey");
+
+        let incremental = closet_builder().must_build()
+            .with_item_added(&shirts, &grey)
+            .unwrap();
+
+        let rebuilt = closet_builder()
+            .add_item(&shirts, &grey)
+            .must_build();
+
+        assert_eq!(rebuilt, incremental);
+        assert_eq!(rebuilt.summary(), incremental.summary());
+    }
+
+    #[test]
+    fn adding_item_to_new_family_matches_rebuild() {
+        let shoes = Family::new("shoes");
+        let boots = Item::new("shoes:boots");
+        let sneakers = Item::new("shoes:sneakers");
+
+        let incremental = closet_builder().must_build()
+            .with_item_added(&shoes, &boots)
+            .and_then(|closet| closet.with_item_added(&shoes, &sneakers))
+            .unwrap();
+
+        let rebuilt = closet_builder()
+            .add_item(&shoes, &boots)
+            .add_item(&shoes, &sneakers)
+            .must_build();
+
+        assert_eq!(rebuilt, incremental);
+    }
+
+    #[test]
+    fn adding_item_to_second_family_is_rejected() {
+        let red = Item::new("shirts:red");
+
+        let result = closet_builder().must_build()
+            .with_item_added(&Family::new("pants"), &red);
+
+        assert_eq!(
+            Err(ClosetBuilderError::ConflictingFamilies(red, vec![Family::new("shirts"), Family::new("pants")])),
+            result
+        );
+    }
+
+    #[test]
+    fn adding_item_to_existing_family_needs_rules() {
+        let built = closet_builder().must_build();
+        let closet = Closet::new(built.item_index().clone(), built.root().clone());
+
+        assert_eq!(
+            Err(ClosetBuilderError::UnknownRules),
+            closet.with_item_added(&Family::new("shirts"), &Item::new("shirts:grey"))
+        );
+        assert!(closet.with_item_added(&Family::new("shoes"), &Item::new("shoes:boots")).is_ok());
+    }
+}
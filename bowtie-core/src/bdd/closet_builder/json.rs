@@ -0,0 +1,350 @@
+use bdd::closet_builder::ClosetBuilder;
+use closet_builder::ClosetBuilderError;
+use core::CardinalitySpec;
+use core::Family;
+use core::Item;
+use serde_json;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ClosetSpec {
+    families: BTreeMap<String, Vec<Item>>,
+    #[serde(default)]
+    exclusions: Vec<(Item, Item)>,
+    #[serde(default)]
+    inclusions: Vec<(Item, Item)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    conditional_exclusions: Vec<(Vec<Item>, Item, Item)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    clauses: Vec<Vec<Item>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    cardinalities: BTreeMap<String, CardinalitySpec>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    soft_exclusions: Vec<(Item, Item, i64)>,
+}
+
+impl ClosetBuilder {
+    /// Parses a closet spec such as
+    /// `{"families": {"shirts": ["blue"], "pants": ["jeans"]}, "exclusions": [["blue", "jeans"]], "inclusions": []}`,
+    /// optionally with `"conditional_exclusions": [[["hat"], "blue", "jeans"]]`, `"clauses": [["blue", "jeans"]]`, `"cardinalities": {"shirts": {"Exactly": 2}}`
+    /// and `"soft_exclusions": [["blue", "jeans", 5]]`. A scoped item is written as `["shoes", "black"]`.
+    /// Rules may only reference items declared under `families`.
+    pub fn from_json(s: &str) -> Result<ClosetBuilder, ClosetBuilderError> {
+        let spec: ClosetSpec = serde_json::from_str(s)
+            .map_err(|error| ClosetBuilderError::MalformedSpec(error.to_string()))?;
+
+        let closet_builder = spec.families.iter()
+            .fold(ClosetBuilder::new(), |closet_builder, (family, items)| {
+                closet_builder.family(&Family::new(family.as_str())).with(items)
+            });
+
+        let mut undeclared = spec.exclusions.iter()
+            .chain(spec.inclusions.iter())
+            .flat_map(|(selection, item)| vec![selection, item])
+            .chain(spec.conditional_exclusions.iter().flat_map(|(condition, selection, exclusion)| condition.iter().chain(vec![selection, exclusion])))
+            .chain(spec.clauses.iter().flatten())
+            .chain(spec.soft_exclusions.iter().flat_map(|(selection, exclusion, _)| vec![selection, exclusion]))
+            .filter(|item| !spec.families.values().any(|items| items.contains(item)))
+            .map(|item| ClosetBuilderError::MissingFamily(item.clone()))
+            .collect::<Vec<_>>();
+        undeclared.sort();
+        undeclared.dedup();
+
+        match undeclared.len() {
+            0 => {}
+            1 => return Err(undeclared.remove(0)),
+            _ => return Err(ClosetBuilderError::CompoundError(undeclared)),
+        }
+
+        let closet_builder = spec.exclusions.iter()
+            .fold(closet_builder, |closet_builder, (selection, exclusion)| {
+                closet_builder.add_exclusion_rule(selection, exclusion)
+            });
+
+        let closet_builder = spec.inclusions.iter()
+            .fold(closet_builder, |closet_builder, (selection, inclusion)| {
+                closet_builder.add_inclusion_rule(selection, inclusion)
+            });
+
+        let closet_builder = spec.conditional_exclusions.iter()
+            .fold(closet_builder, |closet_builder, (condition, selection, exclusion)| {
+                closet_builder.add_conditional_exclusion(condition, (selection, exclusion))
+            });
+
+        let closet_builder = spec.clauses.iter()
+            .fold(closet_builder, |closet_builder, clause| closet_builder.add_clause(clause));
+
+        let closet_builder = spec.cardinalities.iter()
+            .fold(closet_builder, |closet_builder, (family, cardinality)| {
+                closet_builder.set_family_cardinality(&Family::new(family.as_str()), *cardinality)
+            });
+
+        Ok(spec.soft_exclusions.iter()
+            .fold(closet_builder, |closet_builder, (selection, exclusion, penalty)| {
+                closet_builder.add_soft_exclusion(selection, exclusion, *penalty)
+            }))
+    }
+
+    /// Writes the families and rules back out in the format `from_json` reads.
+    /// An exclusion given in both directions is written once.
+    pub fn to_json(&self) -> String {
+        let families = self.contents.iter()
+            .map(|(family, items)| (family.key().to_string(), items.clone()))
+            .collect();
+
+        let mut exclusions: Vec<(Item, Item)> = vec![];
+        for (selection, excluded) in self.exclusions.iter() {
+            for exclusion in excluded {
+                let pair = (selection.clone(), exclusion.clone());
+                let reversed = (pair.1.clone(), pair.0.clone());
+                if !exclusions.contains(&pair) && !exclusions.contains(&reversed) {
+                    exclusions.push(pair);
+                }
+            }
+        }
+
+        let inclusions = self.inclusions.iter()
+            .flat_map(|(selection, included)| included.iter().map(move |inclusion| (selection.clone(), inclusion.clone())))
+            .collect();
+
+        let conditional_exclusions = self.conditional_exclusions.iter()
+            .map(|(condition, selection, exclusion)| (condition.clone(), selection.clone(), exclusion.clone()))
+            .collect();
+
+        let clauses = self.clauses.iter()
+            .cloned()
+            .collect();
+
+        let cardinalities = self.cardinalities.iter()
+            .map(|(family, cardinality)| (family.key().to_string(), *cardinality))
+            .collect();
+
+        let soft_exclusions = self.soft_exclusions.iter()
+            .map(|(selection, exclusion, penalty)| (selection.clone(), exclusion.clone(), *penalty))
+            .collect();
+
+        let spec = ClosetSpec { families, exclusions, inclusions, conditional_exclusions, clauses, cardinalities, soft_exclusions };
+        serde_json::to_string(&spec).expect("expected closet spec to serialize")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet_builder::ClosetBuilder;
+    use closet_builder::ClosetBuilderError;
+    use core::CardinalitySpec;
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+
+    #[test]
+    fn json_spec_builds_same_closet_as_builder() {
+        let spec = r#"{
+            "families": {
+                "shirts": ["shirts:blue", "shirts:red"],
+                "pants": ["pants:jeans", "pants:slacks"]
+            },
+            "exclusions": [["shirts:red", "pants:jeans"]],
+            "inclusions": [["pants:slacks", "shirts:blue"]]
+        }"#;
+
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let expected = ClosetBuilder::new()
+            .add_items(&shirts, &[blue.clone(), red.clone()])
+            .add_items(&pants, &[jeans.clone(), slacks.clone()])
+            .add_exclusion_rule(&red, &jeans)
+            .add_inclusion_rule(&slacks, &blue);
+
+        let closet_builder = ClosetBuilder::from_json(spec).unwrap();
+        assert_eq!(expected, closet_builder);
+
+        let closet = closet_builder.must_build();
+        assert_eq!(Ok(Outfit::new(vec![jeans.clone(), blue.clone()])), closet.complete_outfit(vec![blue]));
+        assert!(closet.complete_outfit(vec![red, jeans]).is_err());
+    }
+
+    #[test]
+    fn rules_are_optional() {
+        let closet = ClosetBuilder::from_json(r#"{"families": {"shirts": ["shirts:blue"]}}"#)
+            .unwrap()
+            .must_build();
+
+        assert_eq!(vec![&Item::new("shirts:blue")], closet.items());
+    }
+
+    #[test]
+    fn rule_with_undeclared_item_is_rejected() {
+        let spec = r#"{
+            "families": {"shirts": ["shirts:blue"], "pants": ["pants:jeans"]},
+            "exclusions": [["shirts:green", "pants:jeans"]],
+            "inclusions": [["shirts:blue", "shoes:boots"]]
+        }"#;
+
+        assert_eq!(
+            Err(ClosetBuilderError::CompoundError(vec![
+                ClosetBuilderError::MissingFamily(Item::new("shirts:green")),
+                ClosetBuilderError::MissingFamily(Item::new("shoes:boots")),
+            ])),
+            ClosetBuilder::from_json(spec)
+        );
+    }
+
+    #[test]
+    fn to_json_round_trips_through_from_json() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_items(&shirts, &[blue.clone(), red.clone()])
+            .add_items(&pants, &[jeans.clone(), slacks.clone()])
+            .add_exclusion_rule(&red, &jeans)
+            .add_inclusion_rule(&slacks, &blue);
+
+        let json = closet_builder.to_json();
+        assert_eq!(
+            r#"{"families":{"pants":["pants:jeans","pants:slacks"],"shirts":["shirts:blue","shirts:red"]},"exclusions":[["shirts:red","pants:jeans"]],"inclusions":[["pants:slacks","shirts:blue"]]}"#,
+            json
+        );
+        assert_eq!(Ok(closet_builder), ClosetBuilder::from_json(&json));
+    }
+
+    #[test]
+    fn clauses_round_trip_through_json() {
+        let blazer = Item::new("jackets:blazer");
+        let denim = Item::new("jackets:denim");
+        let slacks = Item::new("pants:slacks");
+        let jeans = Item::new("pants:jeans");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_items(&Family::new("jackets"), &[blazer.clone(), denim.clone()])
+            .add_items(&Family::new("pants"), &[jeans.clone(), slacks.clone()])
+            .add_clause(&[blazer, slacks]);
+
+        let json = closet_builder.to_json();
+        assert!(json.contains(r#""clauses":[["jackets:blazer","pants:slacks"]]"#), "{}", json);
+
+        let restored = ClosetBuilder::from_json(&json).unwrap();
+        assert_eq!(closet_builder, restored);
+        assert_eq!(3, restored.must_build().outfit_count());
+    }
+
+    #[test]
+    fn conditional_exclusions_round_trip_through_json() {
+        let fedora = Item::new("hats:fedora");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_items(&Family::new("hats"), &[fedora.clone(), Item::new("hats:cap")])
+            .add_items(&Family::new("shirts"), &[Item::new("shirts:blue"), red.clone()])
+            .add_items(&Family::new("pants"), &[jeans.clone(), Item::new("pants:slacks")])
+            .add_conditional_exclusion(&[fedora], (&red, &jeans));
+
+        let json = closet_builder.to_json();
+        assert!(json.contains(r#""conditional_exclusions":[[["hats:fedora"],"shirts:red","pants:jeans"]]"#), "{}", json);
+
+        let restored = ClosetBuilder::from_json(&json).unwrap();
+        assert_eq!(closet_builder, restored);
+        assert_eq!(7, restored.must_build().outfit_count());
+    }
+
+    #[test]
+    fn cardinalities_round_trip_through_json() {
+        let accessories = Family::new("accessories");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_items(&accessories, &[Item::new("accessories:belt"), Item::new("accessories:hat"), Item::new("accessories:watch")])
+            .add_items(&Family::new("shirts"), &[Item::new("shirts:blue"), Item::new("shirts:red")])
+            .set_family_cardinality(&accessories, CardinalitySpec::Exactly(2));
+
+        let json = closet_builder.to_json();
+        assert!(json.contains(r#""cardinalities":{"accessories":{"Exactly":2}}"#), "{}", json);
+
+        let restored = ClosetBuilder::from_json(&json).unwrap();
+        assert_eq!(closet_builder, restored);
+        assert_eq!(6, restored.must_build().outfit_count());
+    }
+
+    #[test]
+    fn soft_exclusions_round_trip_through_json() {
+        let blue = Item::new("shirts:blue");
+        let jeans = Item::new("pants:jeans");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_items(&Family::new("shirts"), &[blue.clone(), Item::new("shirts:red")])
+            .add_items(&Family::new("pants"), &[jeans.clone(), Item::new("pants:slacks")])
+            .add_soft_exclusion(&blue, &jeans, 5);
+
+        let json = closet_builder.to_json();
+        assert!(json.contains(r#""soft_exclusions":[["shirts:blue","pants:jeans",5]]"#), "{}", json);
+
+        let restored = ClosetBuilder::from_json(&json).unwrap();
+        assert_eq!(closet_builder, restored);
+        assert_eq!(
+            Ok(Outfit::new(vec![Item::new("pants:slacks"), blue.clone()])),
+            restored.must_build().complete_outfit_soft(vec![blue])
+        );
+    }
+
+    #[test]
+    fn to_json_writes_symmetric_exclusions_once() {
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&Family::new("shirts"), &red)
+            .add_item(&Family::new("pants"), &jeans)
+            .add_exclusion_rule(&red, &jeans)
+            .add_exclusion_rule(&jeans, &red);
+
+        let json = closet_builder.to_json();
+        assert!(json.contains(r#""exclusions":[["shirts:red","pants:jeans"]]"#), "{}", json);
+
+        let restored = ClosetBuilder::from_json(&json).unwrap();
+        assert_eq!(closet_builder.must_build(), restored.must_build());
+    }
+
+    #[test]
+    fn scoped_items_round_trip_through_json() {
+        let shirts = Family::new("shirts");
+        let shoes = Family::new("shoes");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_scoped_item(&shirts, &Item::new("black"))
+            .add_scoped_item(&shirts, &Item::new("white"))
+            .add_scoped_item(&shoes, &Item::new("black"))
+            .add_scoped_item(&shoes, &Item::new("brown"))
+            .add_exclusion_rule(&Item::scoped(&shirts, "black"), &Item::scoped(&shoes, "black"));
+
+        let json = closet_builder.to_json();
+        assert!(json.contains(r#""exclusions":[[["shirts","black"],["shoes","black"]]]"#), "{}", json);
+
+        let restored = ClosetBuilder::from_json(&json).unwrap();
+        assert_eq!(closet_builder, restored);
+        assert_eq!(3, restored.must_build().outfit_count());
+    }
+
+    #[test]
+    fn malformed_json_is_rejected() {
+        let error = ClosetBuilder::from_json(r#"{"families": ["shirts:blue"]}"#)
+            .expect_err("expected MalformedSpec");
+
+        match error {
+            ClosetBuilderError::MalformedSpec(message) => assert!(message.contains("invalid type"), "{}", message),
+            error => panic!("expected MalformedSpec, but was {:?}", error),
+        }
+    }
+}
@@ -2,48 +2,170 @@ use bdd::closet::Closet;
 use bdd::node::Node;
 use closet_builder::ClosetBuilderError;
 use closet_builder::validate_closet;
+use core::CardinalitySpec;
 use core::Family;
 use core::Item;
+use im::OrdMap;
+use im::Vector;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
 
-#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg(feature = "json")]
+mod json;
+
+// Each collection is a persistent `im` collection, so cloning a builder is cheap and forks of a
+// shared base share structure; a change copies only the part of the collection it touches.
+#[derive(Debug, Clone, Default)]
 pub struct ClosetBuilder {
-    contents: BTreeMap<Family, Vec<Item>>,
-    item_index: BTreeMap<Item, Family>,
-    exclusions: BTreeMap<Item, Vec<Item>>,
-    inclusions: BTreeMap<Item, Vec<Item>>,
+    contents: OrdMap<Family, Vec<Item>>,
+    item_index: OrdMap<Item, Family>,
+    exclusions: OrdMap<Item, Vec<Item>>,
+    inclusions: OrdMap<Item, Vec<Item>>,
+    conditional_exclusions: Vector<(Vec<Item>, Item, Item)>,
+    clauses: Vector<Vec<Item>>,
+    soft_exclusions: Vector<(Item, Item, i64)>,
+    cardinalities: OrdMap<Family, CardinalitySpec>,
+    duplicate_rules: Vector<(Item, Item)>,
+    normalizer: Option<fn(&str) -> String>,
+}
+
+// The normalizer is left out since function pointers can't be compared reliably;
+// items it has already normalized are compared as usual.
+// Duplicate rules are left out as well, since they were never stored.
+impl PartialEq for ClosetBuilder {
+    fn eq(&self, other: &ClosetBuilder) -> bool {
+        self.contents == other.contents
+            && self.item_index == other.item_index
+            && self.exclusions == other.exclusions
+            && self.inclusions == other.inclusions
+            && self.conditional_exclusions == other.conditional_exclusions
+            && self.clauses == other.clauses
+            && self.soft_exclusions == other.soft_exclusions
+            && self.cardinalities == other.cardinalities
+    }
 }
 
 impl ClosetBuilder {
     pub fn new() -> ClosetBuilder {
         ClosetBuilder {
-            contents: BTreeMap::new(),
-            item_index: BTreeMap::new(),
-            exclusions: BTreeMap::new(),
-            inclusions: BTreeMap::new(),
+            contents: OrdMap::new(),
+            item_index: OrdMap::new(),
+            exclusions: OrdMap::new(),
+            inclusions: OrdMap::new(),
+            conditional_exclusions: Vector::new(),
+            clauses: Vector::new(),
+            soft_exclusions: Vector::new(),
+            cardinalities: OrdMap::new(),
+            duplicate_rules: Vector::new(),
+            normalizer: None,
+        }
+    }
+
+    /// Creates a builder that passes every item id through `normalizer` before storing it,
+    /// e.g. to lowercase names so that `Blue` and `blue` are the same item.
+    /// Items are still compared and ordered by their normalized ids, so lookups on the built
+    /// closet must use normalized items as well.
+    pub fn new_with_normalizer(normalizer: fn(&str) -> String) -> ClosetBuilder {
+        ClosetBuilder {
+            normalizer: Some(normalizer),
+            ..ClosetBuilder::new()
         }
     }
 
+    fn normalize(&self, item: &Item) -> Item {
+        match self.normalizer {
+            None => item.clone(),
+            Some(normalizer) => item.renamed(normalizer(&item.to_string())),
+        }
+    }
+
+    /// Adds `item` to `family`. Adding an item to a family that already lists it changes nothing,
+    /// while adding it to a second family is reported by `build` as `ConflictingFamilies`.
     pub fn add_item(mut self, family: &Family, item: &Item) -> ClosetBuilder {
+        let item = &self.normalize(item);
+        // Every listed item is indexed under its family, so the index finds repeats without scanning the family.
+        match self.item_index.get(item) {
+            Some(existing) if existing == family => return self,
+            Some(_) => {}
+            None => {
+                self.item_index.insert(item.clone(), family.clone());
+            }
+        }
         self.contents.entry(family.clone())
             .or_insert_with(|| vec![])
             .push(item.clone());
 
-        self.item_index.entry(item.clone())
-            .or_insert_with(|| family.clone());
-
         self
     }
 
+    /// Adds `item` to `family` scoped to the family, as `Item::scoped` does, so the same name can be listed in several families.
+    /// The name is normalized like any other item.
+    pub fn add_scoped_item(self, family: &Family, item: &Item) -> ClosetBuilder {
+        let scoped = item.scoped_to(family);
+
+        self.add_item(family, &scoped)
+    }
+
+    /// Like `add_item`, but fails right away with `ConflictingFamilies` when the item already belongs to another family,
+    /// instead of leaving the conflict for `build` to report.
+    pub fn try_add_item(self, family: &Family, item: &Item) -> Result<ClosetBuilder, ClosetBuilderError> {
+        let normalized = self.normalize(item);
+        match self.item_index.get(&normalized) {
+            Some(existing) if existing != family =>
+                Err(ClosetBuilderError::ConflictingFamilies(normalized, vec![existing.clone(), family.clone()])),
+            _ => Ok(self.add_item(family, item)),
+        }
+    }
+
     pub fn add_items(self, family: &Family, items: &[Item]) -> ClosetBuilder {
         items.iter()
             .fold(self, |closet_builder, item| closet_builder.add_item(family, item))
     }
 
-    pub fn add_exclusion_rule(mut self, selection: &Item, exclusion: &Item) -> ClosetBuilder {
-        self.exclusions.entry(selection.clone())
+    /// Adds each `(family, item)` row in order, as `add_item` would.
+    pub fn extend<I: IntoIterator<Item=(Family, Item)>>(self, rows: I) -> ClosetBuilder {
+        rows.into_iter()
+            .fold(self, |closet_builder, (family, item)| closet_builder.add_item(&family, &item))
+    }
+
+    /// Sets how many items of `family` an outfit contains, e.g. `CardinalitySpec::Exactly(2)` for a pair.
+    /// Families that allow several items may have exclusion rules between their own items.
+    pub fn set_family_cardinality(mut self, family: &Family, cardinality: CardinalitySpec) -> ClosetBuilder {
+        self.cardinalities.insert(family.clone(), cardinality);
+
+        self
+    }
+
+    /// How many items have been added to `family`, or 0 for a family that hasn't been declared.
+    pub fn family_size(&self, family: &Family) -> usize {
+        self.contents.get(family)
+            .map(|items| items.len())
+            .unwrap_or(0)
+    }
+
+    /// Starts adding items to `family`, e.g. `builder.family(&shirts).with(&[blue, red])`.
+    pub fn family(self, family: &Family) -> FamilyBuilder {
+        FamilyBuilder { closet_builder: self, family: family.clone() }
+    }
+
+    /// Exclusions are symmetric, so an exclusion already added in either direction is
+    /// recorded as a duplicate instead of being stored again.
+    pub fn add_exclusion_rule(self, selection: &Item, exclusion: &Item) -> ClosetBuilder {
+        let (selection, exclusion) = (self.normalize(selection), self.normalize(exclusion));
+        self.insert_exclusion(selection, exclusion)
+    }
+
+    /// Adds an exclusion between already normalized items, recording it as a repeat if it is known in either direction.
+    fn insert_exclusion(mut self, selection: Item, exclusion: Item) -> ClosetBuilder {
+        if ClosetBuilder::has_rule(&self.exclusions, &selection, &exclusion) || ClosetBuilder::has_rule(&self.exclusions, &exclusion, &selection) {
+            self.duplicate_rules.push_back((selection, exclusion));
+            return self;
+        }
+
+        self.exclusions.entry(selection)
             .or_insert_with(|| vec![])
-            .push(exclusion.clone());
+            .push(exclusion);
 
         self
     }
@@ -53,10 +175,56 @@ impl ClosetBuilder {
             .fold(self, |closet_builder, item| closet_builder.add_exclusion_rule(selection, item))
     }
 
+    pub fn remove_exclusion_rule(mut self, selection: &Item, exclusion: &Item) -> ClosetBuilder {
+        let (selection, exclusion) = (&self.normalize(selection), &self.normalize(exclusion));
+        ClosetBuilder::remove_rule(&mut self.exclusions, selection, exclusion);
+        ClosetBuilder::remove_rule(&mut self.exclusions, exclusion, selection);
+        self.duplicate_rules.retain(|rule| *rule != (selection.clone(), exclusion.clone()) && *rule != (exclusion.clone(), selection.clone()));
+
+        self
+    }
+
+    pub fn add_conditional_exclusion(mut self, condition: &[Item], excluded_pair: (&Item, &Item)) -> ClosetBuilder {
+        let (selection, exclusion) = excluded_pair;
+        let condition = condition.iter()
+            .map(|item| self.normalize(item))
+            .collect();
+        let rule = (condition, self.normalize(selection), self.normalize(exclusion));
+        self.conditional_exclusions.push_back(rule);
+
+        self
+    }
+
+    /// Requires at least one of `required_any` to be part of every outfit.
+    /// An empty clause could never be met, so `build` rejects it with `EmptyClause`.
+    pub fn add_clause(mut self, required_any: &[Item]) -> ClosetBuilder {
+        let clause = required_any.iter()
+            .map(|item| self.normalize(item))
+            .collect();
+        self.clauses.push_back(clause);
+
+        self
+    }
+
+    /// Discourages outfits with both items without ruling them out: `Closet::complete_outfit_soft`
+    /// adds `penalty` for every soft exclusion an outfit breaks and picks the outfit with the lowest total.
+    pub fn add_soft_exclusion(mut self, selection: &Item, exclusion: &Item, penalty: i64) -> ClosetBuilder {
+        let rule = (self.normalize(selection), self.normalize(exclusion), penalty);
+        self.soft_exclusions.push_back(rule);
+
+        self
+    }
+
     pub fn add_inclusion_rule(mut self, selection: &Item, inclusion: &Item) -> ClosetBuilder {
-        self.inclusions.entry(selection.clone())
+        let (selection, inclusion) = (self.normalize(selection), self.normalize(inclusion));
+        if ClosetBuilder::has_rule(&self.inclusions, &selection, &inclusion) {
+            self.duplicate_rules.push_back((selection, inclusion));
+            return self;
+        }
+
+        self.inclusions.entry(selection)
             .or_insert_with(|| vec![])
-            .push(inclusion.clone());
+            .push(inclusion);
 
         self
     }
@@ -66,32 +234,264 @@ impl ClosetBuilder {
             .fold(self, |closet_builder, item| closet_builder.add_inclusion_rule(selection, item))
     }
 
+    pub fn add_inclusion_rule_many(mut self, selection: &Item, inclusions: &[Item]) -> ClosetBuilder {
+        let selection = self.normalize(selection);
+        let inclusions = inclusions.iter()
+            .map(|inclusion| self.normalize(inclusion))
+            .collect::<Vec<_>>();
+        let rules = self.inclusions.entry(selection.clone())
+            .or_default();
+
+        for inclusion in inclusions {
+            if !rules.contains(&inclusion) {
+                rules.push(inclusion);
+            } else {
+                self.duplicate_rules.push_back((selection.clone(), inclusion));
+            }
+        }
+
+        self
+    }
+
+    pub fn add_coupling_rule(self, item: &Item, other: &Item) -> ClosetBuilder {
+        self.add_inclusion_rule(item, other)
+            .add_inclusion_rule(other, item)
+    }
+
+    pub fn remove_inclusion_rule(mut self, selection: &Item, inclusion: &Item) -> ClosetBuilder {
+        let (selection, inclusion) = (&self.normalize(selection), &self.normalize(inclusion));
+        ClosetBuilder::remove_rule(&mut self.inclusions, selection, inclusion);
+        self.duplicate_rules.retain(|rule| *rule != (selection.clone(), inclusion.clone()));
+
+        self
+    }
+
+    fn has_rule(rules: &OrdMap<Item, Vec<Item>>, selection: &Item, item: &Item) -> bool {
+        match rules.get(selection) {
+            None => false,
+            Some(items) => items.contains(item),
+        }
+    }
+
+    /// Reports the exclusion and inclusion rules that were added more than once, counting an exclusion
+    /// given in both directions as a repeat. Each rule is reported once, as `(selection, item)`.
+    /// Rules between items of one family are left out: `build` rejects them, unless the family allows
+    /// several items, in which case they do constrain the closet.
+    pub fn redundant_rules(&self) -> Vec<(Item, Item)> {
+        self.duplicate_rules.iter()
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    fn remove_rule(rules: &mut OrdMap<Item, Vec<Item>>, selection: &Item, item: &Item) {
+        let is_empty = match rules.get_mut(selection) {
+            None => return,
+            Some(items) => {
+                items.retain(|rule_item| rule_item != item);
+                items.is_empty()
+            }
+        };
+
+        if is_empty {
+            rules.remove(selection);
+        }
+    }
+
+    /// Keeps this builder's normalizer; items from `other` are merged as `other` stored them.
+    pub fn merge(self, other: ClosetBuilder) -> Result<ClosetBuilder, ClosetBuilderError> {
+        let conflicts = other.item_index.iter()
+            .filter_map(|(item, other_family)| match self.item_index.get(item) {
+                Some(family) if family != other_family =>
+                    Some(ClosetBuilderError::ConflictingFamilies(item.clone(), vec![family.clone(), other_family.clone()])),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        match conflicts.len() {
+            0 => {}
+            1 => return Err(conflicts[0].clone()),
+            _ => return Err(ClosetBuilderError::CompoundError(conflicts)),
+        }
+
+        let item_index = other.item_index.into_iter()
+            .fold(self.item_index, |mut item_index, (item, family)| {
+                item_index.entry(item).or_insert(family);
+                item_index
+            });
+
+        let merged = ClosetBuilder {
+            contents: ClosetBuilder::merge_entries(self.contents, other.contents),
+            item_index,
+            exclusions: self.exclusions,
+            inclusions: ClosetBuilder::merge_entries(self.inclusions, other.inclusions),
+            conditional_exclusions: other.conditional_exclusions.into_iter()
+                .fold(self.conditional_exclusions, |mut conditional_exclusions, rule| {
+                    if !conditional_exclusions.contains(&rule) {
+                        conditional_exclusions.push_back(rule);
+                    }
+                    conditional_exclusions
+                }),
+            clauses: other.clauses.into_iter()
+                .fold(self.clauses, |mut clauses, clause| {
+                    if !clauses.contains(&clause) {
+                        clauses.push_back(clause);
+                    }
+                    clauses
+                }),
+            soft_exclusions: other.soft_exclusions.into_iter()
+                .fold(self.soft_exclusions, |mut soft_exclusions, rule| {
+                    if !soft_exclusions.contains(&rule) {
+                        soft_exclusions.push_back(rule);
+                    }
+                    soft_exclusions
+                }),
+            cardinalities: other.cardinalities.into_iter()
+                .fold(self.cardinalities, |mut cardinalities, (family, cardinality)| {
+                    cardinalities.entry(family).or_insert(cardinality);
+                    cardinalities
+                }),
+            duplicate_rules: self.duplicate_rules + other.duplicate_rules,
+            normalizer: self.normalizer,
+        };
+
+        // Exclusions go both ways, so one stored the other way around in `other` is still a repeat.
+        Ok(other.exclusions.into_iter()
+            .flat_map(|(selection, exclusions)| exclusions.into_iter().map(move |exclusion| (selection.clone(), exclusion)))
+            .fold(merged, |merged, (selection, exclusion)| merged.insert_exclusion(selection, exclusion)))
+    }
+
+    fn merge_entries<K: Ord + Clone>(entries: OrdMap<K, Vec<Item>>, other: OrdMap<K, Vec<Item>>) -> OrdMap<K, Vec<Item>> {
+        other.into_iter()
+            .fold(entries, |mut entries, (key, items)| {
+                let merged = entries.entry(key).or_insert_with(Vec::new);
+                for item in items {
+                    if !merged.contains(&item) {
+                        merged.push(item);
+                    }
+                }
+                entries
+            })
+    }
+
+    /// Runs every check `build` makes without building the closet, returning all of the problems found.
+    pub fn validate_preview(&self) -> Result<(), Vec<ClosetBuilderError>> {
+        validate_closet(&self.contents, &self.item_index, &self.exclusions, &self.inclusions, &self.conditional_exclusions, &self.clauses, &self.soft_exclusions, &self.cardinalities)
+            .map_err(|error| match error {
+                ClosetBuilderError::CompoundError(errors) => errors,
+                error => vec![error],
+            })
+    }
+
+    /// Runs the checks `build` makes, then checks that every family that needs an item can still get one.
+    /// Unlike `validate_preview`, this catches rules that together leave a family without any item.
+    /// A family is only reported when each of its items is ruled out by the rules and the other families' constraints,
+    /// and those allow some outfit on their own, so one broken family doesn't take the others with it.
+    pub fn deep_validate(&self) -> Result<(), ClosetBuilderError> {
+        validate_closet(&self.contents, &self.item_index, &self.exclusions, &self.inclusions, &self.conditional_exclusions, &self.clauses, &self.soft_exclusions, &self.cardinalities)?;
+
+        let rules = self.rules();
+        let relationships = self.contents.iter()
+            .map(|(family, items)| (family, ClosetBuilder::family_relationship(items, self.cardinalities.get(family).copied().unwrap_or_default())))
+            .collect::<Vec<_>>();
+
+        let mut errors = self.contents.iter()
+            .filter(|&(family, _)| self.cardinalities.get(family).copied().unwrap_or_default().bounds().0 > 0)
+            .filter(|&(family, items)| {
+                let rules = relationships.iter()
+                    .filter(|&&(other, _)| other != family)
+                    .fold(rules.clone(), |rules, (_, relationship)| rules & relationship.clone());
+
+                rules != Node::FALSE_LEAF && items.iter().all(|item| Node::restrict(&rules, item, true) == Node::FALSE_LEAF)
+            })
+            .map(|(family, _)| ClosetBuilderError::FamilyUnsatisfiable(family.clone()))
+            .collect::<Vec<_>>();
+
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(ClosetBuilderError::CompoundError(errors)),
+        }
+    }
+
     pub fn must_build(self) -> Closet {
         self.build().expect("expected build to return Closet")
     }
 
     pub fn build(&self) -> Result<Closet, ClosetBuilderError> {
-        validate_closet(&self.contents, &self.item_index, &self.exclusions, &self.inclusions)?;
+        validate_closet(&self.contents, &self.item_index, &self.exclusions, &self.inclusions, &self.conditional_exclusions, &self.clauses, &self.soft_exclusions, &self.cardinalities)?;
 
-        let root = self.contents.iter()
-            .map(|(_, items)| ClosetBuilder::sibling_relationship(items))
+        let families = self.contents.iter()
+            .map(|(family, items)| ClosetBuilder::family_relationship(items, self.cardinalities.get(family).copied().unwrap_or_default()))
             .fold(Node::TRUE_LEAF, |other, family_node| other & family_node);
 
-        let root = self.exclusions.iter()
+        let rules = self.rules();
+        let root = families & rules.clone();
+
+        let item_index = self.item_index.iter().map(|(item, family)| (item.clone(), family.clone())).collect();
+        Ok(Closet::with_rules(item_index, root, Some(rules))
+            .with_cardinalities(self.cardinalities.iter().map(|(family, cardinality)| (family.clone(), *cardinality)).collect())
+            .with_soft_exclusions(self.soft_exclusions.iter().cloned().collect()))
+    }
+
+    /// The rules without the family constraints.
+    fn rules(&self) -> Node {
+        let rules = self.exclusions.iter()
             .flat_map(|(selection, exclusions)| exclusions.iter().map(|exclusion| (selection, exclusion)).collect::<Vec<_>>())
             .map(|(selection, exclusion)| ClosetBuilder::exclusion_relationship(selection, exclusion))
-            .fold(root, |new_root, exclusion| new_root & exclusion);
+            .fold(Node::TRUE_LEAF, |new_root, exclusion| new_root & exclusion);
 
-        let root = self.inclusions.iter()
+        let rules = self.inclusions.iter()
             .flat_map(|(selection, inclusions)| inclusions.iter().map(|exclusion| (selection, exclusion)).collect::<Vec<_>>())
             .map(|(selection, inclusion)| ClosetBuilder::inclusion_relationship(selection, inclusion))
-            .fold(root, |new_root, inclusion| new_root & inclusion);
+            .fold(rules, |new_root, inclusion| new_root & inclusion);
+
+        let rules = self.conditional_exclusions.iter()
+            .map(|(condition, selection, exclusion)| ClosetBuilder::conditional_exclusion_relationship(condition, selection, exclusion))
+            .fold(rules, |new_root, conditional_exclusion| new_root & conditional_exclusion);
+
+        self.clauses.iter()
+            .map(|clause| ClosetBuilder::clause_relationship(clause))
+            .fold(rules, |new_root, clause| new_root & clause)
+    }
+
+    /// The items are sorted first, so the family's diagram doesn't depend on the order they were added in.
+    pub(crate) fn family_relationship(items: &[Item], cardinality: CardinalitySpec) -> Node {
+        let mut items = items.to_vec();
+        items.sort();
+        items.dedup();
+
+        match cardinality {
+            CardinalitySpec::ExactlyOne => ClosetBuilder::sibling_relationship(&items),
+            _ => {
+                let (min, max) = cardinality.bounds();
+                ClosetBuilder::cardinality_relationship(&items, 0, 0, (min, max), &mut HashMap::new())
+            }
+        }
+    }
+
+    fn cardinality_relationship(items: &[Item], index: usize, count: usize, bounds: (usize, usize), memo: &mut HashMap<(usize, usize), Node>) -> Node {
+        let (min, max) = bounds;
+        if count > max {
+            return Node::FALSE_LEAF;
+        }
+        if index == items.len() {
+            return Node::Leaf(count >= min);
+        }
+        if let Some(node) = memo.get(&(index, count)) {
+            return node.clone();
+        }
 
-        let item_index = self.item_index.clone();
-        Ok(Closet::new(item_index, root))
+        let low = ClosetBuilder::cardinality_relationship(items, index + 1, count, bounds, memo);
+        let high = ClosetBuilder::cardinality_relationship(items, index + 1, count + 1, bounds, memo);
+
+        let node = Node::branch(&items[index], low, high);
+        memo.insert((index, count), node.clone());
+        node
     }
 
-    fn sibling_relationship(items: &[Item]) -> Node {
+    pub(crate) fn sibling_relationship(items: &[Item]) -> Node {
         let all_nodes = items.iter()
             .map(|item| (item, Node::negative_branch(item)))
             .collect::<BTreeMap<&Item, Node>>();
@@ -111,9 +511,39 @@ impl ClosetBuilder {
         Node::negative_branch(selection) | Node::negative_branch(exclusion)
     }
 
+    fn conditional_exclusion_relationship(condition: &[Item], selection: &Item, exclusion: &Item) -> Node {
+        let condition = condition.iter()
+            .fold(Node::TRUE_LEAF, |new_root, item| new_root & Node::positive_branch(item));
+
+        Node::ite(&condition, &ClosetBuilder::exclusion_relationship(selection, exclusion), &Node::TRUE_LEAF)
+    }
+
     fn inclusion_relationship(selection: &Item, exclusion: &Item) -> Node {
         Node::negative_branch(selection) | Node::positive_branch(exclusion)
     }
+
+    fn clause_relationship(required_any: &[Item]) -> Node {
+        required_any.iter()
+            .fold(Node::FALSE_LEAF, |new_root, item| new_root | Node::positive_branch(item))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FamilyBuilder {
+    closet_builder: ClosetBuilder,
+    family: Family,
+}
+
+impl FamilyBuilder {
+    /// Adds `items` to the family. The family is declared even when `items` is empty,
+    /// which `build` then reports as an `EmptyFamily` error.
+    pub fn with(mut self, items: &[Item]) -> ClosetBuilder {
+        if !self.closet_builder.contents.contains_key(&self.family) {
+            self.closet_builder.contents.insert(self.family.clone(), vec![]);
+        }
+
+        self.closet_builder.add_items(&self.family, items)
+    }
 }
 
 #[cfg(test)]
@@ -500,7 +930,7 @@ mod exclude_rules_tests {
         let red_and_jeans_selected = closet
             .select_item(&blue).unwrap();
 
-        let expected = Node::positive_branch(&slacks) ^ Node::positive_branch(&jeans) & Node::negative_branch(&red);
+        let expected = (Node::positive_branch(&slacks) ^ Node::positive_branch(&jeans)) & Node::negative_branch(&red);
         assert_eq!(
             &expected,
             red_and_jeans_selected.root()
@@ -536,6 +966,76 @@ mod exclude_rules_tests {
             red_and_jeans_selected.root()
         );
     }
+
+    #[test]
+    fn removing_exclusion_rule_only_enforces_remaining_rule() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &slacks)
+            .add_item(&pants, &jeans)
+            .add_exclusion_rule(&red, &jeans)
+            .add_exclusion_rule(&blue, &slacks)
+            .remove_exclusion_rule(&jeans, &red);
+
+        let closet = closet_builder.must_build();
+
+        let expected = {
+            let shirts_branch = Node::positive_branch(&red) ^ Node::positive_branch(&blue);
+            let pants_branch = Node::positive_branch(&slacks) ^ Node::positive_branch(&jeans);
+
+            let exclusion = Node::negative_branch(&blue) | Node::negative_branch(&slacks);
+            let root = shirts_branch & pants_branch;
+
+            root & exclusion
+        };
+        assert_eq!(
+            &expected,
+            closet.root()
+        );
+
+        let red_selected = closet
+            .select_item(&red).unwrap();
+
+        let expected = (Node::positive_branch(&slacks) ^ Node::positive_branch(&jeans)) & Node::negative_branch(&blue);
+        assert_eq!(
+            &expected,
+            red_selected.root()
+        );
+    }
+
+    #[test]
+    fn removing_unknown_exclusion_rule_does_nothing() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &slacks)
+            .add_item(&pants, &jeans)
+            .add_exclusion_rule(&red, &jeans);
+
+        assert_eq!(
+            closet_builder.clone(),
+            closet_builder.remove_exclusion_rule(&blue, &slacks)
+        );
+    }
 }
 
 #[cfg(test)]
@@ -633,7 +1133,7 @@ mod include_rules_tests {
         let red_and_jeans_selected = closet
             .select_item(&blue).unwrap();
 
-        let expected = Node::positive_branch(&slacks) ^ Node::positive_branch(&jeans) & Node::negative_branch(&red);
+        let expected = (Node::positive_branch(&slacks) ^ Node::positive_branch(&jeans)) & Node::negative_branch(&red);
         assert_eq!(
             &expected,
             red_and_jeans_selected.root()
@@ -699,4 +1199,1354 @@ mod include_rules_tests {
             red_and_jeans_selected.root()
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn removing_inclusion_rule_only_enforces_remaining_rule() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &slacks)
+            .add_item(&pants, &jeans)
+            .add_inclusion_rule(&red, &jeans)
+            .add_inclusion_rule(&blue, &slacks)
+            .remove_inclusion_rule(&red, &jeans);
+
+        let closet = closet_builder.must_build();
+
+        let red_selected = closet
+            .select_item(&red).unwrap();
+
+        let expected = (Node::positive_branch(&slacks) ^ Node::positive_branch(&jeans)) & Node::negative_branch(&blue);
+        assert_eq!(
+            &expected,
+            red_selected.root()
+        );
+
+        let blue_selected = closet
+            .select_item(&blue).unwrap();
+
+        let expected = Node::positive_branch(&slacks) & Node::negative_branch(&jeans) & Node::negative_branch(&red);
+        assert_eq!(
+            &expected,
+            blue_selected.root()
+        );
+    }
+
+    #[test]
+    fn removing_inclusion_rule_is_one_way() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &slacks)
+            .add_item(&pants, &jeans)
+            .add_inclusion_rule(&red, &jeans);
+
+        assert_eq!(
+            closet_builder.clone(),
+            closet_builder.remove_inclusion_rule(&jeans, &red)
+        );
+    }
+}
+
+#[cfg(test)]
+mod inclusion_rule_many_tests {
+    use closet_builder::ClosetBuilderError;
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+    use super::ClosetBuilder;
+
+    fn closet_builder() -> ClosetBuilder {
+        let jackets = Family::new("jackets");
+        let shirts = Family::new("shirts");
+        let shoes = Family::new("shoes");
+
+        ClosetBuilder::new()
+            .add_item(&jackets, &Item::new("jackets:wedding"))
+            .add_item(&jackets, &Item::new("jackets:denim"))
+            .add_item(&shirts, &Item::new("shirts:white"))
+            .add_item(&shirts, &Item::new("shirts:plaid"))
+            .add_item(&shoes, &Item::new("shoes:black"))
+            .add_item(&shoes, &Item::new("shoes:brown"))
+    }
+
+    #[test]
+    fn selection_requires_every_consequent() {
+        let wedding = Item::new("jackets:wedding");
+        let white = Item::new("shirts:white");
+        let black = Item::new("shoes:black");
+
+        let closet = closet_builder()
+            .add_inclusion_rule_many(&wedding, &[white.clone(), black.clone(), white.clone()])
+            .must_build();
+
+        assert_eq!(
+            Ok(Outfit::new(vec![wedding.clone(), white.clone(), black.clone()])),
+            closet.complete_outfit(vec![wedding.clone()])
+        );
+
+        let expected = closet_builder()
+            .add_inclusion_rule(&wedding, &white)
+            .add_inclusion_rule(&wedding, &black);
+        assert_eq!(
+            expected,
+            closet_builder().add_inclusion_rule_many(&wedding, &[white.clone(), black.clone(), white.clone()])
+        );
+    }
+
+    #[test]
+    fn consequents_in_selection_family_are_reported_together() {
+        let wedding = Item::new("jackets:wedding");
+        let denim = Item::new("jackets:denim");
+        let white = Item::new("shirts:white");
+
+        let result = closet_builder()
+            .add_item(&Family::new("jackets"), &Item::new("jackets:tweed"))
+            .add_inclusion_rule_many(&wedding, &[denim.clone(), white.clone(), Item::new("jackets:tweed")])
+            .build();
+
+        assert_eq!(
+            Err(ClosetBuilderError::InclusionError(Family::new("jackets"), vec![denim, Item::new("jackets:tweed"), wedding])),
+            result.map(|_| ())
+        );
+    }
+
+    #[test]
+    fn exclusions_in_selection_family_are_still_reported_per_pair() {
+        let wedding = Item::new("jackets:wedding");
+        let denim = Item::new("jackets:denim");
+        let tweed = Item::new("jackets:tweed");
+
+        let result = closet_builder()
+            .add_item(&Family::new("jackets"), &tweed)
+            .add_exclusion_rule(&wedding, &denim)
+            .add_exclusion_rule(&wedding, &tweed)
+            .build();
+
+        assert_eq!(
+            Err(ClosetBuilderError::CompoundError(vec![
+                ClosetBuilderError::ExclusionError(Family::new("jackets"), vec![denim, wedding.clone()]),
+                ClosetBuilderError::ExclusionError(Family::new("jackets"), vec![tweed, wedding]),
+            ])),
+            result.map(|_| ())
+        );
+    }
+}
+
+#[cfg(test)]
+mod coupling_rules_tests {
+    use bdd::node::Node;
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+    use super::ClosetBuilder;
+
+    #[test]
+    fn coupling_rule_adds_inclusion_in_both_directions() {
+        let blue = Item::new("shirts:blue");
+        let jeans = Item::new("pants:jeans");
+
+        let coupled = ClosetBuilder::new()
+            .add_coupling_rule(&blue, &jeans);
+
+        let expected = ClosetBuilder::new()
+            .add_inclusion_rule(&blue, &jeans)
+            .add_inclusion_rule(&jeans, &blue);
+
+        assert_eq!(expected, coupled);
+    }
+
+    #[test]
+    fn selecting_either_coupled_item_forces_the_other() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &slacks)
+            .add_item(&pants, &jeans)
+            .add_coupling_rule(&red, &slacks)
+            .must_build();
+
+        assert_eq!(Ok(Outfit::new(vec![slacks.clone(), red.clone()])), closet.complete_outfit(vec![red.clone()]));
+        assert_eq!(Ok(Outfit::new(vec![slacks.clone(), red.clone()])), closet.complete_outfit(vec![slacks.clone()]));
+
+        let expected = (Node::positive_branch(&red) & Node::positive_branch(&slacks) & Node::negative_branch(&blue) & Node::negative_branch(&jeans))
+            | (Node::positive_branch(&blue) & Node::positive_branch(&jeans) & Node::negative_branch(&red) & Node::negative_branch(&slacks));
+        assert_eq!(&expected, closet.root());
+    }
+
+    #[test]
+    fn coupling_rule_within_family_is_rejected() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let shirts = Family::new("shirts");
+
+        let result = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_coupling_rule(&red, &blue)
+            .build();
+
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(test)]
+mod conditional_exclusion_tests {
+    use closet_builder::ClosetBuilderError;
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+    use core::OutfitError;
+    use super::ClosetBuilder;
+
+    fn closet_builder() -> ClosetBuilder {
+        let shirts = Family::new("shirts");
+        let ties = Family::new("ties");
+        let shoes = Family::new("shoes");
+
+        ClosetBuilder::new()
+            .add_item(&shirts, &Item::new("shirts:blue"))
+            .add_item(&shirts, &Item::new("shirts:white"))
+            .add_item(&ties, &Item::new("ties:red"))
+            .add_item(&ties, &Item::new("ties:green"))
+            .add_item(&shoes, &Item::new("shoes:brown"))
+            .add_item(&shoes, &Item::new("shoes:black"))
+    }
+
+    #[test]
+    fn exclusion_applies_only_when_condition_is_selected() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("ties:red");
+        let brown = Item::new("shoes:brown");
+        let black = Item::new("shoes:black");
+
+        let closet = closet_builder()
+            .add_conditional_exclusion(&[Item::new("shoes:brown")], (&red, &blue))
+            .must_build();
+
+        assert_eq!(
+            Err(OutfitError::IncompatibleSelections(vec![blue.clone(), brown.clone(), red.clone()])),
+            closet.complete_outfit(vec![blue.clone(), red.clone(), brown.clone()])
+        );
+        assert_eq!(
+            Ok(Outfit::new(vec![blue.clone(), black.clone(), red.clone()])),
+            closet.complete_outfit(vec![blue.clone(), red.clone(), black.clone()])
+        );
+        assert_eq!(
+            Ok(Outfit::new(vec![blue.clone(), black.clone(), red.clone()])),
+            closet.complete_outfit(vec![blue.clone(), red.clone()])
+        );
+        assert_eq!(8 - 1, closet.outfit_count());
+    }
+
+    #[test]
+    fn empty_condition_is_a_plain_exclusion() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("ties:red");
+
+        let conditional = closet_builder()
+            .add_conditional_exclusion(&[], (&red, &blue))
+            .must_build();
+
+        let plain = closet_builder()
+            .add_exclusion_rule(&red, &blue)
+            .must_build();
+
+        assert_eq!(plain.root(), conditional.root());
+    }
+
+    #[test]
+    fn condition_sharing_family_with_excluded_pair_is_rejected() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("ties:red");
+
+        let result = closet_builder()
+            .add_conditional_exclusion(&[Item::new("shirts:white")], (&red, &blue))
+            .build();
+
+        assert_eq!(
+            Err(ClosetBuilderError::ExclusionError(Family::new("shirts"), vec![blue, Item::new("shirts:white")])),
+            result.map(|_| ())
+        );
+    }
+}
+
+#[cfg(test)]
+mod merge_tests {
+    use closet_builder::ClosetBuilderError;
+    use core::Family;
+    use core::Item;
+    use super::ClosetBuilder;
+
+    #[test]
+    fn merging_shirts_and_pants_combines_outfits() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let shirts_builder = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue);
+        let pants_builder = ClosetBuilder::new()
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .add_exclusion_rule(&jeans, &red);
+
+        let merged = shirts_builder.merge(pants_builder).unwrap();
+
+        let expected = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .add_exclusion_rule(&jeans, &red);
+        assert_eq!(expected, merged);
+
+        let closet = merged.must_build();
+        assert_eq!(expected.must_build().root(), closet.root());
+        assert_eq!(3, closet.outfit_count());
+    }
+
+    #[test]
+    fn merging_does_not_duplicate_items_or_rules() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let summer_builder = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &jeans)
+            .add_inclusion_rule(&blue, &jeans);
+        let winter_builder = ClosetBuilder::new()
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &jeans)
+            .add_inclusion_rule(&blue, &jeans);
+
+        let merged = summer_builder.clone().merge(winter_builder).unwrap();
+
+        assert_eq!(summer_builder, merged);
+    }
+
+    #[test]
+    fn merging_reversed_exclusion_keeps_one_rule_and_reports_the_repeat() {
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let summer_builder = ClosetBuilder::new()
+            .add_items(&shirts, &[red.clone(), Item::new("shirts:blue")])
+            .add_item(&pants, &jeans)
+            .add_exclusion_rule(&red, &jeans);
+        let winter_builder = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&pants, &jeans)
+            .add_exclusion_rule(&jeans, &red);
+
+        let merged = summer_builder.clone().merge(winter_builder).unwrap();
+
+        assert_eq!(summer_builder.clone().add_exclusion_rule(&jeans, &red), merged);
+        assert_eq!(vec![(jeans, red)], merged.redundant_rules());
+        assert_eq!(summer_builder.must_build(), merged.must_build());
+    }
+
+    #[test]
+    fn merging_item_with_different_families_returns_error() {
+        let blue = Item::new("blue");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let shirts_builder = ClosetBuilder::new()
+            .add_item(&shirts, &blue);
+        let pants_builder = ClosetBuilder::new()
+            .add_item(&pants, &blue);
+
+        assert_eq!(
+            Err(ClosetBuilderError::ConflictingFamilies(blue, vec![shirts, pants])),
+            shirts_builder.merge(pants_builder)
+        );
+    }
+}
+
+#[cfg(test)]
+mod scoped_item_tests {
+    use closet_builder::ClosetBuilderError;
+    use core::Family;
+    use core::Item;
+    use super::ClosetBuilder;
+
+    #[test]
+    fn scoped_items_keep_each_family_apart() {
+        let shirts = Family::new("shirts");
+        let shoes = Family::new("shoes");
+
+        let closet = ClosetBuilder::new()
+            .add_scoped_item(&shirts, &Item::new("black"))
+            .add_scoped_item(&shoes, &Item::new("black"))
+            .must_build();
+
+        assert_eq!(Some(&shirts), closet.get_family(&Item::scoped(&shirts, "black")));
+        assert_eq!(Some(&shoes), closet.get_family(&Item::scoped(&shoes, "black")));
+        assert_eq!(None, closet.get_family(&Item::new("shirts:black")));
+        assert_eq!(None, closet.get_family(&Item::new("black")));
+    }
+
+    #[test]
+    fn plain_item_named_like_a_scoped_one_does_not_conflict() {
+        let shirts = Family::new("shirts");
+        let shoes = Family::new("shoes");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &Item::new("shoes:black"))
+            .add_scoped_item(&shoes, &Item::new("black"))
+            .must_build();
+
+        assert_eq!(Some(&shirts), closet.get_family(&Item::new("shoes:black")));
+        assert_eq!(Some(&shoes), closet.get_family(&Item::scoped(&shoes, "black")));
+    }
+
+    #[test]
+    fn scoped_item_listed_in_another_family_conflicts() {
+        let shirts = Family::new("shirts");
+        let shoes = Family::new("shoes");
+        let black = Item::scoped(&shoes, "black");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&shirts, &black)
+            .add_scoped_item(&shoes, &Item::new("black"));
+
+        assert_eq!(
+            Err(ClosetBuilderError::ConflictingFamilies(black, vec![shirts, shoes])),
+            closet_builder.build()
+        );
+    }
+}
+
+#[cfg(test)]
+mod normalizer_tests {
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+    use super::ClosetBuilder;
+
+    fn lowercase(id: &str) -> String {
+        id.to_lowercase()
+    }
+
+    #[test]
+    fn normalizer_merges_items_with_different_casing() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new_with_normalizer(lowercase)
+            .add_item(&shirts, &Item::new("shirts:Blue"))
+            .add_item(&shirts, &Item::new("shirts:blue"))
+            .add_item(&shirts, &Item::new("shirts:red"))
+            .add_item(&pants, &Item::new("pants:Jeans"))
+            .add_item(&pants, &Item::new("pants:slacks"))
+            .add_exclusion_rule(&Item::new("shirts:BLUE"), &Item::new("pants:jeans"))
+            .must_build();
+
+        let blue = Item::new("shirts:blue");
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        assert_eq!(vec![&jeans, &slacks, &blue, &Item::new("shirts:red")], closet.items());
+        assert_eq!(Ok(Outfit::new(vec![slacks, blue.clone()])), closet.complete_outfit(vec![blue]));
+    }
+
+    #[test]
+    fn items_are_kept_as_given_without_normalizer() {
+        let shirts = Family::new("shirts");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &Item::new("shirts:Blue"))
+            .add_item(&shirts, &Item::new("shirts:blue"))
+            .must_build();
+
+        assert_eq!(vec![&Item::new("shirts:Blue"), &Item::new("shirts:blue")], closet.items());
+    }
+
+    #[test]
+    fn scoped_names_are_normalized() {
+        let shoes = Family::new("shoes");
+
+        let closet = ClosetBuilder::new_with_normalizer(lowercase)
+            .add_scoped_item(&shoes, &Item::new("Black"))
+            .add_scoped_item(&shoes, &Item::new("black"))
+            .must_build();
+
+        assert_eq!(vec![&Item::scoped(&shoes, "black")], closet.items());
+    }
+}
+
+#[cfg(test)]
+mod family_builder_tests {
+    use core::Family;
+    use core::Item;
+    use super::ClosetBuilder;
+
+    #[test]
+    fn bulk_added_family_matches_items_added_one_by_one() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let one_by_one = ClosetBuilder::new()
+            .add_item(&shirts, &blue)
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks);
+
+        let bulk = ClosetBuilder::new()
+            .add_items(&shirts, &[blue.clone(), red.clone(), blue.clone()])
+            .add_items(&pants, &[jeans.clone(), slacks.clone()]);
+
+        let fluent = ClosetBuilder::new()
+            .family(&shirts).with(&[blue.clone(), red.clone(), blue.clone()])
+            .family(&pants).with(&[jeans.clone(), slacks.clone()]);
+
+        assert_eq!(one_by_one, bulk);
+        assert_eq!(one_by_one, fluent);
+        assert_eq!(one_by_one.must_build(), fluent.must_build());
+    }
+
+    #[test]
+    fn repeated_items_are_listed_once() {
+        let shirts = Family::new("shirts");
+        let items = (0..1000)
+            .map(|index| Item::new(format!("shirts:{}", index % 500)))
+            .collect::<Vec<_>>();
+
+        let closet_builder = ClosetBuilder::new()
+            .add_items(&shirts, &items);
+
+        assert_eq!(500, closet_builder.family_size(&shirts));
+    }
+}
+
+#[cfg(test)]
+mod empty_family_tests {
+    use closet_builder::ClosetBuilderError;
+    use core::Family;
+    use core::Item;
+    use super::ClosetBuilder;
+
+    #[test]
+    fn rule_referencing_item_of_empty_family_is_rejected() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let boots = Item::new("shoes:boots");
+
+        let shirts = Family::new("shirts");
+        let shoes = Family::new("shoes");
+
+        let result = ClosetBuilder::new()
+            .family(&shirts).with(&[blue.clone(), red])
+            .family(&shoes).with(&[])
+            .add_exclusion_rule(&blue, &boots)
+            .build();
+
+        assert_eq!(
+            Err(ClosetBuilderError::CompoundError(vec![
+                ClosetBuilderError::EmptyFamily(shoes),
+                ClosetBuilderError::MissingFamily(boots),
+            ])),
+            result
+        );
+    }
+}
+
+#[cfg(test)]
+mod cardinality_tests {
+    use closet_builder::ClosetBuilderError;
+    use core::CardinalitySpec;
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+    use core::OutfitError;
+    use super::ClosetBuilder;
+
+    fn closet_builder() -> ClosetBuilder {
+        let shirts = Family::new("shirts");
+        let accessories = Family::new("accessories");
+
+        ClosetBuilder::new()
+            .add_items(&shirts, &[Item::new("shirts:blue"), Item::new("shirts:red")])
+            .add_items(&accessories, &[Item::new("accessories:belt"), Item::new("accessories:hat"), Item::new("accessories:watch")])
+    }
+
+    #[test]
+    fn exactly_two_family_completes_with_two_items() {
+        let accessories = Family::new("accessories");
+        let belt = Item::new("accessories:belt");
+        let hat = Item::new("accessories:hat");
+        let watch = Item::new("accessories:watch");
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let closet = closet_builder()
+            .set_family_cardinality(&accessories, CardinalitySpec::Exactly(2))
+            .add_exclusion_rule(&red, &belt)
+            .must_build();
+
+        assert_eq!(
+            Ok(Outfit::new(vec![belt.clone(), hat.clone(), blue.clone()])),
+            closet.complete_outfit(vec![])
+        );
+        assert_eq!(
+            Ok(Outfit::new(vec![hat.clone(), watch.clone(), red.clone()])),
+            closet.complete_outfit(vec![red.clone()])
+        );
+        assert_eq!(
+            Ok(Outfit::new(vec![belt.clone(), watch.clone(), blue.clone()])),
+            closet.complete_outfit(vec![watch.clone(), belt.clone()])
+        );
+        assert_eq!(4, closet.outfit_count());
+
+        assert!(closet.is_valid_outfit(&Outfit::new(vec![hat.clone(), watch.clone(), blue.clone()])));
+        assert!(!closet.is_valid_outfit(&Outfit::new(vec![hat.clone(), blue.clone()])));
+        assert!(!closet.is_valid_outfit(&Outfit::new(vec![belt.clone(), hat.clone(), watch.clone(), blue.clone()])));
+    }
+
+    #[test]
+    fn exactly_two_family_rejects_three_selections() {
+        let accessories = Family::new("accessories");
+        let belt = Item::new("accessories:belt");
+        let hat = Item::new("accessories:hat");
+        let watch = Item::new("accessories:watch");
+
+        let closet = closet_builder()
+            .set_family_cardinality(&accessories, CardinalitySpec::Exactly(2))
+            .must_build();
+
+        match closet.complete_outfit(vec![belt, hat, watch]) {
+            Err(OutfitError::MultipleItemsPerFamily(duplicates)) => assert_eq!(vec![&accessories], duplicates.keys().collect::<Vec<_>>()),
+            result => panic!("expected MultipleItemsPerFamily, but was {:?}", result),
+        }
+    }
+
+    #[test]
+    fn at_most_one_family_may_be_left_out() {
+        let accessories = Family::new("accessories");
+        let blue = Item::new("shirts:blue");
+
+        let closet = closet_builder()
+            .set_family_cardinality(&accessories, CardinalitySpec::AtMostOne)
+            .add_exclusion_rules(&blue, &[Item::new("accessories:belt"), Item::new("accessories:hat"), Item::new("accessories:watch")])
+            .must_build();
+
+        assert_eq!(Ok(Outfit::new(vec![blue.clone()])), closet.complete_outfit(vec![blue]));
+        assert_eq!(CardinalitySpec::AtMostOne, closet.family_cardinality(&accessories));
+        assert_eq!(CardinalitySpec::ExactlyOne, closet.family_cardinality(&Family::new("shirts")));
+    }
+
+    #[test]
+    fn item_added_to_exactly_two_family_matches_rebuild() {
+        let accessories = Family::new("accessories");
+        let scarf = Item::new("accessories:scarf");
+
+        let builder = closet_builder()
+            .set_family_cardinality(&accessories, CardinalitySpec::Range(1, 2));
+
+        let incremental = builder.clone().must_build()
+            .with_item_added(&accessories, &scarf)
+            .unwrap();
+        let rebuilt = builder
+            .add_item(&accessories, &scarf)
+            .must_build();
+
+        assert_eq!(rebuilt, incremental);
+        assert_eq!(rebuilt.family_cardinality(&accessories), incremental.family_cardinality(&accessories));
+    }
+
+    #[test]
+    fn exclusion_within_family_of_several_items_is_allowed() {
+        let accessories = Family::new("accessories");
+        let belt = Item::new("accessories:belt");
+        let hat = Item::new("accessories:hat");
+
+        let closet = closet_builder()
+            .set_family_cardinality(&accessories, CardinalitySpec::Range(0, 3))
+            .add_exclusion_rule(&belt, &hat)
+            .must_build();
+
+        assert_eq!(12, closet.outfit_count());
+        assert!(closet.complete_outfit(vec![belt, hat]).is_err());
+    }
+
+    #[test]
+    fn inclusion_within_exactly_two_family_is_allowed() {
+        let accessories = Family::new("accessories");
+        let belt = Item::new("accessories:belt");
+        let hat = Item::new("accessories:hat");
+        let watch = Item::new("accessories:watch");
+
+        let closet = closet_builder()
+            .set_family_cardinality(&accessories, CardinalitySpec::Exactly(2))
+            .add_inclusion_rule(&belt, &hat)
+            .must_build();
+
+        assert_eq!(4, closet.outfit_count());
+        assert!(closet.complete_outfit(vec![belt.clone(), watch]).is_err());
+        assert_eq!(
+            Ok(Outfit::new(vec![belt.clone(), hat, Item::new("shirts:blue")])),
+            closet.complete_outfit(vec![belt])
+        );
+    }
+
+    #[test]
+    fn unsatisfiable_cardinalities_are_rejected() {
+        let shirts = Family::new("shirts");
+        let accessories = Family::new("accessories");
+        let shoes = Family::new("shoes");
+
+        let closet_builder = closet_builder()
+            .set_family_cardinality(&shirts, CardinalitySpec::Range(2, 1))
+            .set_family_cardinality(&accessories, CardinalitySpec::Exactly(4))
+            .set_family_cardinality(&shoes, CardinalitySpec::AtMostOne);
+
+        assert_eq!(
+            Err(vec![
+                ClosetBuilderError::UnsatisfiableCardinality(accessories, CardinalitySpec::Exactly(4)),
+                ClosetBuilderError::UnsatisfiableCardinality(shirts, CardinalitySpec::Range(2, 1)),
+                ClosetBuilderError::EmptyFamily(shoes),
+            ]),
+            closet_builder.validate_preview()
+        );
+    }
+}
+
+#[cfg(test)]
+mod clause_tests {
+    use closet_builder::ClosetBuilderError;
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+    use core::OutfitError;
+    use super::ClosetBuilder;
+
+    #[test]
+    fn clause_requires_one_of_its_items() {
+        let blazer = Item::new("jackets:blazer");
+        let denim = Item::new("jackets:denim");
+        let slacks = Item::new("pants:slacks");
+        let jeans = Item::new("pants:jeans");
+        let oxfords = Item::new("shoes:oxfords");
+        let sneakers = Item::new("shoes:sneakers");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&Family::new("jackets"), &[blazer.clone(), denim.clone()])
+            .add_items(&Family::new("pants"), &[jeans.clone(), slacks.clone()])
+            .add_items(&Family::new("shoes"), &[oxfords.clone(), sneakers.clone()])
+            .add_clause(&[blazer.clone(), slacks.clone(), oxfords.clone()])
+            .must_build();
+
+        assert_eq!(
+            Err(OutfitError::IncompatibleSelections(vec![denim.clone(), jeans.clone(), sneakers.clone()])),
+            closet.complete_outfit(vec![denim.clone(), jeans.clone(), sneakers.clone()])
+        );
+        assert_eq!(
+            Ok(Outfit::new(vec![denim.clone(), jeans.clone(), oxfords.clone()])),
+            closet.complete_outfit(vec![denim.clone(), jeans.clone(), oxfords.clone()])
+        );
+        assert_eq!(
+            Ok(Outfit::new(vec![denim.clone(), slacks.clone(), sneakers.clone()])),
+            closet.complete_outfit(vec![denim.clone(), slacks.clone(), sneakers.clone()])
+        );
+        assert_eq!(
+            Ok(Outfit::new(vec![blazer.clone(), jeans.clone(), sneakers.clone()])),
+            closet.complete_outfit(vec![blazer, jeans, sneakers])
+        );
+        assert_eq!(7, closet.outfit_count());
+    }
+
+    #[test]
+    fn clause_with_unknown_item_is_rejected() {
+        let blazer = Item::new("jackets:blazer");
+        let tie = Item::new("ties:silk");
+
+        let result = ClosetBuilder::new()
+            .add_item(&Family::new("jackets"), &blazer)
+            .add_clause(&[blazer, tie.clone()])
+            .build();
+
+        assert_eq!(Err(ClosetBuilderError::MissingFamily(tie)), result);
+    }
+
+    #[test]
+    fn empty_clause_is_rejected() {
+        let result = ClosetBuilder::new()
+            .add_item(&Family::new("jackets"), &Item::new("jackets:blazer"))
+            .add_clause(&[])
+            .build();
+
+        assert_eq!(Err(ClosetBuilderError::EmptyClause), result);
+    }
+}
+
+#[cfg(test)]
+mod redundant_rules_tests {
+    use bdd::fixtures::shirts_and_pants;
+    use core::Item;
+
+    #[test]
+    fn duplicate_exclusion_is_reported_once() {
+        let blue = Item::new("shirts:blue");
+        let jeans = Item::new("pants:jeans");
+
+        let builder = shirts_and_pants()
+            .add_exclusion_rule(&blue, &jeans)
+            .add_exclusion_rule(&blue, &jeans)
+            .add_exclusion_rule(&blue, &jeans);
+
+        assert_eq!(vec![(blue.clone(), jeans.clone())], builder.redundant_rules());
+        assert_eq!(shirts_and_pants().add_exclusion_rule(&blue, &jeans), builder);
+    }
+
+    #[test]
+    fn reversed_exclusion_is_reported() {
+        let blue = Item::new("shirts:blue");
+        let jeans = Item::new("pants:jeans");
+
+        let builder = shirts_and_pants()
+            .add_exclusion_rule(&blue, &jeans)
+            .add_exclusion_rule(&jeans, &blue);
+
+        assert_eq!(vec![(jeans.clone(), blue.clone())], builder.redundant_rules());
+        assert_eq!(shirts_and_pants().add_exclusion_rule(&blue, &jeans), builder);
+    }
+
+    #[test]
+    fn duplicate_inclusion_is_reported() {
+        let blue = Item::new("shirts:blue");
+        let slacks = Item::new("pants:slacks");
+
+        let inclusions = vec![blue.clone()];
+
+        let builder = shirts_and_pants()
+            .add_inclusion_rule(&slacks, &blue)
+            .add_inclusion_rule_many(&slacks, &inclusions);
+
+        assert_eq!(vec![(slacks.clone(), blue.clone())], builder.redundant_rules());
+    }
+
+    #[test]
+    fn rules_within_family_are_left_to_validation() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let builder = shirts_and_pants()
+            .add_exclusion_rule(&blue, &red)
+            .add_inclusion_rule(&blue, &blue);
+
+        assert_eq!(Vec::<(Item, Item)>::new(), builder.redundant_rules());
+        assert!(builder.validate_preview().is_err());
+    }
+
+    #[test]
+    fn removed_rules_are_not_reported() {
+        let blue = Item::new("shirts:blue");
+        let jeans = Item::new("pants:jeans");
+
+        let builder = shirts_and_pants()
+            .add_exclusion_rule(&blue, &jeans)
+            .add_exclusion_rule(&jeans, &blue)
+            .remove_exclusion_rule(&blue, &jeans);
+
+        assert_eq!(Vec::<(Item, Item)>::new(), builder.redundant_rules());
+    }
+}
+
+#[cfg(test)]
+mod validate_preview_tests {
+    use closet_builder::ClosetBuilderError;
+    use core::Family;
+    use core::Item;
+    use super::ClosetBuilder;
+
+    #[test]
+    fn valid_builder_passes_preview() {
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&Family::new("shirts"), &Item::new("shirts:blue"))
+            .add_item(&Family::new("pants"), &Item::new("pants:jeans"));
+
+        assert_eq!(Ok(()), closet_builder.validate_preview());
+        assert!(closet_builder.build().is_ok());
+    }
+
+    #[test]
+    fn preview_reports_every_problem() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_items(&shirts, &[blue.clone(), red.clone()])
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &blue)
+            .add_inclusion_rule(&blue, &red);
+
+        assert_eq!(
+            Err(vec![
+                ClosetBuilderError::ConflictingFamilies(blue.clone(), vec![shirts.clone(), pants.clone()]),
+                ClosetBuilderError::InclusionError(shirts.clone(), vec![blue.clone(), red.clone()]),
+            ]),
+            closet_builder.validate_preview()
+        );
+        assert_eq!(
+            Err(ClosetBuilderError::CompoundError(closet_builder.validate_preview().unwrap_err())),
+            closet_builder.build()
+        );
+    }
+
+    #[test]
+    fn preview_reports_item_listed_in_two_families() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let blue = Item::new("blue");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &Item::new("pants:jeans"))
+            .add_item(&pants, &blue);
+
+        assert_eq!(
+            Err(vec![ClosetBuilderError::ConflictingFamilies(blue.clone(), vec![shirts, pants])]),
+            closet_builder.validate_preview()
+        );
+        assert_eq!(Err(closet_builder.validate_preview().unwrap_err().remove(0)), closet_builder.build());
+    }
+}
+
+#[cfg(test)]
+mod deep_validate_tests {
+    use closet_builder::ClosetBuilderError;
+    use core::CardinalitySpec;
+    use core::Family;
+    use core::Item;
+    use super::ClosetBuilder;
+
+    #[test]
+    fn satisfiable_closet_passes() {
+        let closet_builder = ClosetBuilder::new()
+            .add_items(&Family::new("shirts"), &[Item::new("shirts:blue"), Item::new("shirts:red")])
+            .add_items(&Family::new("pants"), &[Item::new("pants:jeans"), Item::new("pants:slacks")])
+            .add_exclusion_rule(&Item::new("shirts:red"), &Item::new("pants:jeans"));
+
+        assert_eq!(Ok(()), closet_builder.deep_validate());
+    }
+
+    #[test]
+    fn exclusions_against_a_mandatory_item_leave_families_unsatisfiable() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+        let hats = Family::new("hats");
+
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_items(&pants, &[jeans.clone(), slacks.clone()])
+            .add_item(&hats, &Item::new("hats:fedora"))
+            .set_family_cardinality(&hats, CardinalitySpec::AtMostOne)
+            .add_exclusion_rules(&red, &[jeans, slacks]);
+
+        assert_eq!(Ok(()), closet_builder.validate_preview());
+        assert_eq!(
+            Err(ClosetBuilderError::CompoundError(vec![
+                ClosetBuilderError::FamilyUnsatisfiable(pants),
+                ClosetBuilderError::FamilyUnsatisfiable(shirts),
+            ])),
+            closet_builder.deep_validate()
+        );
+    }
+
+    #[test]
+    fn independent_family_is_not_reported() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+        let shoes = Family::new("shoes");
+
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_items(&pants, &[jeans.clone(), slacks.clone()])
+            .add_items(&shoes, &[Item::new("shoes:boots"), Item::new("shoes:sneakers")])
+            .add_exclusion_rules(&red, &[jeans, slacks]);
+
+        assert_eq!(
+            Err(ClosetBuilderError::CompoundError(vec![
+                ClosetBuilderError::FamilyUnsatisfiable(pants),
+                ClosetBuilderError::FamilyUnsatisfiable(shirts),
+            ])),
+            closet_builder.deep_validate()
+        );
+    }
+
+    #[test]
+    fn family_whose_items_are_excluded_by_every_choice_elsewhere_is_unsatisfiable() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_items(&shirts, &[blue.clone(), red.clone()])
+            .add_items(&pants, &[jeans.clone(), slacks.clone()])
+            .add_items(&Family::new("shoes"), &[Item::new("shoes:boots"), Item::new("shoes:sneakers")])
+            .add_exclusion_rules(&jeans, &[blue.clone(), red.clone()])
+            .add_exclusion_rules(&slacks, &[blue, red]);
+
+        assert_eq!(
+            Err(ClosetBuilderError::CompoundError(vec![
+                ClosetBuilderError::FamilyUnsatisfiable(pants),
+                ClosetBuilderError::FamilyUnsatisfiable(shirts),
+            ])),
+            closet_builder.deep_validate()
+        );
+    }
+
+    #[test]
+    fn conflicting_mandatory_items_report_only_their_families() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+        let hats = Family::new("hats");
+
+        let red = Item::new("shirts:red");
+        let fedora = Item::new("hats:fedora");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_items(&pants, &[Item::new("pants:jeans"), Item::new("pants:slacks")])
+            .add_item(&hats, &fedora)
+            .add_exclusion_rule(&red, &fedora);
+
+        assert_eq!(
+            Err(ClosetBuilderError::CompoundError(vec![
+                ClosetBuilderError::FamilyUnsatisfiable(hats),
+                ClosetBuilderError::FamilyUnsatisfiable(shirts),
+            ])),
+            closet_builder.deep_validate()
+        );
+    }
+
+    #[test]
+    fn build_errors_are_returned_first() {
+        let closet_builder = ClosetBuilder::new()
+            .family(&Family::new("shoes")).with(&[]);
+
+        assert_eq!(Err(ClosetBuilderError::EmptyFamily(Family::new("shoes"))), closet_builder.deep_validate());
+    }
+}
+
+#[cfg(test)]
+mod soft_exclusion_tests {
+    use closet_builder::ClosetBuilderError;
+    use core::Family;
+    use core::Item;
+    use super::ClosetBuilder;
+
+    #[test]
+    fn soft_exclusion_keeps_every_outfit() {
+        let blue = Item::new("shirts:blue");
+        let jeans = Item::new("pants:jeans");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&Family::new("shirts"), &blue)
+            .add_item(&Family::new("pants"), &jeans);
+        let softened = closet_builder.clone()
+            .add_soft_exclusion(&blue, &jeans, 1);
+
+        assert_ne!(closet_builder, softened);
+        assert_eq!(closet_builder.must_build(), softened.must_build());
+    }
+
+    #[test]
+    fn soft_exclusion_with_unknown_item_is_rejected() {
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&Family::new("shirts"), &Item::new("shirts:blue"))
+            .add_soft_exclusion(&Item::new("shirts:blue"), &Item::new("pants:jeans"), 1);
+
+        assert_eq!(Err(ClosetBuilderError::MissingFamily(Item::new("pants:jeans"))), closet_builder.build());
+    }
+}
+
+#[cfg(test)]
+mod insertion_order_tests {
+    use core::CardinalitySpec;
+    use core::Family;
+    use core::Item;
+    use super::ClosetBuilder;
+
+    #[test]
+    fn items_added_in_any_order_build_the_same_closet() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+        let socks = Family::new("socks");
+
+        let blue = Item::new("shirts:blue");
+        let green = Item::new("shirts:green");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+        let black = Item::new("socks:black");
+        let white = Item::new("socks:white");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&shirts, &[blue.clone(), green.clone(), red.clone()])
+            .add_items(&pants, &[jeans.clone(), slacks.clone()])
+            .add_items(&socks, &[black.clone(), white.clone()])
+            .set_family_cardinality(&socks, CardinalitySpec::AtMostOne)
+            .add_exclusion_rule(&red, &jeans)
+            .must_build();
+
+        let reordered = ClosetBuilder::new()
+            .add_items(&socks, &[white, black])
+            .set_family_cardinality(&socks, CardinalitySpec::AtMostOne)
+            .add_items(&pants, &[slacks, jeans.clone()])
+            .add_items(&shirts, &[red.clone(), green, blue])
+            .add_exclusion_rule(&red, &jeans)
+            .must_build();
+
+        assert_eq!(closet.root(), reordered.root());
+        assert_eq!(closet.complete_outfit(vec![]), reordered.complete_outfit(vec![]));
+        assert_eq!(closet.complete_outfit(vec![red.clone()]), reordered.complete_outfit(vec![red]));
+        assert_eq!(
+            closet.completions(vec![]).unwrap().collect::<Vec<_>>(),
+            reordered.completions(vec![]).unwrap().collect::<Vec<_>>()
+        );
+    }
+}
+
+#[cfg(test)]
+mod try_add_item_tests {
+    use closet_builder::ClosetBuilderError;
+    use core::Family;
+    use core::Item;
+    use super::ClosetBuilder;
+
+    #[test]
+    fn item_in_another_family_fails_immediately() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+        let blue = Item::new("blue");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&shirts, &blue);
+
+        assert_eq!(
+            Err(ClosetBuilderError::ConflictingFamilies(blue.clone(), vec![shirts.clone(), pants.clone()])),
+            closet_builder.try_add_item(&pants, &blue)
+        );
+    }
+
+    #[test]
+    fn new_or_repeated_item_is_added() {
+        let shirts = Family::new("shirts");
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let closet_builder = ClosetBuilder::new()
+            .try_add_item(&shirts, &blue)
+            .and_then(|closet_builder| closet_builder.try_add_item(&shirts, &red))
+            .and_then(|closet_builder| closet_builder.try_add_item(&shirts, &blue))
+            .unwrap();
+
+        assert_eq!(ClosetBuilder::new().add_items(&shirts, &[blue, red]), closet_builder);
+    }
+
+    #[test]
+    fn conflict_is_found_after_normalizing() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet_builder = ClosetBuilder::new_with_normalizer(|id| id.to_lowercase())
+            .add_item(&shirts, &Item::new("blue"));
+
+        assert_eq!(
+            Err(ClosetBuilderError::ConflictingFamilies(Item::new("blue"), vec![shirts, pants.clone()])),
+            closet_builder.try_add_item(&pants, &Item::new("Blue"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod family_size_tests {
+    use core::Family;
+    use core::Item;
+    use super::ClosetBuilder;
+
+    #[test]
+    fn family_size_counts_items_added_to_family() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_items(&shirts, &[Item::new("shirts:blue"), Item::new("shirts:red"), Item::new("shirts:blue")])
+            .add_item(&pants, &Item::new("pants:jeans"))
+            .family(&Family::new("shoes")).with(&[]);
+
+        assert_eq!(2, closet_builder.family_size(&shirts));
+        assert_eq!(1, closet_builder.family_size(&pants));
+        assert_eq!(0, closet_builder.family_size(&Family::new("shoes")));
+        assert_eq!(0, closet_builder.family_size(&Family::new("hats")));
+    }
+}
+
+#[cfg(test)]
+mod extend_tests {
+    use core::Family;
+    use core::Item;
+    use super::ClosetBuilder;
+
+    #[test]
+    fn extend_matches_adding_items_one_at_a_time() {
+        let rows = vec![
+            ("shirts", "shirts:red"),
+            ("pants", "pants:jeans"),
+            ("shirts", "shirts:blue"),
+            ("shirts", "shirts:red"),
+        ];
+
+        let closet_builder = ClosetBuilder::new()
+            .extend(rows.into_iter().map(|(family, item)| (Family::new(family), Item::new(item))));
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+        let expected = ClosetBuilder::new()
+            .add_item(&shirts, &Item::new("shirts:red"))
+            .add_item(&pants, &Item::new("pants:jeans"))
+            .add_item(&shirts, &Item::new("shirts:blue"));
+
+        assert_eq!(expected, closet_builder);
+        assert_eq!(vec![Item::new("shirts:red"), Item::new("shirts:blue")], closet_builder.contents[&shirts]);
+        assert_eq!(expected.must_build(), closet_builder.must_build());
+    }
+}
+
+#[cfg(test)]
+mod shared_base_tests {
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+    use super::ClosetBuilder;
+
+    #[test]
+    fn forks_share_collections_they_do_not_change() {
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+
+        let base = ClosetBuilder::new()
+            .add_item(&Family::new("shirts"), &red)
+            .add_item(&Family::new("pants"), &jeans);
+
+        let fork = base.clone()
+            .add_exclusion_rule(&red, &jeans);
+
+        assert!(base.contents.ptr_eq(&fork.contents));
+        assert!(base.item_index.ptr_eq(&fork.item_index));
+        assert!(!base.exclusions.ptr_eq(&fork.exclusions));
+        assert!(base.exclusions.is_empty());
+    }
+
+    #[test]
+    fn base_forked_many_ways_builds_each_fork() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let shirt_items = (0..10)
+            .map(|index| Item::new(format!("shirts:{}", index)))
+            .collect::<Vec<_>>();
+        let pants_items = (0..10)
+            .map(|index| Item::new(format!("pants:{}", index)))
+            .collect::<Vec<_>>();
+
+        let base = ClosetBuilder::new()
+            .add_items(&shirts, &shirt_items)
+            .add_items(&pants, &pants_items);
+
+        for index in 0..1000 {
+            let shirt = &shirt_items[index % 10];
+            let included = &pants_items[index / 100];
+            let excluded = &pants_items[(index / 100 + 1) % 10];
+
+            let closet = base.clone()
+                .add_inclusion_rule(shirt, included)
+                .add_exclusion_rule(shirt, excluded)
+                .must_build();
+
+            let expected = ClosetBuilder::new()
+                .add_items(&shirts, &shirt_items)
+                .add_items(&pants, &pants_items)
+                .add_inclusion_rule(shirt, included)
+                .add_exclusion_rule(shirt, excluded)
+                .must_build();
+
+            assert_eq!(expected, closet);
+            assert_eq!(Ok(Outfit::new(vec![included.clone(), shirt.clone()])), closet.complete_outfit(vec![shirt.clone()]));
+            assert!(closet.complete_outfit(vec![shirt.clone(), excluded.clone()]).is_err());
+        }
+
+        assert!(base.exclusions.is_empty());
+        assert!(base.inclusions.is_empty());
+    }
+}
@@ -0,0 +1,10 @@
+use bdd::closet_builder::ClosetBuilder;
+use core::Family;
+use core::Item;
+
+/// Two shirts, `blue` and `red`, and two pants, `jeans` and `slacks`, with no rules between them.
+pub fn shirts_and_pants() -> ClosetBuilder {
+    ClosetBuilder::new()
+        .add_items(&Family::new("shirts"), &[Item::new("shirts:blue"), Item::new("shirts:red")])
+        .add_items(&Family::new("pants"), &[Item::new("pants:jeans"), Item::new("pants:slacks")])
+}
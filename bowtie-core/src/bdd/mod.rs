@@ -5,3 +5,6 @@ pub use self::node::*;
 mod closet;
 mod closet_builder;
 mod node;
+
+#[cfg(test)]
+mod fixtures;
@@ -1,29 +1,115 @@
 use bdd::node::Node;
-use bdd::node::bit_operations::Operation;
+use bdd::node::NodeId;
+use bdd::node::VariableOrder;
 use core::Item;
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::RwLock;
 
-pub fn apply(node1: &Node, node2: &Node, op: &Operation) -> Node {
+/// How many results `APPLY_CACHE` holds before it is emptied, so long-running processes don't grow it without bound
+const APPLY_CACHE_LIMIT: usize = 1 << 20;
+
+lazy_static! {
+    static ref APPLY_CACHE: RwLock<HashMap<(BoolOp, usize, NodeId, NodeId), NodeId>> = RwLock::new(HashMap::new());
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum BoolOp {
+    And,
+    Or,
+    Xor,
+    Imply,
+}
+
+impl BoolOp {
+    fn eval(self, node1: &Node, node2: &Node) -> Option<Node> {
+        match self {
+            BoolOp::And =>
+                match (node1, node2) {
+                    (_, Node::Leaf(true)) => Some(node1.clone()),
+                    (Node::Leaf(true), _) => Some(node2.clone()),
+                    (_, Node::Leaf(false)) | (Node::Leaf(false), _) => Some(Node::FALSE_LEAF),
+                    _ => None,
+                },
+            BoolOp::Or =>
+                match (node1, node2) {
+                    (_, Node::Leaf(false)) => Some(node1.clone()),
+                    (Node::Leaf(false), _) => Some(node2.clone()),
+                    (_, Node::Leaf(true)) | (Node::Leaf(true), _) => Some(Node::TRUE_LEAF),
+                    _ => None,
+                },
+            BoolOp::Xor =>
+                match (node1, node2) {
+                    (Node::Leaf(val_1), Node::Leaf(val_2)) => Some(Node::Leaf(val_1 ^ val_2)),
+                    (_, Node::Leaf(false)) => Some(node1.clone()),
+                    (Node::Leaf(false), _) => Some(node2.clone()),
+                    _ => None,
+                },
+            BoolOp::Imply =>
+                match (node1, node2) {
+                    (Node::Leaf(false), _) | (_, Node::Leaf(true)) => Some(Node::TRUE_LEAF),
+                    (Node::Leaf(true), _) => Some(node2.clone()),
+                    _ => None,
+                },
+        }
+    }
+}
+
+impl Node {
+    /// Combines two diagrams with `op`, e.g. `BoolOp::Imply` for `left → right`.
+    pub fn apply_op(left: &Node, right: &Node, op: BoolOp) -> Node {
+        apply(left, right, op)
+    }
+
+    /// Like `apply_op`, for diagrams that test their items in `order` rather than the natural order,
+    /// which `apply_op` and the `&`, `|` and `^` operators assume.
+    pub fn apply_in(left: &Node, right: &Node, op: BoolOp, order: &VariableOrder) -> Node {
+        apply_in(left, right, op, order)
+    }
+}
+
+pub fn apply(node1: &Node, node2: &Node, op: BoolOp) -> Node {
+    apply_in(node1, node2, op, &VariableOrder::default())
+}
+
+fn apply_in(node1: &Node, node2: &Node, op: BoolOp, order: &VariableOrder) -> Node {
     if let Some(result) = op.eval(node1, node2) {
         return result;
     }
 
-    let first_id = get_first_id(node1, node2).expect("BLAH");
+    let key = (op, order.id(), NodeId::from(node1), NodeId::from(node2));
+    if let Some(result) = APPLY_CACHE.read().unwrap().get(&key) {
+        return Node::from(result);
+    }
+
+    let first_id = get_first_id(node1, node2, order).expect("expected at least one branch");
 
     let (node1_low, node1_high) = split_branch(node1, &first_id);
     let (node2_low, node2_high) = split_branch(node2, &first_id);
 
-    let low = apply(&node1_low, &node2_low, op);
-    let high = apply(&node1_high, &node2_high, op);
+    let low = apply_in(&node1_low, &node2_low, op, order);
+    let high = apply_in(&node1_high, &node2_high, op, order);
 
-    if low == high {
-        return low.clone();
-    }
+    let result = if low == high {
+        low
+    } else {
+        Node::branch(&first_id, low, high)
+    };
+
+    insert_bounded(&mut APPLY_CACHE.write().unwrap(), key, NodeId::from(&result), APPLY_CACHE_LIMIT);
+    result
+}
 
-    Node::branch(&first_id, low, high)
+/// Empties `cache` once it holds `limit` results rather than growing past it
+fn insert_bounded<K: Eq + Hash>(cache: &mut HashMap<K, NodeId>, key: K, result: NodeId, limit: usize) {
+    if cache.len() >= limit {
+        cache.clear();
+    }
+    cache.insert(key, result);
 }
 
-fn get_first_id(node1: &Node, node2: &Node) -> Option<Item> {
+fn get_first_id(node1: &Node, node2: &Node, order: &VariableOrder) -> Option<Item> {
     match node1 {
         Node::Leaf(_) =>
             match node2 {
@@ -34,7 +120,7 @@ fn get_first_id(node1: &Node, node2: &Node) -> Option<Item> {
             match node2 {
                 Node::Leaf(_) => Some(id_1.clone()),
                 Node::Branch(id_2, _low, _high) =>
-                    match id_1.cmp(&id_2) {
+                    match order.compare(id_1, id_2) {
                         Ordering::Less => Some(id_1.clone()),
                         Ordering::Equal => Some(id_1.clone()),
                         Ordering::Greater => Some(id_2.clone()),
@@ -43,7 +129,7 @@ fn get_first_id(node1: &Node, node2: &Node) -> Option<Item> {
     }
 }
 
-fn split_branch(node: &Node, first_id: &Item) -> (Node, Node) {
+pub(super) fn split_branch(node: &Node, first_id: &Item) -> (Node, Node) {
     if let Node::Branch(id, low, high) = node {
         if first_id == id {
             let low = Node::from(low);
@@ -57,10 +143,15 @@ fn split_branch(node: &Node, first_id: &Item) -> (Node, Node) {
 
 #[cfg(test)]
 mod tests {
+    use bdd::closet_builder::ClosetBuilder;
     use bdd::node::Node;
-    use bdd::node::bit_operations::AndOperation;
+    use bdd::node::NodeId;
+    use core::Family;
     use core::Item;
+    use std::collections::HashMap;
     use super::apply;
+    use super::insert_bounded;
+    use super::BoolOp;
 
     #[test]
     fn apply_test() {
@@ -78,7 +169,7 @@ mod tests {
         let slacks_high_branch = Node::negative_branch(&jeans);
         let slacks_branch = Node::branch(&slacks, slacks_low_branch, slacks_high_branch);
 
-        let actual = apply(&slacks_branch, &blue_branch, &AndOperation::new());
+        let actual = apply(&slacks_branch, &blue_branch, BoolOp::And);
 
         let expected = {
             let slacks_low_branch = Node::branch(&jeans, Node::FALSE_LEAF, &blue_branch);
@@ -89,4 +180,92 @@ mod tests {
         };
         assert_eq!(expected, actual);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn bounded_cache_empties_once_full() {
+        let node = Node::positive_branch(&Item::new("shirts:blue"));
+        let mut cache = HashMap::new();
+
+        insert_bounded(&mut cache, 1, NodeId::from(&node), 2);
+        insert_bounded(&mut cache, 2, NodeId::from(&node), 2);
+        assert_eq!(2, cache.len());
+
+        insert_bounded(&mut cache, 3, NodeId::from(&node), 2);
+        assert_eq!(vec![&3], cache.keys().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn apply_matches_operators() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+
+        let shirts = Node::positive_branch(&red) ^ Node::positive_branch(&blue);
+        let pants = Node::negative_branch(&jeans);
+
+        assert_eq!(shirts.clone() & pants.clone(), apply(&shirts, &pants, BoolOp::And));
+        assert_eq!(shirts.clone() | pants.clone(), apply(&shirts, &pants, BoolOp::Or));
+        assert_eq!(shirts.clone() ^ pants.clone(), apply(&shirts, &pants, BoolOp::Xor));
+        assert_eq!(Node::FALSE_LEAF, apply(&shirts, &Node::FALSE_LEAF, BoolOp::And));
+        assert_eq!(Node::TRUE_LEAF, apply(&Node::TRUE_LEAF, &pants, BoolOp::Or));
+    }
+
+    #[test]
+    fn ten_family_closet_stays_linear() {
+        let closet_builder = (0..10)
+            .map(|family| Family::new(format!("family{}", family)))
+            .fold(ClosetBuilder::new(), |closet_builder, family| {
+                let items = (0..2)
+                    .map(|item| Item::new(format!("{}:{}", String::from(family.clone()), item)))
+                    .collect::<Vec<_>>();
+
+                closet_builder.add_items(&family, &items)
+            });
+
+        let closet = closet_builder.must_build();
+
+        assert_eq!(30, closet.root().size());
+        assert_eq!(1024, closet.outfit_count());
+    }
+
+    #[test]
+    fn apply_op_on_terminals_matches_truth_tables() {
+        type TruthTable = fn(bool, bool) -> bool;
+        let ops: [(BoolOp, TruthTable); 4] = [
+            (BoolOp::And, |a, b| a && b),
+            (BoolOp::Or, |a, b| a || b),
+            (BoolOp::Xor, |a, b| a ^ b),
+            (BoolOp::Imply, |a, b| !a || b),
+        ];
+
+        for (op, expected) in ops.iter() {
+            for &left in &[false, true] {
+                for &right in &[false, true] {
+                    assert_eq!(
+                        Node::Leaf(expected(left, right)),
+                        Node::apply_op(&Node::Leaf(left), &Node::Leaf(right), *op),
+                        "{:?}({}, {})", op, left, right
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn apply_op_imply_composes_with_other_operators() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+
+        let shirts = Node::positive_branch(&red) | Node::positive_branch(&blue);
+        let pants = Node::positive_branch(&jeans);
+
+        let expected = (Node::negative_branch(&red) & Node::negative_branch(&blue)) | pants.clone();
+        assert_eq!(expected, Node::apply_op(&shirts, &pants, BoolOp::Imply));
+
+        assert_eq!(Node::TRUE_LEAF, Node::apply_op(&Node::FALSE_LEAF, &shirts, BoolOp::Imply));
+        assert_eq!(shirts, Node::apply_op(&Node::TRUE_LEAF, &shirts, BoolOp::Imply));
+        assert_eq!(shirts.clone() & pants.clone(), Node::apply_op(&shirts, &pants, BoolOp::And));
+        assert_eq!(shirts.clone() ^ pants.clone(), Node::apply_op(&shirts, &pants, BoolOp::Xor));
+    }
+}
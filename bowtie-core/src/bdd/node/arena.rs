@@ -40,6 +40,10 @@ impl Arena {
         self.nodes.get(index.0)
     }
 
+    pub fn get_by_node(&self, node: &Node) -> Option<&NodeId> {
+        self.node_index.get(node)
+    }
+
     #[allow(dead_code)]
     pub fn count(&self) -> usize {
         self.nodes.len()
@@ -47,8 +51,12 @@ impl Arena {
 }
 
 pub fn add(node: Node) -> NodeId {
-    let mut arena = ARENA.write().unwrap();
-    arena.add(node)
+    let node_id = {
+        let arena = ARENA.read().unwrap();
+        arena.get_by_node(&node).cloned()
+    };
+
+    node_id.unwrap_or_else(|| ARENA.write().unwrap().add(node))
 }
 
 pub fn get(index: NodeId) -> Node {
@@ -69,6 +77,8 @@ pub fn count() -> usize {
 mod tests {
     use bdd::node::arena::Arena;
     use bdd::node::Node;
+    use bdd::node::NodeId;
+    use core::Item;
 
     #[test]
     fn add() {
@@ -84,6 +94,19 @@ mod tests {
         assert_eq!(&node2, arena.get(node2_id).expect("Expected node to exist"));
     }
 
+    #[test]
+    fn get_by_node_returns_existing_id() {
+        let node1 = Node::TRUE_LEAF;
+
+        let mut arena = Arena::new();
+
+        assert_eq!(None, arena.get_by_node(&node1));
+
+        let node1_id = arena.add(node1.clone());
+
+        assert_eq!(Some(&node1_id), arena.get_by_node(&node1));
+    }
+
     #[test]
     fn add_does_not_duplicate() {
         let node1 = Node::TRUE_LEAF;
@@ -95,4 +118,20 @@ mod tests {
 
         assert_eq!(node1_id, node2_id);
     }
+
+    #[test]
+    fn identical_subgraphs_share_one_node_id() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let first = Node::branch(&blue, Node::positive_branch(&red), Node::negative_branch(&red));
+        let second = Node::branch(&blue, Node::positive_branch(&red), Node::negative_branch(&red));
+
+        assert_eq!(NodeId::from(&first), NodeId::from(&second));
+
+        if let (Node::Branch(_, low1, high1), Node::Branch(_, low2, high2)) = (first, second) {
+            assert_eq!(low1, low2);
+            assert_eq!(high1, high2);
+        }
+    }
 }
@@ -0,0 +1,132 @@
+use bdd::node::Node;
+use bdd::node::count_satisfying::collect_variables;
+use core::Item;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+impl Node {
+    /// Iterates every assignment over `universe` that satisfies this diagram, high branches first.
+    /// Unlike `sat_one`, each assignment gives a value to every variable, so variables the diagram
+    /// skips are enumerated both ways. Variables tested by the diagram but missing from `universe` are added to it.
+    pub fn assignments(&self, universe: &[Item]) -> impl Iterator<Item=BTreeMap<Item, bool>> {
+        let mut variables: BTreeSet<Item> = universe.iter().cloned().collect();
+        collect_variables(self, &mut variables);
+
+        Assignments {
+            variables: variables.into_iter().collect(),
+            stack: vec![(self.clone(), 0, BTreeMap::new())],
+        }
+    }
+}
+
+struct Assignments {
+    variables: Vec<Item>,
+    stack: Vec<(Node, usize, BTreeMap<Item, bool>)>,
+}
+
+impl Iterator for Assignments {
+    type Item = BTreeMap<Item, bool>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, level, assignment)) = self.stack.pop() {
+            if node == Node::FALSE_LEAF {
+                continue;
+            }
+
+            let variable = match self.variables.get(level) {
+                Some(variable) => variable.clone(),
+                None => return Some(assignment),
+            };
+
+            let (low, high) = match node {
+                Node::Branch(ref id, low, high) if *id == variable => (Node::from(low), Node::from(high)),
+                _ => (node.clone(), node),
+            };
+
+            let mut low_assignment = assignment.clone();
+            low_assignment.insert(variable.clone(), false);
+            let mut high_assignment = assignment;
+            high_assignment.insert(variable, true);
+
+            self.stack.push((low, level + 1, low_assignment));
+            self.stack.push((high, level + 1, high_assignment));
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet_builder::ClosetBuilder;
+    use bdd::node::Node;
+    use core::Family;
+    use core::Item;
+    use std::collections::BTreeMap;
+
+    fn assignment(values: &[(&Item, bool)]) -> BTreeMap<Item, bool> {
+        values.iter()
+            .map(|&(item, value)| (item.clone(), value))
+            .collect()
+    }
+
+    #[test]
+    fn assignments_of_leaves() {
+        let blue = Item::new("shirts:blue");
+        let universe = vec![blue.clone()];
+
+        assert_eq!(vec![BTreeMap::new()], Node::TRUE_LEAF.assignments(&[]).collect::<Vec<_>>());
+        assert_eq!(0, Node::FALSE_LEAF.assignments(&universe).count());
+        assert_eq!(
+            vec![
+                assignment(&[(&blue, true)]),
+                assignment(&[(&blue, false)]),
+            ],
+            Node::TRUE_LEAF.assignments(&universe).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn skipped_variables_take_both_values() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+
+        let root = Node::positive_branch(&blue) | Node::positive_branch(&red);
+
+        assert_eq!(
+            vec![
+                assignment(&[(&jeans, true), (&blue, true), (&red, true)]),
+                assignment(&[(&jeans, true), (&blue, true), (&red, false)]),
+                assignment(&[(&jeans, true), (&blue, false), (&red, true)]),
+                assignment(&[(&jeans, false), (&blue, true), (&red, true)]),
+                assignment(&[(&jeans, false), (&blue, true), (&red, false)]),
+                assignment(&[(&jeans, false), (&blue, false), (&red, true)]),
+            ],
+            root.assignments(&[jeans]).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn assignment_count_matches_count_satisfying() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&Family::new("shirts"), &[blue.clone(), red.clone()])
+            .add_items(&Family::new("pants"), &[jeans.clone(), slacks.clone()])
+            .add_exclusion_rule(&red, &jeans)
+            .must_build();
+
+        let hat = Item::new("hats:fedora");
+        for universe in [vec![], vec![blue, jeans], vec![hat]] {
+            let root = closet.root();
+
+            assert_eq!(root.count_satisfying(&universe), Some(root.assignments(&universe).count() as u64));
+            assert!(root.assignments(&universe).all(|assignment| root.restrict_all(&assignment) == Node::TRUE_LEAF));
+        }
+    }
+}
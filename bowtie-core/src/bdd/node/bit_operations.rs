@@ -1,60 +1,18 @@
+use bdd::node::apply::apply;
+use bdd::node::apply::BoolOp;
 use bdd::node::Node;
-use core::Item;
-use std::cmp::Ordering;
+use bdd::node::NodeId;
+use std::collections::HashMap;
 use std::ops::BitAnd;
 use std::ops::BitOr;
 use std::ops::BitXor;
 use std::ops::Not;
 
-fn split_branch(node: &Node, first_id: &Item) -> (Node, Node) {
-    if let Node::Branch(id, low, high) = node {
-        if first_id == id {
-            let low = Node::from(low);
-            let high = Node::from(high);
-            return (low, high);
-        }
-    };
-
-    (node.clone(), node.clone())
-}
-
-
 impl BitOr for Node {
     type Output = Self;
 
     fn bitor(self, rhs: Self) -> Self {
-        let node1 = &self;
-        let node2 = &rhs;
-
-        let first_id = match (node1, node2) {
-            (_, Node::Leaf(false)) => return node1.clone(),
-            (Node::Leaf(false), _) => return node2.clone(),
-            (Node::Leaf(val_1), Node::Leaf(val_2)) => return Node::Leaf(val_1 | val_2),
-
-            (Node::Branch(id, _, _), Node::Leaf(_)) => {
-                (id)
-            }
-            (Node::Leaf(_), Node::Branch(id, _, _)) => {
-                (id)
-            }
-            (Node::Branch(id_1, _, _), Node::Branch(id_2, _, _)) =>
-                match id_1.cmp(&id_2) {
-                    Ordering::Less | Ordering::Equal => (id_1),
-                    Ordering::Greater => (id_2),
-                },
-        };
-
-        let (node1_low, node1_high) = split_branch(node1, first_id);
-        let (node2_low, node2_high) = split_branch(node2, first_id);
-
-        let low = node1_low | node2_low;
-        let high = node1_high | node2_high;
-
-        if low == high {
-            return low.clone();
-        }
-
-        Node::branch(&first_id, low, high)
+        apply(&self, &rhs, BoolOp::Or)
     }
 }
 
@@ -62,34 +20,7 @@ impl BitAnd for Node {
     type Output = Self;
 
     fn bitand(self, rhs: Self) -> Self {
-        let node1 = &self;
-        let node2 = &rhs;
-
-        let first_id = match (node1, node2) {
-            (_, Node::Leaf(true)) => return node1.clone(),
-            (Node::Leaf(true), _) => return node2.clone(),
-            (Node::Leaf(val_1), Node::Leaf(val_2)) => return Node::Leaf(val_1 & val_2),
-
-            (Node::Branch(id, _, _), Node::Leaf(_)) => id,
-            (Node::Leaf(_), Node::Branch(id, _, _)) => id,
-            (Node::Branch(id_1, _, _), Node::Branch(id_2, _, _)) =>
-                match id_1.cmp(&id_2) {
-                    Ordering::Less | Ordering::Equal => id_1,
-                    Ordering::Greater => id_2,
-                },
-        };
-
-        let (node1_low, node1_high) = split_branch(node1, &first_id);
-        let (node2_low, node2_high) = split_branch(node2, &first_id);
-
-        let low = node1_low & node2_low;
-        let high = node1_high & node2_high;
-
-        if low == high {
-            return low.clone();
-        }
-
-        Node::branch(&first_id, low, high)
+        apply(&self, &rhs, BoolOp::And)
     }
 }
 
@@ -97,7 +28,7 @@ impl BitXor for Node {
     type Output = Self;
 
     fn bitxor(self, rhs: Self) -> Self {
-        ((self.clone() & !rhs.clone()) | (!self.clone() & rhs.clone()))
+        apply(&self, &rhs, BoolOp::Xor)
     }
 }
 
@@ -105,10 +36,32 @@ impl Not for Node {
     type Output = Self;
 
     fn not(self) -> Self {
-        match &self {
-            Node::Leaf(true) => Node::FALSE_LEAF,
-            Node::Leaf(false) => Node::TRUE_LEAF,
-            Node::Branch(id, low, high) => Node::branch(id, *high, *low)
+        Node::not(&self)
+    }
+}
+
+impl Node {
+    /// The complement of the node: swaps the leaves throughout the diagram, visiting each node once.
+    /// Every branch keeps its variable, so the result tests variables in the same order.
+    pub fn not(&self) -> Node {
+        not(self, &mut HashMap::new())
+    }
+}
+
+fn not(node: &Node, visited: &mut HashMap<NodeId, Node>) -> Node {
+    match node {
+        Node::Leaf(val) => Node::Leaf(!val),
+        Node::Branch(id, low, high) => {
+            if let Some(negated) = visited.get(&NodeId::from(node)) {
+                return negated.clone();
+            }
+
+            let low = not(&Node::from(low), visited);
+            let high = not(&Node::from(high), visited);
+
+            let negated = Node::branch(id, low, high);
+            visited.insert(NodeId::from(node), negated.clone());
+            negated
         }
     }
 }
@@ -218,8 +171,10 @@ mod bitnand_tests {
         let slacks_branch = Node::branch(&slacks, slacks_low_branch, slacks_high_branch);
 
         let expected = {
-            let slacks_high_branch = Node::branch(&jeans, Node::FALSE_LEAF, &blue_branch);
-            let slacks_low_branch = Node::branch(&jeans, &blue_branch, Node::FALSE_LEAF);
+            let not_blue_branch = Node::branch(&blue, Node::negative_branch(&red), Node::positive_branch(&red));
+
+            let slacks_high_branch = Node::branch(&jeans, &not_blue_branch, Node::TRUE_LEAF);
+            let slacks_low_branch = Node::branch(&jeans, Node::TRUE_LEAF, &not_blue_branch);
             let slacks_branch = Node::branch(&slacks, slacks_low_branch, slacks_high_branch);
 
             slacks_branch
@@ -423,4 +378,87 @@ mod bitxor_tests {
         let actual = Node::positive_branch(&blue) ^ Node::positive_branch(&red) ^ Node::positive_branch(&slacks) ^ Node::positive_branch(&jeans);
         assert_eq!(expected, actual);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn xor_of_multi_level_diagrams_matches_truth_table() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+
+        let left = Node::positive_branch(&blue) & Node::positive_branch(&red);
+        let right = Node::positive_branch(&red) | Node::positive_branch(&jeans);
+
+        let actual = left.clone() ^ right.clone();
+
+        for assignment in 0..8 {
+            let values = [assignment & 1 == 1, assignment & 2 == 2, assignment & 4 == 4];
+            let evaluate = |node: &Node| {
+                let node = Node::restrict(node, &blue, values[0]);
+                let node = Node::restrict(&node, &red, values[1]);
+                Node::restrict(&node, &jeans, values[2])
+            };
+
+            let expected = match (evaluate(&left), evaluate(&right)) {
+                (Node::Leaf(val_1), Node::Leaf(val_2)) => Node::Leaf(val_1 ^ val_2),
+                _ => panic!("expected every variable to be assigned"),
+            };
+            assert_eq!(expected, evaluate(&actual));
+        }
+    }
+}
+
+#[cfg(test)]
+mod not_tests {
+    use bdd::node::apply::BoolOp;
+    use bdd::node::Node;
+    use core::Item;
+
+    #[test]
+    fn not_leaf_nodes() {
+        assert_eq!(Node::FALSE_LEAF, !Node::TRUE_LEAF);
+        assert_eq!(Node::TRUE_LEAF, !Node::FALSE_LEAF);
+    }
+
+    #[test]
+    fn not_branch_swaps_its_leaves() {
+        let blue = Item::new("shirts:blue");
+
+        assert_eq!(Node::negative_branch(&blue), !Node::positive_branch(&blue));
+    }
+
+    #[test]
+    fn double_negation_is_identity() {
+        let blue = Node::positive_branch(&Item::new("shirts:blue"));
+        let jeans = Node::positive_branch(&Item::new("pants:jeans"));
+        let boots = Node::positive_branch(&Item::new("shoes:boots"));
+
+        let node = (blue.clone() & jeans.clone()) | (jeans ^ boots.clone()) | (blue & boots);
+
+        assert_eq!(node, !!node.clone());
+        assert_eq!(node, node.not().not());
+    }
+
+    #[test]
+    fn not_of_multi_level_diagram_is_complement() {
+        let blue = Node::positive_branch(&Item::new("shirts:blue"));
+        let jeans = Node::positive_branch(&Item::new("pants:jeans"));
+        let boots = Node::positive_branch(&Item::new("shoes:boots"));
+
+        let node = (blue & jeans) | boots;
+
+        assert_eq!(Node::apply_op(&node, &Node::TRUE_LEAF, BoolOp::Xor), !node.clone());
+        assert!((node.clone() | !node.clone()).is_tautology());
+        assert!((node.clone() & !node).is_contradiction());
+    }
+
+    #[test]
+    fn not_keeps_size() {
+        let blue = Node::positive_branch(&Item::new("shirts:blue"));
+        let jeans = Node::positive_branch(&Item::new("pants:jeans"));
+        let boots = Node::positive_branch(&Item::new("shoes:boots"));
+
+        let node = (blue ^ jeans) | boots;
+
+        assert_eq!(node.size(), (!node).size());
+    }
+}
@@ -0,0 +1,195 @@
+use bdd::node::Node;
+use bdd::node::NodeId;
+use bdd::node::VariableOrder;
+use core::Item;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+
+impl Node {
+    /// Counts the assignments over `variables` that satisfy this diagram.
+    /// Variables that the diagram never tests are free, so each doubles the count.
+    /// Variables tested by the diagram but missing from `variables` are added to the universe.
+    /// Returns `None` when the count doesn't fit in a `u64`, which takes at least 64 variables.
+    pub fn count_satisfying(&self, variables: &[Item]) -> Option<u64> {
+        self.count_satisfying_in(variables, &VariableOrder::default())
+    }
+
+    /// Like `count_satisfying`, for a diagram that tests its items in `order` rather than the natural order.
+    pub fn count_satisfying_in(&self, variables: &[Item], order: &VariableOrder) -> Option<u64> {
+        let mut universe: BTreeSet<Item> = variables.iter().cloned().collect();
+        collect_variables(self, &mut universe);
+
+        let mut universe = universe.into_iter().collect::<Vec<_>>();
+        universe.sort_by(|item, other| order.compare(item, other));
+
+        let levels = universe.into_iter()
+            .enumerate()
+            .map(|(level, item)| (item, level))
+            .collect::<HashMap<_, _>>();
+
+        let mut cache = HashMap::new();
+        let root_level = level(self, &levels);
+
+        count(self, &levels, &mut cache)
+            .and_then(|count| skip_levels(count, root_level))
+    }
+}
+
+pub(super) fn collect_variables(node: &Node, universe: &mut BTreeSet<Item>) {
+    let mut visited = BTreeSet::new();
+
+    let mut queue = vec![node.clone()];
+    while let Some(node) = queue.pop() {
+        if !visited.insert(NodeId::from(&node)) {
+            continue;
+        }
+
+        if let Node::Branch(id, low, high) = node {
+            universe.insert(id);
+            queue.push(Node::from(low));
+            queue.push(Node::from(high));
+        }
+    }
+}
+
+fn level(node: &Node, levels: &HashMap<Item, usize>) -> usize {
+    match node {
+        Node::Leaf(_) => levels.len(),
+        Node::Branch(id, _, _) => levels[id],
+    }
+}
+
+fn count(node: &Node, levels: &HashMap<Item, usize>, cache: &mut HashMap<NodeId, Option<u64>>) -> Option<u64> {
+    match node {
+        Node::Leaf(true) => Some(1),
+        Node::Leaf(false) => Some(0),
+        Node::Branch(id, low, high) => {
+            let node_id = NodeId::from(node);
+            if let Some(count) = cache.get(&node_id) {
+                return *count;
+            }
+
+            let node_level = levels[id];
+            let low = Node::from(low);
+            let high = Node::from(high);
+
+            let low_count = count(&low, levels, cache)
+                .and_then(|count| skip_levels(count, level(&low, levels) - node_level - 1));
+            let high_count = count(&high, levels, cache)
+                .and_then(|count| skip_levels(count, level(&high, levels) - node_level - 1));
+
+            let total = match (low_count, high_count) {
+                (Some(low_count), Some(high_count)) => low_count.checked_add(high_count),
+                _ => None,
+            };
+            cache.insert(node_id, total);
+            total
+        }
+    }
+}
+
+/// Doubles `count` once for every skipped level, since each skipped variable is free.
+fn skip_levels(count: u64, skipped: usize) -> Option<u64> {
+    if count == 0 {
+        return Some(0);
+    }
+
+    1u64.checked_shl(skipped as u32)
+        .and_then(|free| count.checked_mul(free))
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet::Closet;
+    use bdd::closet_builder::ClosetBuilder;
+    use bdd::node::Node;
+    use core::Family;
+    use core::Item;
+
+    #[test]
+    fn leaves_over_two_variables() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        assert_eq!(Some(4), Node::TRUE_LEAF.count_satisfying(&[blue.clone(), red.clone()]));
+        assert_eq!(Some(0), Node::FALSE_LEAF.count_satisfying(&[blue, red]));
+        assert_eq!(Some(1), Node::TRUE_LEAF.count_satisfying(&[]));
+    }
+
+    #[test]
+    fn single_variable_over_two_variables() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        assert_eq!(Some(2), Node::positive_branch(&blue).count_satisfying(&[blue.clone(), red.clone()]));
+        assert_eq!(Some(2), Node::negative_branch(&red).count_satisfying(&[blue.clone(), red.clone()]));
+        assert_eq!(Some(1), Node::positive_branch(&blue).count_satisfying(&[blue]));
+    }
+
+    #[test]
+    fn variables_missing_from_universe_are_added() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let root = Node::positive_branch(&blue) | Node::positive_branch(&red);
+
+        assert_eq!(Some(3), root.count_satisfying(&[]));
+        assert_eq!(Some(6), root.count_satisfying(&[Item::new("pants:jeans")]));
+    }
+
+    #[test]
+    fn closet_root_over_its_items_matches_outfit_count() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .add_exclusion_rule(&red, &jeans)
+            .must_build();
+
+        let variables = [blue, red, jeans, slacks];
+        assert_eq!(Some(closet.outfit_count()), closet.root().count_satisfying(&variables));
+        assert_eq!(Some(3), closet.root().count_satisfying(&variables));
+    }
+
+    #[test]
+    fn counts_past_u64_are_none() {
+        let variables = (0..64)
+            .map(|index| Item::new(format!("item{:02}", index)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(Some(1 << 63), Node::TRUE_LEAF.count_satisfying(&variables[1..]));
+        assert_eq!(None, Node::TRUE_LEAF.count_satisfying(&variables));
+        assert_eq!(Some(1 << 63), Node::positive_branch(&variables[0]).count_satisfying(&variables));
+        assert_eq!(Some(1 << 63), Node::positive_branch(&variables[63]).count_satisfying(&variables));
+        assert_eq!(Some(0), Node::FALSE_LEAF.count_satisfying(&variables));
+
+        let wider = (0..65)
+            .map(|index| Item::new(format!("item{:02}", index)))
+            .collect::<Vec<_>>();
+        assert_eq!(None, Node::positive_branch(&wider[64]).count_satisfying(&wider));
+    }
+
+    #[test]
+    fn many_single_item_families_count_one_outfit() {
+        let items = (0..70)
+            .map(|index| (Family::new(format!("family{:02}", index)), Item::new(format!("item{:02}", index))))
+            .collect::<Vec<_>>();
+        let variables = items.iter()
+            .map(|(_, item)| item.clone())
+            .collect::<Vec<_>>();
+
+        let closet = Closet::universal(&items);
+
+        assert_eq!(Some(1), closet.root().count_satisfying(&variables));
+    }
+}
@@ -0,0 +1,70 @@
+use bdd::node::Node;
+use bdd::node::NodeId;
+use std::collections::BTreeSet;
+
+impl Node {
+    pub fn to_dot(&self) -> String {
+        let mut nodes = vec![];
+        let mut edges = vec![];
+        let mut visited = BTreeSet::new();
+
+        let mut queue = vec![self.clone()];
+        while let Some(node) = queue.pop() {
+            let node_id = NodeId::from(&node);
+            if !visited.insert(node_id) {
+                continue;
+            }
+
+            match node {
+                Node::Leaf(val) => {
+                    nodes.push(format!("    n{} [label=\"{}\", shape=box];", node_id.0, if val { 1 } else { 0 }));
+                }
+                Node::Branch(id, low, high) => {
+                    let label = String::from(id).replace('"', "\\\"");
+                    nodes.push(format!("    n{} [label=\"{}\"];", node_id.0, label));
+
+                    edges.push(format!("    n{} -> n{} [style=dashed];", node_id.0, low.0));
+                    edges.push(format!("    n{} -> n{};", node_id.0, high.0));
+
+                    queue.push(Node::from(high));
+                    queue.push(Node::from(low));
+                }
+            }
+        }
+
+        format!("digraph {{\n{}\n{}\n}}\n", nodes.join("\n"), edges.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::node::Node;
+    use core::Item;
+
+    #[test]
+    fn dot_of_leaf_has_single_terminal() {
+        let dot = Node::TRUE_LEAF.to_dot();
+
+        assert_eq!(1, dot.matches("shape=box").count());
+        assert_eq!(0, dot.matches("->").count());
+        assert!(dot.contains("[label=\"1\", shape=box]"));
+    }
+
+    #[test]
+    fn dot_of_two_items_shares_false_leaf() {
+        let blue = Item::new("shirts:blue");
+        let jeans = Item::new("pants:jeans");
+
+        let root = Node::branch(&jeans, Node::FALSE_LEAF, Node::positive_branch(&blue));
+
+        let dot = root.to_dot();
+
+        assert!(dot.starts_with("digraph {"));
+        assert_eq!(4, dot.matches("[label=").count());
+        assert_eq!(2, dot.matches("shape=box").count());
+        assert_eq!(4, dot.matches("->").count());
+        assert_eq!(2, dot.matches("style=dashed").count());
+        assert!(dot.contains("[label=\"pants:jeans\"]"));
+        assert!(dot.contains("[label=\"shirts:blue\"]"));
+    }
+}
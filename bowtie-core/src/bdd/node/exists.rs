@@ -0,0 +1,66 @@
+use bdd::node::Node;
+use core::Item;
+
+impl Node {
+    pub fn exists(&self, item: &Item) -> Node {
+        Node::restrict(self, item, true) | Node::restrict(self, item, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet_builder::ClosetBuilder;
+    use bdd::node::Node;
+    use core::Family;
+    use core::Item;
+
+    #[test]
+    fn exists_over_leaves_is_identity() {
+        let blue = Item::new("shirts:blue");
+
+        assert_eq!(Node::TRUE_LEAF, Node::TRUE_LEAF.exists(&blue));
+        assert_eq!(Node::FALSE_LEAF, Node::FALSE_LEAF.exists(&blue));
+    }
+
+    #[test]
+    fn exists_over_single_branch_is_tautology() {
+        let blue = Item::new("shirts:blue");
+
+        assert_eq!(Node::TRUE_LEAF, Node::positive_branch(&blue).exists(&blue));
+        assert_eq!(Node::TRUE_LEAF, Node::negative_branch(&blue).exists(&blue));
+    }
+
+    #[test]
+    fn exists_accepts_both_assignments_of_item() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .add_exclusion_rule(&blue, &jeans)
+            .must_build();
+
+        let projected = closet.root().exists(&blue);
+
+        assert_eq!(projected, Node::restrict(&projected, &blue, true));
+        assert_eq!(projected, Node::restrict(&projected, &blue, false));
+
+        let expected = {
+            let red_and_slacks = Node::positive_branch(&red) & Node::negative_branch(&jeans) & Node::positive_branch(&slacks);
+            let red_and_jeans = Node::positive_branch(&red) & Node::positive_branch(&jeans) & Node::negative_branch(&slacks);
+            let not_red_and_slacks = Node::negative_branch(&red) & Node::negative_branch(&jeans) & Node::positive_branch(&slacks);
+
+            red_and_slacks | red_and_jeans | not_red_and_slacks
+        };
+        assert_eq!(expected, projected);
+    }
+}
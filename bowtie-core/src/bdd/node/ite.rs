@@ -0,0 +1,167 @@
+use bdd::node::apply::split_branch;
+use bdd::node::Node;
+use bdd::node::NodeId;
+use bdd::node::VariableOrder;
+use core::Item;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+lazy_static! {
+    static ref ITE_CACHE: RwLock<HashMap<(usize, NodeId, NodeId, NodeId), NodeId>> = RwLock::new(HashMap::new());
+}
+
+/// Results of `ite` in an unregistered order, kept for a single operation rather than in `ITE_CACHE`
+pub(super) type IteTable = HashMap<(NodeId, NodeId, NodeId), Node>;
+
+impl Node {
+    /// Returns the diagram for "if `f` then `g` else `h`".
+    pub fn ite(f: &Node, g: &Node, h: &Node) -> Node {
+        Node::ite_in(f, g, h, &VariableOrder::default())
+    }
+
+    /// Like `ite`, for diagrams that test their items in `order` rather than the natural order.
+    pub fn ite_in(f: &Node, g: &Node, h: &Node, order: &VariableOrder) -> Node {
+        ite_in(f, g, h, order, &mut HashMap::new())
+    }
+}
+
+pub(super) fn ite_in(f: &Node, g: &Node, h: &Node, order: &VariableOrder, computed: &mut IteTable) -> Node {
+    match (f, g, h) {
+        (Node::Leaf(true), _, _) => return g.clone(),
+        (Node::Leaf(false), _, _) => return h.clone(),
+        (_, Node::Leaf(true), Node::Leaf(false)) => return f.clone(),
+        _ if g == h => return g.clone(),
+        _ => {}
+    }
+
+    let key = (NodeId::from(f), NodeId::from(g), NodeId::from(h));
+    let cached = if order.is_registered() {
+        ITE_CACHE.read().unwrap().get(&(order.id(), key.0, key.1, key.2)).map(Node::from)
+    } else {
+        computed.get(&key).cloned()
+    };
+    if let Some(result) = cached {
+        return result;
+    }
+
+    let top_id = [f, g, h].iter()
+        .filter_map(|node| match node {
+            Node::Branch(id, _low, _high) => Some(id),
+            Node::Leaf(_) => None,
+        })
+        .fold(None, |top: Option<&Item>, id| match top {
+            Some(top) if order.compare(top, id) != Ordering::Greater => Some(top),
+            _ => Some(id),
+        })
+        .cloned()
+        .expect("expected at least one branch");
+
+    let (f_low, f_high) = split_branch(f, &top_id);
+    let (g_low, g_high) = split_branch(g, &top_id);
+    let (h_low, h_high) = split_branch(h, &top_id);
+
+    let low = ite_in(&f_low, &g_low, &h_low, order, computed);
+    let high = ite_in(&f_high, &g_high, &h_high, order, computed);
+
+    let result = Node::branch(&top_id, low, high);
+    if order.is_registered() {
+        ITE_CACHE.write().unwrap().insert((order.id(), key.0, key.1, key.2), NodeId::from(&result));
+    } else {
+        computed.insert(key, result.clone());
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet_builder::ClosetBuilder;
+    use bdd::node::Node;
+    use core::Family;
+    use core::Item;
+
+    #[test]
+    fn ite_with_true_condition_is_then_branch() {
+        let blue = Item::new("shirts:blue");
+        let jeans = Item::new("pants:jeans");
+
+        let g = Node::positive_branch(&blue);
+        let h = Node::positive_branch(&jeans);
+
+        assert_eq!(g, Node::ite(&Node::TRUE_LEAF, &g, &h));
+    }
+
+    #[test]
+    fn ite_with_false_condition_is_else_branch() {
+        let blue = Item::new("shirts:blue");
+        let jeans = Item::new("pants:jeans");
+
+        let g = Node::positive_branch(&blue);
+        let h = Node::positive_branch(&jeans);
+
+        assert_eq!(h, Node::ite(&Node::FALSE_LEAF, &g, &h));
+    }
+
+    #[test]
+    fn ite_matches_and_or_operators() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+
+        let f = Node::positive_branch(&blue);
+        let g = Node::positive_branch(&red);
+
+        assert_eq!(f.clone() & g.clone(), Node::ite(&f, &g, &Node::FALSE_LEAF));
+        assert_eq!(f.clone() | g.clone(), Node::ite(&f, &Node::TRUE_LEAF, &g));
+
+        let h = Node::positive_branch(&jeans);
+        let expected = (f.clone() & g.clone()) | (Node::negative_branch(&blue) & h.clone());
+        assert_eq!(expected, Node::ite(&f, &g, &h));
+    }
+
+    #[test]
+    fn ite_encodes_inclusion_rule() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .must_build();
+
+        let root = closet.root();
+        let constrained = Node::ite(
+            &Node::positive_branch(&blue),
+            &(root.clone() & Node::positive_branch(&jeans)),
+            root,
+        );
+
+        let expected = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .add_inclusion_rule(&blue, &jeans)
+            .must_build();
+
+        assert_eq!(expected.root(), &constrained);
+    }
+
+    #[test]
+    fn ite_visits_shared_subgraphs_once() {
+        let root = (0..40)
+            .map(|family| Node::positive_branch(&Item::new(format!("{:02}:a", family))) ^ Node::positive_branch(&Item::new(format!("{:02}:b", family))))
+            .fold(Node::TRUE_LEAF, |root, family| root & family);
+
+        assert_eq!(root, Node::ite(&root, &root, &Node::FALSE_LEAF));
+        assert_eq!(Node::TRUE_LEAF, Node::ite(&root, &Node::TRUE_LEAF, &!root.clone()));
+    }
+}
@@ -1,14 +1,26 @@
 use core::Item;
 use std::fmt;
 
+pub use self::apply::BoolOp;
+pub use self::variable_order::VariableOrder;
+
+mod apply;
 mod arena;
+mod assignments;
 mod bit_operations;
+mod count_satisfying;
+mod dot;
+mod exists;
 mod hash_structure;
+mod ite;
 mod reduce;
 mod restrict;
+mod sat_one;
 mod serialize;
+mod size;
 mod summarize;
 mod structure;
+mod variable_order;
 
 #[derive(Eq, PartialEq, Clone, Hash, Ord, PartialOrd)]
 pub enum Node {
@@ -46,8 +58,17 @@ impl Node {
     pub const TRUE_LEAF: Node = Node::Leaf(true);
     pub const FALSE_LEAF: Node = Node::Leaf(false);
 
+    /// Nodes are shared between closets, so `id` is stored without its metadata;
+    /// closets look their own items back up when they return them.
     pub fn branch<L, H>(id: &Item, low: L, high: H) -> Node where L: Into<NodeId>, H: Into<NodeId> {
-        Node::Branch(id.clone(), low.into(), high.into())
+        let low = low.into();
+        let high = high.into();
+
+        if low == high {
+            return Node::from(low);
+        }
+
+        Node::Branch(id.without_meta(), low, high)
     }
 
     pub fn positive_branch(id: &Item) -> Node {
@@ -57,6 +78,16 @@ impl Node {
     pub fn negative_branch(id: &Item) -> Node {
         Node::branch(id, Node::TRUE_LEAF, Node::FALSE_LEAF)
     }
+
+    /// Whether the node holds for every assignment. Nodes are kept reduced, so this only has to look at the root.
+    pub fn is_tautology(&self) -> bool {
+        *self == Node::TRUE_LEAF
+    }
+
+    /// Whether the node holds for no assignment. Nodes are kept reduced, so this only has to look at the root.
+    pub fn is_contradiction(&self) -> bool {
+        *self == Node::FALSE_LEAF
+    }
 }
 
 impl<'a> From<Node> for NodeId {
@@ -88,3 +119,52 @@ impl<'a> From<&'a Node> for Node {
         node.clone()
     }
 }
+
+#[cfg(test)]
+mod tautology_tests {
+    use bdd::node::Node;
+    use core::Item;
+
+    #[test]
+    fn leaves() {
+        assert!(Node::TRUE_LEAF.is_tautology());
+        assert!(!Node::TRUE_LEAF.is_contradiction());
+        assert!(Node::FALSE_LEAF.is_contradiction());
+        assert!(!Node::FALSE_LEAF.is_tautology());
+    }
+
+    #[test]
+    fn item_or_not_item_is_tautology() {
+        let a = Node::positive_branch(&Item::new("a"));
+
+        assert!((a.clone() | !a).is_tautology());
+    }
+
+    #[test]
+    fn item_and_not_item_is_contradiction() {
+        let a = Node::positive_branch(&Item::new("a"));
+
+        assert!((a.clone() & !a).is_contradiction());
+    }
+
+    #[test]
+    fn diagram_covering_every_case_is_tautology() {
+        let a = Node::positive_branch(&Item::new("a"));
+        let b = Node::positive_branch(&Item::new("b"));
+
+        let node = (a.clone() & b.clone()) | (a.clone() & !b.clone()) | (!a.clone() & b.clone()) | (!a & !b);
+
+        assert!(node.is_tautology());
+    }
+
+    #[test]
+    fn satisfiable_node_is_neither() {
+        let a = Node::positive_branch(&Item::new("a"));
+        let b = Node::positive_branch(&Item::new("b"));
+
+        let node = a | b;
+
+        assert!(!node.is_tautology());
+        assert!(!node.is_contradiction());
+    }
+}
@@ -99,4 +99,16 @@ mod reduce_tests {
             actual
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn branch_with_equal_children_reduces_to_child() {
+        let jeans = Item::new("pants:jeans");
+        let blue_shirt = Item::new("shirts:blue");
+
+        let child = Node::positive_branch(&jeans);
+
+        assert_eq!(child, Node::branch(&blue_shirt, &child, &child));
+        assert_eq!(Node::TRUE_LEAF, Node::branch(&blue_shirt, Node::TRUE_LEAF, Node::TRUE_LEAF));
+        assert_eq!(Node::FALSE_LEAF, Node::branch(&blue_shirt, Node::FALSE_LEAF, Node::FALSE_LEAF));
+    }
+}
@@ -1,7 +1,12 @@
 use bdd::node::Node;
+use bdd::node::NodeId;
 use core::Item;
+use std::collections::BTreeMap;
 
 impl Node {
+    /// Fixes `item` to `selected`. Every remaining node keeps its variable and is only ever
+    /// given descendants of its original children, so the result tests variables in the same
+    /// order as `node` does.
     pub fn restrict(node: &Node, item: &Item, selected: bool) -> Node {
         match node {
             Node::Leaf(true) => Node::TRUE_LEAF,
@@ -25,12 +30,45 @@ impl Node {
             }
         }
     }
+
+    /// Like folding `restrict` over `assignments`, with the same ordering guarantee.
+    pub fn restrict_all(&self, assignments: &BTreeMap<Item, bool>) -> Node {
+        restrict_all(self, assignments, &mut BTreeMap::new())
+    }
+}
+
+fn restrict_all(node: &Node, assignments: &BTreeMap<Item, bool>, visited: &mut BTreeMap<NodeId, Node>) -> Node {
+    match node {
+        Node::Leaf(_) => node.clone(),
+        Node::Branch(id, low, high) => {
+            if let Some(result) = visited.get(&NodeId::from(node)) {
+                return result.clone();
+            }
+
+            let result = match assignments.get(id) {
+                Some(false) => restrict_all(&Node::from(low), assignments, visited),
+                Some(true) => restrict_all(&Node::from(high), assignments, visited),
+                None => {
+                    let restricted_low = restrict_all(&Node::from(low), assignments, visited);
+                    let restricted_high = restrict_all(&Node::from(high), assignments, visited);
+
+                    Node::branch(id, restricted_low, restricted_high)
+                }
+            };
+
+            visited.insert(NodeId::from(node), result.clone());
+            result
+        }
+    }
 }
 
 #[cfg(test)]
 mod restrict_tests {
+    use bdd::closet_builder::ClosetBuilder;
     use bdd::node::Node;
+    use core::Family;
     use core::Item;
+    use std::collections::BTreeMap;
 
     #[test]
     fn selecting_child_returns_correct_node() {
@@ -85,4 +123,87 @@ mod restrict_tests {
             actual
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn restrict_all_matches_folding_restrict() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+        let shoes = Family::new("shoes");
+
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+        let boots = Item::new("shoes:boots");
+        let sneakers = Item::new("shoes:sneakers");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &blue)
+            .add_item(&shirts, &red)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .add_item(&shoes, &boots)
+            .add_item(&shoes, &sneakers)
+            .add_exclusion_rule(&red, &boots)
+            .must_build();
+
+        let assignments: BTreeMap<Item, bool> = vec![
+            (blue.clone(), true),
+            (slacks.clone(), false),
+            (sneakers.clone(), true),
+        ].into_iter().collect();
+
+        let expected = assignments.iter()
+            .fold(closet.root().clone(), |root, (item, &selected)| Node::restrict(&root, item, selected));
+
+        assert_eq!(expected, closet.root().restrict_all(&assignments));
+        assert_eq!(closet.root().clone(), closet.root().restrict_all(&BTreeMap::new()));
+    }
+
+    fn assert_paths_are_ordered(node: &Node, previous: Option<&Item>) {
+        if let Node::Branch(id, low, high) = node {
+            if let Some(previous) = previous {
+                assert!(previous < id, "{:?} is tested after {:?}", id, previous);
+            }
+
+            assert_paths_are_ordered(&Node::from(low), Some(id));
+            assert_paths_are_ordered(&Node::from(high), Some(id));
+        }
+    }
+
+    #[test]
+    fn restrict_preserves_variable_order_on_every_path() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+        let shoes = Family::new("shoes");
+
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let grey = Item::new("shirts:grey");
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+        let boots = Item::new("shoes:boots");
+        let sneakers = Item::new("shoes:sneakers");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&shirts, &[blue.clone(), red.clone(), grey.clone()])
+            .add_items(&pants, &[jeans.clone(), slacks.clone()])
+            .add_items(&shoes, &[boots.clone(), sneakers.clone()])
+            .add_exclusion_rule(&red, &boots)
+            .add_inclusion_rule(&grey, &slacks)
+            .must_build();
+        assert_paths_are_ordered(closet.root(), None);
+
+        for item in closet.items() {
+            for &selected in &[false, true] {
+                let restricted = Node::restrict(closet.root(), item, selected);
+
+                assert_paths_are_ordered(&restricted, None);
+                assert_eq!(restricted, Node::restrict(&restricted, item, selected));
+            }
+        }
+
+        let assignments: BTreeMap<Item, bool> = vec![(red, false), (jeans, false)].into_iter().collect();
+        assert_paths_are_ordered(&closet.root().restrict_all(&assignments), None);
+    }
+}
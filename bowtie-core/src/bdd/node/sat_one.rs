@@ -0,0 +1,78 @@
+use bdd::node::Node;
+use core::Item;
+use std::collections::BTreeMap;
+
+impl Node {
+    /// Returns one satisfying assignment, preferring the high branch at each node.
+    /// Only the items on the chosen path are assigned; items that are skipped may take either value.
+    pub fn sat_one(&self) -> Option<BTreeMap<Item, bool>> {
+        let mut assignment = BTreeMap::new();
+        let mut node = self.clone();
+
+        loop {
+            match node {
+                Node::Leaf(true) => return Some(assignment),
+                Node::Leaf(false) => return None,
+                Node::Branch(id, low, high) => {
+                    let high = Node::from(high);
+
+                    if high != Node::FALSE_LEAF {
+                        assignment.insert(id, true);
+                        node = high;
+                    } else {
+                        assignment.insert(id, false);
+                        node = Node::from(low);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet_builder::ClosetBuilder;
+    use bdd::node::Node;
+    use core::Family;
+    use core::Item;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn sat_one_of_leaves() {
+        assert_eq!(Some(BTreeMap::new()), Node::TRUE_LEAF.sat_one());
+        assert_eq!(None, Node::FALSE_LEAF.sat_one());
+    }
+
+    #[test]
+    fn sat_one_of_unsatisfiable_diagram_is_none() {
+        let blue = Item::new("shirts:blue");
+
+        let root = Node::positive_branch(&blue) & Node::negative_branch(&blue);
+
+        assert_eq!(None, root.sat_one());
+    }
+
+    #[test]
+    fn sat_one_satisfies_closet_root() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .add_exclusion_rule(&blue, &jeans)
+            .must_build();
+
+        let assignment = closet.root().sat_one().expect("expected closet to be satisfiable");
+
+        assert_eq!(Node::TRUE_LEAF, closet.root().restrict_all(&assignment));
+    }
+}
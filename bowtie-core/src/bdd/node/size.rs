@@ -0,0 +1,74 @@
+use bdd::node::Node;
+use bdd::node::NodeId;
+use std::collections::BTreeSet;
+
+impl Node {
+    /// Counts the distinct `Branch` nodes reachable from this node.
+    /// Shared sub-diagrams are only counted once, and leaves are not counted.
+    pub fn size(&self) -> usize {
+        let mut visited = BTreeSet::new();
+
+        let mut queue = vec![self.clone()];
+        while let Some(node) = queue.pop() {
+            if let Node::Branch(_, low, high) = node {
+                if visited.insert(NodeId::from(&node)) {
+                    queue.push(Node::from(low));
+                    queue.push(Node::from(high));
+                }
+            }
+        }
+
+        visited.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdd::closet_builder::ClosetBuilder;
+    use bdd::node::Node;
+    use core::Family;
+    use core::Item;
+
+    #[test]
+    fn size_of_leaves_is_zero() {
+        assert_eq!(0, Node::TRUE_LEAF.size());
+        assert_eq!(0, Node::FALSE_LEAF.size());
+    }
+
+    #[test]
+    fn size_of_single_branch_is_one() {
+        let blue = Item::new("shirts:blue");
+
+        assert_eq!(1, Node::positive_branch(&blue).size());
+    }
+
+    #[test]
+    fn size_counts_shared_nodes_once() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &blue)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .must_build();
+
+        assert_eq!(6, closet.root().size());
+        assert_eq!(9, expanded_size(closet.root()));
+    }
+
+    /// Counts branches once for every path that reaches them, as if nothing were shared.
+    fn expanded_size(node: &Node) -> usize {
+        match node {
+            Node::Leaf(_) => 0,
+            Node::Branch(_, low, high) => 1 + expanded_size(&Node::from(low)) + expanded_size(&Node::from(high)),
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use bdd::node::ite::ite_in;
+use bdd::node::ite::IteTable;
+use bdd::node::Node;
+use bdd::node::NodeId;
+use core::Item;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+lazy_static! {
+    static ref ORDER_IDS: RwLock<HashMap<Vec<Item>, usize>> = RwLock::new(HashMap::new());
+}
+
+/// The id of orders left out of `ORDER_IDS`, which the operation caches skip
+const UNREGISTERED: usize = usize::MAX;
+
+/// The order a diagram tests its items in. Items the order doesn't list are tested after those it does,
+/// in their natural order, so the empty order is the natural order of `Item`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "Vec<Item>", into = "Vec<Item>")]
+pub struct VariableOrder {
+    /// Tells orders apart in the operation caches, with 0 for the natural order and `UNREGISTERED` for throwaway orders
+    id: usize,
+    items: Arc<Vec<Item>>,
+    ranks: Arc<HashMap<Item, usize>>,
+}
+
+impl VariableOrder {
+    /// Tests `items` first, in the given order. Repeated items keep their first position.
+    pub fn new(items: &[Item]) -> VariableOrder {
+        let order = VariableOrder::unregistered(items);
+        if order.items.is_empty() {
+            return VariableOrder::default();
+        }
+
+        let id = {
+            let mut order_ids = ORDER_IDS.write().unwrap();
+            let next = order_ids.len() + 1;
+            *order_ids.entry(order.items.to_vec()).or_insert(next)
+        };
+
+        VariableOrder { id, ..order }
+    }
+
+    /// Like `new`, without giving the order an id, for orders that are tried and thrown away.
+    /// Operations in an unregistered order aren't remembered between calls.
+    pub(crate) fn unregistered(items: &[Item]) -> VariableOrder {
+        let mut ranks = HashMap::new();
+        let mut ordered = vec![];
+        for item in items {
+            if !ranks.contains_key(item) {
+                ranks.insert(item.without_meta(), ordered.len());
+                ordered.push(item.without_meta());
+            }
+        }
+
+        VariableOrder { id: UNREGISTERED, items: Arc::new(ordered), ranks: Arc::new(ranks) }
+    }
+
+    pub fn is_natural(&self) -> bool {
+        self.id == 0
+    }
+
+    pub(crate) fn is_registered(&self) -> bool {
+        self.id != UNREGISTERED
+    }
+
+    /// The items the order lists, first tested first.
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    pub(crate) fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn compare(&self, item: &Item, other: &Item) -> Ordering {
+        match (self.ranks.get(item), self.ranks.get(other)) {
+            (Some(rank), Some(other_rank)) => rank.cmp(other_rank),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => item.cmp(other),
+        }
+    }
+}
+
+impl PartialEq for VariableOrder {
+    fn eq(&self, other: &VariableOrder) -> bool {
+        match (self.is_registered(), other.is_registered()) {
+            (true, true) => self.id == other.id,
+            _ => self.items == other.items,
+        }
+    }
+}
+
+impl Eq for VariableOrder {}
+
+impl Hash for VariableOrder {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.items.hash(state);
+    }
+}
+
+impl From<Vec<Item>> for VariableOrder {
+    fn from(items: Vec<Item>) -> Self {
+        VariableOrder::new(&items)
+    }
+}
+
+impl From<VariableOrder> for Vec<Item> {
+    fn from(order: VariableOrder) -> Self {
+        order.items.to_vec()
+    }
+}
+
+impl Node {
+    /// Rebuilds the diagram to test its items in `order`, whatever order it tested them in before.
+    /// The result allows the same assignments.
+    pub fn reordered(&self, order: &VariableOrder) -> Node {
+        reordered(self, order, &mut HashMap::new(), &mut HashMap::new())
+    }
+}
+
+fn reordered(node: &Node, order: &VariableOrder, visited: &mut HashMap<NodeId, Node>, computed: &mut IteTable) -> Node {
+    match node {
+        Node::Leaf(_) => node.clone(),
+        Node::Branch(id, low, high) => {
+            if let Some(result) = visited.get(&NodeId::from(node)) {
+                return result.clone();
+            }
+
+            let low = reordered(&Node::from(low), order, visited, computed);
+            let high = reordered(&Node::from(high), order, visited, computed);
+
+            let result = ite_in(&Node::positive_branch(id), &high, &low, order, computed);
+            visited.insert(NodeId::from(node), result.clone());
+            result
+        }
+    }
+}
+
+#[cfg(test)]
+mod variable_order_tests {
+    use bdd::closet_builder::ClosetBuilder;
+    use bdd::node::BoolOp;
+    use bdd::node::Node;
+    use core::Family;
+    use core::Item;
+    use std::cmp::Ordering;
+    use super::VariableOrder;
+    use super::ORDER_IDS;
+
+    fn assert_paths_follow(node: &Node, order: &VariableOrder, previous: Option<&Item>) {
+        if let Node::Branch(id, low, high) = node {
+            if let Some(previous) = previous {
+                assert_eq!(Ordering::Less, order.compare(previous, id), "{:?} is tested after {:?}", id, previous);
+            }
+
+            assert_paths_follow(&Node::from(low), order, Some(id));
+            assert_paths_follow(&Node::from(high), order, Some(id));
+        }
+    }
+
+    #[test]
+    fn unlisted_items_follow_listed_items_in_natural_order() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let order = VariableOrder::new(&[red.clone(), jeans.clone(), red.clone()]);
+
+        assert_eq!(&[red.clone(), jeans.clone()], order.items());
+        assert_eq!(Ordering::Less, order.compare(&red, &jeans));
+        assert_eq!(Ordering::Less, order.compare(&jeans, &slacks));
+        assert_eq!(Ordering::Less, order.compare(&slacks, &blue));
+        assert_eq!(Ordering::Equal, order.compare(&blue, &blue));
+    }
+
+    #[test]
+    fn orders_listing_the_same_items_are_equal() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        assert_eq!(VariableOrder::new(&[red.clone(), blue.clone()]), VariableOrder::new(&[red.clone(), blue.clone()]));
+        assert_ne!(VariableOrder::new(&[red.clone(), blue.clone()]), VariableOrder::new(&[blue, red]));
+        assert_eq!(VariableOrder::default(), VariableOrder::new(&[]));
+        assert!(VariableOrder::new(&[]).is_natural());
+    }
+
+    #[test]
+    fn reordered_diagram_follows_order_and_keeps_outfits() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&shirts, &[blue.clone(), red.clone()])
+            .add_items(&pants, &[jeans.clone(), slacks.clone()])
+            .add_exclusion_rule(&red, &jeans)
+            .must_build();
+
+        let order = VariableOrder::new(&[red.clone(), slacks.clone(), blue.clone(), jeans.clone()]);
+        let reordered = closet.root().reordered(&order);
+        assert_paths_follow(&reordered, &order, None);
+
+        let items = closet.items().into_iter().cloned().collect::<Vec<_>>();
+        assert_eq!(closet.root().count_satisfying(&items), reordered.count_satisfying_in(&items, &order));
+        for assignments in closet.root().assignments(&items) {
+            assert_eq!(Node::TRUE_LEAF, reordered.restrict_all(&assignments));
+        }
+
+        assert_eq!(closet.root(), &reordered.reordered(&VariableOrder::default()));
+    }
+
+    #[test]
+    fn unregistered_order_reorders_alike_without_an_id() {
+        let shirts = Family::new("unregistered shirts");
+        let pants = Family::new("unregistered pants");
+
+        let blue = Item::new("unregistered shirts:blue");
+        let red = Item::new("unregistered shirts:red");
+        let jeans = Item::new("unregistered pants:jeans");
+        let slacks = Item::new("unregistered pants:slacks");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&shirts, &[blue.clone(), red.clone()])
+            .add_items(&pants, &[jeans.clone(), slacks.clone()])
+            .add_exclusion_rule(&red, &jeans)
+            .must_build();
+
+        let items = vec![red.clone(), slacks.clone(), blue.clone(), jeans.clone()];
+        let unregistered = VariableOrder::unregistered(&items);
+        let reordered = closet.root().reordered(&unregistered);
+        assert!(!ORDER_IDS.read().unwrap().contains_key(&items));
+
+        let order = VariableOrder::new(&items);
+        assert_eq!(order, unregistered);
+        assert_eq!(closet.root().reordered(&order), reordered);
+    }
+
+    #[test]
+    fn apply_in_order_matches_apply_in_natural_order() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Node::positive_branch(&red) ^ Node::positive_branch(&blue);
+        let pants = Node::positive_branch(&jeans) ^ Node::positive_branch(&slacks);
+        let order = VariableOrder::new(&[red.clone(), slacks.clone(), blue.clone(), jeans.clone()]);
+
+        for &op in &[BoolOp::And, BoolOp::Or, BoolOp::Xor, BoolOp::Imply] {
+            let expected = Node::apply_op(&shirts, &pants, op).reordered(&order);
+            let actual = Node::apply_in(&shirts.reordered(&order), &pants.reordered(&order), op, &order);
+
+            assert_eq!(expected, actual, "{:?}", op);
+            assert_paths_follow(&actual, &order, None);
+        }
+    }
+}
@@ -1,29 +1,91 @@
-use closet_builder::ClosetBuilderError::{CompoundError, ConflictingFamilies, ExclusionError, InclusionError, MissingFamily};
+use closet_builder::ClosetBuilderError::{CompoundError, ConflictingFamilies, EmptyClause, EmptyFamily, ExclusionError, FamilyUnsatisfiable, InclusionError, MalformedSpec, MissingFamily, UnknownRules, UnsatisfiableCardinality};
+use core::CardinalitySpec;
 use core::Family;
 use core::Item;
+use im::OrdMap;
+use im::Vector;
 use itertools::Itertools;
-use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
 
 #[derive(Debug, Eq, PartialEq, Clone, Hash, Ord, PartialOrd)]
 pub enum ClosetBuilderError {
     MissingFamily(Item),
     ConflictingFamilies(Item, Vec<Family>),
+    EmptyFamily(Family),
     InclusionError(Family, Vec<Item>),
     ExclusionError(Family, Vec<Item>),
     CompoundError(Vec<ClosetBuilderError>),
+    MalformedSpec(String),
+    FamilyUnsatisfiable(Family),
+    UnknownRules,
+    EmptyClause,
+    UnsatisfiableCardinality(Family, CardinalitySpec),
 }
 
+impl fmt::Display for ClosetBuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MissingFamily(item) =>
+                write!(f, "item '{}' is not assigned to a family", item),
+            ConflictingFamilies(item, families) =>
+                write!(f, "item '{}' is assigned to multiple families: {}", item, families.iter().join(", ")),
+            EmptyFamily(family) =>
+                write!(f, "family '{}' has no items", family),
+            InclusionError(family, items) =>
+                write!(f, "inclusion rule between items of the same family '{}': {}", family, items.iter().join(", ")),
+            ExclusionError(family, items) =>
+                write!(f, "exclusion rule between items of the same family '{}': {}", family, items.iter().join(", ")),
+            CompoundError(errors) =>
+                write!(f, "{}", errors.iter().join("; ")),
+            MalformedSpec(message) =>
+                write!(f, "malformed closet spec: {}", message),
+            FamilyUnsatisfiable(family) =>
+                write!(f, "no item of family '{}' is part of any valid outfit", family),
+            UnknownRules =>
+                write!(f, "closet does not know its rules apart from its families, rebuild it with a ClosetBuilder"),
+            EmptyClause =>
+                write!(f, "clause requires one of no items, so no outfit can satisfy it"),
+            UnsatisfiableCardinality(family, cardinality) =>
+                write!(f, "family '{}' can never hold {}", family, describe_cardinality(*cardinality)),
+        }
+    }
+}
+
+fn describe_cardinality(cardinality: CardinalitySpec) -> String {
+    let items = |count: usize| if count == 1 { "item" } else { "items" };
+
+    match cardinality {
+        CardinalitySpec::ExactlyOne => String::from("exactly 1 item"),
+        CardinalitySpec::AtMostOne => String::from("at most 1 item"),
+        CardinalitySpec::Exactly(count) => format!("exactly {} {}", count, items(count)),
+        CardinalitySpec::Range(min, max) => format!("between {} and {} items", min, max),
+    }
+}
+
+impl Error for ClosetBuilderError {}
+
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn validate_closet(
-    contents: &BTreeMap<Family, Vec<Item>>,
-    item_index: &BTreeMap<Item, Family>,
-    exclusions: &BTreeMap<Item, Vec<Item>>,
-    inclusions: &BTreeMap<Item, Vec<Item>>,
+    contents: &OrdMap<Family, Vec<Item>>,
+    item_index: &OrdMap<Item, Family>,
+    exclusions: &OrdMap<Item, Vec<Item>>,
+    inclusions: &OrdMap<Item, Vec<Item>>,
+    conditional_exclusions: &Vector<(Vec<Item>, Item, Item)>,
+    clauses: &Vector<Vec<Item>>,
+    soft_exclusions: &Vector<(Item, Item, i64)>,
+    cardinalities: &OrdMap<Family, CardinalitySpec>,
 ) -> Result<(), ClosetBuilderError> {
     let conflicts =
         vec![
             find_conflicting_families(contents, item_index),
-            find_illegal_rules(exclusions, item_index, ExclusionError),
-            find_illegal_rules(inclusions, item_index, InclusionError)
+            find_empty_families(contents, item_index),
+            find_illegal_exclusions(exclusions, item_index, cardinalities),
+            find_illegal_inclusions(inclusions, item_index, cardinalities),
+            find_illegal_conditional_exclusions(conditional_exclusions, item_index),
+            find_illegal_clauses(clauses, item_index),
+            find_unknown_soft_exclusion_items(soft_exclusions, item_index),
+            find_illegal_cardinalities(contents, cardinalities),
         ]
             .iter()
             .flat_map(|conflicts| conflicts)
@@ -38,7 +100,7 @@ pub(crate) fn validate_closet(
     }
 }
 
-fn find_conflicting_families(contents: &BTreeMap<Family, Vec<Item>>, item_index: &BTreeMap<Item, Family>) -> Vec<ClosetBuilderError> {
+fn find_conflicting_families(contents: &OrdMap<Family, Vec<Item>>, item_index: &OrdMap<Item, Family>) -> Vec<ClosetBuilderError> {
     contents.iter()
         .flat_map(|(family, items)| {
             items.iter()
@@ -59,9 +121,26 @@ fn find_conflicting_families(contents: &BTreeMap<Family, Vec<Item>>, item_index:
         .collect::<Vec<ClosetBuilderError>>()
 }
 
+fn find_empty_families(contents: &OrdMap<Family, Vec<Item>>, item_index: &OrdMap<Item, Family>) -> Vec<ClosetBuilderError> {
+    let declared_families = contents.iter()
+        .filter(|(_, items)| items.is_empty())
+        .map(|(family, _)| family);
+
+    let indexed_families = item_index.values()
+        .filter(|family| match contents.get(family) {
+            None => true,
+            Some(items) => items.is_empty(),
+        });
+
+    declared_families.chain(indexed_families)
+        .unique()
+        .map(|family| EmptyFamily(family.clone()))
+        .collect::<Vec<_>>()
+}
+
 fn find_illegal_rules(
-    rules: &BTreeMap<Item, Vec<Item>>,
-    item_index: &BTreeMap<Item, Family>,
+    rules: &OrdMap<Item, Vec<Item>>,
+    item_index: &OrdMap<Item, Family>,
     rule_error: fn(Family, Vec<Item>) -> ClosetBuilderError,
 ) -> Vec<ClosetBuilderError> {
     let find_selections_and_items_without_families = |(selection, items): (&Item, &Vec<Item>)| {
@@ -92,4 +171,225 @@ fn find_illegal_rules(
     rules.iter()
         .flat_map(find_selections_and_items_without_families)
         .collect::<Vec<_>>()
-}
\ No newline at end of file
+}
+
+/// Like `find_illegal_rules`, but allows exclusions within families that let an outfit hold several items.
+fn find_illegal_exclusions(
+    exclusions: &OrdMap<Item, Vec<Item>>,
+    item_index: &OrdMap<Item, Family>,
+    cardinalities: &OrdMap<Family, CardinalitySpec>,
+) -> Vec<ClosetBuilderError> {
+    find_illegal_rules(exclusions, item_index, ExclusionError).into_iter()
+        .filter(|error| match error {
+            ExclusionError(family, _) => cardinalities.get(family).copied().unwrap_or_default().bounds().1 <= 1,
+            _ => true,
+        })
+        .collect::<Vec<_>>()
+}
+
+/// Like `find_illegal_rules`, but reports every same-family item an inclusion requires in one error.
+/// As with exclusions, inclusions within families that let an outfit hold several items are allowed.
+fn find_illegal_inclusions(
+    inclusions: &OrdMap<Item, Vec<Item>>,
+    item_index: &OrdMap<Item, Family>,
+    cardinalities: &OrdMap<Family, CardinalitySpec>,
+) -> Vec<ClosetBuilderError> {
+    let find_selections_and_items_without_families = |(selection, items): (&Item, &Vec<Item>)| {
+        let selection_family = match item_index.get(selection) {
+            None => return vec![MissingFamily(selection.clone())],
+            Some(selection_family) => selection_family,
+        };
+
+        let mut errors = vec![];
+        let mut same_family_items = vec![];
+        for item in items.iter().unique() {
+            match item_index.get(item) {
+                None => errors.push(MissingFamily(item.clone())),
+                Some(item_family) if item_family == selection_family => same_family_items.push(item.clone()),
+                Some(_) => {}
+            }
+        }
+
+        let holds_one_item = cardinalities.get(selection_family).copied().unwrap_or_default().bounds().1 <= 1;
+        if holds_one_item && !same_family_items.is_empty() {
+            same_family_items.push(selection.clone());
+            same_family_items.sort();
+            same_family_items.dedup();
+
+            errors.push(InclusionError(selection_family.clone(), same_family_items));
+        }
+        errors
+    };
+
+    inclusions.iter()
+        .flat_map(find_selections_and_items_without_families)
+        .collect::<Vec<_>>()
+}
+
+fn find_illegal_conditional_exclusions(
+    conditional_exclusions: &Vector<(Vec<Item>, Item, Item)>,
+    item_index: &OrdMap<Item, Family>,
+) -> Vec<ClosetBuilderError> {
+    conditional_exclusions.iter()
+        .flat_map(|(condition, selection, exclusion)| {
+            let mut rule = OrdMap::new();
+            rule.insert(selection.clone(), vec![exclusion.clone()]);
+
+            let pair_errors = find_illegal_rules(&rule, item_index, ExclusionError);
+            if !pair_errors.is_empty() {
+                return pair_errors;
+            }
+
+            condition.iter()
+                .filter_map(|item| {
+                    let item_family = match item_index.get(item) {
+                        None => return Some(MissingFamily(item.clone())),
+                        Some(item_family) => item_family,
+                    };
+
+                    [selection, exclusion].iter()
+                        .find(|excluded| item_index.get(excluded) == Some(item_family) && *excluded != &item)
+                        .map(|excluded| {
+                            let mut items = vec![(*excluded).clone(), item.clone()];
+                            items.sort();
+
+                            ExclusionError(item_family.clone(), items)
+                        })
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>()
+}
+
+fn find_illegal_clauses(clauses: &Vector<Vec<Item>>, item_index: &OrdMap<Item, Family>) -> Vec<ClosetBuilderError> {
+    clauses.iter()
+        .flat_map(|clause| {
+            if clause.is_empty() {
+                return vec![EmptyClause];
+            }
+
+            clause.iter()
+                .filter(|item| !item_index.contains_key(item))
+                .map(|item| MissingFamily(item.clone()))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>()
+}
+
+fn find_illegal_cardinalities(contents: &OrdMap<Family, Vec<Item>>, cardinalities: &OrdMap<Family, CardinalitySpec>) -> Vec<ClosetBuilderError> {
+    cardinalities.iter()
+        .filter_map(|(family, cardinality)| {
+            let size = match contents.get(family) {
+                None => return Some(EmptyFamily(family.clone())),
+                Some(items) => items.len(),
+            };
+
+            let (min, max) = cardinality.bounds();
+            if min > max || min > size {
+                Some(UnsatisfiableCardinality(family.clone(), *cardinality))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>()
+}
+
+fn find_unknown_soft_exclusion_items(soft_exclusions: &Vector<(Item, Item, i64)>, item_index: &OrdMap<Item, Family>) -> Vec<ClosetBuilderError> {
+    soft_exclusions.iter()
+        .flat_map(|(selection, exclusion, _)| vec![selection, exclusion])
+        .filter(|item| !item_index.contains_key(item))
+        .map(|item| MissingFamily(item.clone()))
+        .collect::<Vec<_>>()
+}
+
+#[cfg(test)]
+mod tests {
+    use closet_builder::ClosetBuilderError::{CompoundError, ConflictingFamilies, EmptyClause, EmptyFamily, ExclusionError, FamilyUnsatisfiable, InclusionError, MalformedSpec, MissingFamily, UnknownRules, UnsatisfiableCardinality};
+    use core::CardinalitySpec;
+    use core::Family;
+    use core::Item;
+
+    #[test]
+    fn missing_family_message() {
+        let error = MissingFamily(Item::new("blue"));
+
+        assert_eq!("item 'blue' is not assigned to a family", error.to_string());
+    }
+
+    #[test]
+    fn conflicting_families_message() {
+        let error = ConflictingFamilies(Item::new("blue"), vec![Family::new("shirts"), Family::new("pants")]);
+
+        assert_eq!("item 'blue' is assigned to multiple families: shirts, pants", error.to_string());
+    }
+
+    #[test]
+    fn empty_family_message() {
+        let error = EmptyFamily(Family::new("shoes"));
+
+        assert_eq!("family 'shoes' has no items", error.to_string());
+    }
+
+    #[test]
+    fn inclusion_error_message() {
+        let error = InclusionError(Family::new("shirts"), vec![Item::new("blue"), Item::new("red")]);
+
+        assert_eq!("inclusion rule between items of the same family 'shirts': blue, red", error.to_string());
+    }
+
+    #[test]
+    fn exclusion_error_message() {
+        let error = ExclusionError(Family::new("pants"), vec![Item::new("jeans"), Item::new("slacks")]);
+
+        assert_eq!("exclusion rule between items of the same family 'pants': jeans, slacks", error.to_string());
+    }
+
+    #[test]
+    fn family_unsatisfiable_message() {
+        let error = FamilyUnsatisfiable(Family::new("pants"));
+
+        assert_eq!("no item of family 'pants' is part of any valid outfit", error.to_string());
+    }
+
+    #[test]
+    fn malformed_spec_message() {
+        let error = MalformedSpec(String::from("missing field `families` at line 1 column 2"));
+
+        assert_eq!("malformed closet spec: missing field `families` at line 1 column 2", error.to_string());
+    }
+
+    #[test]
+    fn unknown_rules_message() {
+        assert_eq!(
+            "closet does not know its rules apart from its families, rebuild it with a ClosetBuilder",
+            UnknownRules.to_string()
+        );
+    }
+
+    #[test]
+    fn empty_clause_message() {
+        assert_eq!("clause requires one of no items, so no outfit can satisfy it", EmptyClause.to_string());
+    }
+
+    #[test]
+    fn unsatisfiable_cardinality_message() {
+        let error = UnsatisfiableCardinality(Family::new("shirts"), CardinalitySpec::Range(2, 1));
+        assert_eq!("family 'shirts' can never hold between 2 and 1 items", error.to_string());
+
+        let error = UnsatisfiableCardinality(Family::new("accessories"), CardinalitySpec::Exactly(4));
+        assert_eq!("family 'accessories' can never hold exactly 4 items", error.to_string());
+    }
+
+    #[test]
+    fn compound_error_message() {
+        let error = CompoundError(vec![
+            MissingFamily(Item::new("blue")),
+            ExclusionError(Family::new("pants"), vec![Item::new("jeans"), Item::new("slacks")]),
+        ]);
+
+        assert_eq!(
+            "item 'blue' is not assigned to a family; exclusion rule between items of the same family 'pants': jeans, slacks",
+            error.to_string()
+        );
+    }
+}
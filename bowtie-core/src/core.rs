@@ -1,4 +1,16 @@
+use itertools::Itertools;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+use std::any::Any;
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct Family(String);
@@ -7,20 +19,179 @@ impl Family {
     pub fn new<S>(id: S) -> Family where S: Into<String> {
         Family(id.into())
     }
+
+    pub fn from_id(id: u32) -> Family {
+        Family(id.to_string())
+    }
+
+    pub fn key(&self) -> &str {
+        &self.0
+    }
+
+    /// Parses the key back into the id given to `from_id`.
+    pub fn id(&self) -> Option<u32> {
+        self.0.parse().ok()
+    }
+}
+
+impl From<u32> for Family {
+    fn from(id: u32) -> Self {
+        Family::from_id(id)
+    }
+}
+
+impl fmt::Display for Family {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
 }
 
 impl From<Item> for String {
     fn from(item: Item) -> Self {
-        item.0
+        item.id
     }
 }
 
-#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
-pub struct Item(String);
+/// An item of a closet, identified by its id and, for scoped items, the family it is scoped to.
+/// The optional metadata is carried along but never compared, hashed or serialized.
+#[derive(Clone)]
+pub struct Item {
+    id: String,
+    scope: Option<Family>,
+    meta: Option<Arc<dyn Any + Send + Sync>>,
+}
 
 impl Item {
     pub fn new<S>(id: S) -> Item where S: Into<String> {
-        Item(id.into())
+        Item { id: id.into(), scope: None, meta: None }
+    }
+
+    /// Creates an item carrying `meta`, e.g. a price or an image url, which can be read back
+    /// with `meta` from the outfits a closet completes.
+    pub fn with_meta<S, T>(id: S, meta: T) -> Item where S: Into<String>, T: Any + Send + Sync {
+        Item { id: id.into(), scope: None, meta: Some(Arc::new(meta)) }
+    }
+
+    /// Creates an item keyed by a numeric id. The key is still a string, so items
+    /// are ordered as text, e.g. `10` before `2`.
+    pub fn from_id(id: u32) -> Item {
+        Item::new(id.to_string())
+    }
+
+    pub fn key(&self) -> &str {
+        &self.id
+    }
+
+    /// Parses the key back into the id given to `from_id`.
+    pub fn id(&self) -> Option<u32> {
+        self.id.parse().ok()
+    }
+
+    /// The metadata given to `with_meta`, if it has type `T`.
+    pub fn meta<T: Any>(&self) -> Option<&T> {
+        self.meta.as_ref()
+            .and_then(|meta| meta.downcast_ref::<T>())
+    }
+
+    /// The same item without its metadata, for storing in diagram nodes that outlive any one closet.
+    pub(crate) fn without_meta(&self) -> Item {
+        Item { id: self.id.clone(), scope: self.scope.clone(), meta: None }
+    }
+
+    pub(crate) fn renamed(&self, id: String) -> Item {
+        Item { id, scope: self.scope.clone(), meta: self.meta.clone() }
+    }
+
+    /// Creates an item scoped to `family`, so the same name can be added to several families without conflicting.
+    pub fn scoped<S>(family: &Family, name: S) -> Item where S: Into<String> {
+        Item::new(name).scoped_to(family)
+    }
+
+    pub(crate) fn scoped_to(&self, family: &Family) -> Item {
+        Item { id: self.id.clone(), scope: Some(family.clone()), meta: self.meta.clone() }
+    }
+
+    /// The family given to `scoped`, if any.
+    pub fn scope(&self) -> Option<&Family> {
+        self.scope.as_ref()
+    }
+}
+
+impl fmt::Debug for Item {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.scope {
+            None => f.debug_tuple("Item").field(&self.id).finish(),
+            Some(scope) => f.debug_tuple("Item").field(&scope.0).field(&self.id).finish(),
+        }
+    }
+}
+
+impl PartialEq for Item {
+    fn eq(&self, other: &Item) -> bool {
+        self.scope == other.scope && self.id == other.id
+    }
+}
+
+impl Eq for Item {}
+
+impl PartialOrd for Item {
+    fn partial_cmp(&self, other: &Item) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Item {
+    fn cmp(&self, other: &Item) -> Ordering {
+        (&self.scope, &self.id).cmp(&(&other.scope, &other.id))
+    }
+}
+
+impl Hash for Item {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.scope.hash(state);
+        self.id.hash(state);
+    }
+}
+
+impl Serialize for Item {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match &self.scope {
+            None => serializer.serialize_newtype_struct("Item", &self.id),
+            Some(scope) => serializer.serialize_newtype_struct("Item", &(scope, &self.id)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Item {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Item, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum ItemKey {
+            Plain(String),
+            Scoped(Family, String),
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename = "Item")]
+        struct ItemId(ItemKey);
+
+        ItemId::deserialize(deserializer)
+            .map(|ItemId(key)| match key {
+                ItemKey::Plain(id) => Item::new(id),
+                ItemKey::Scoped(scope, id) => Item::scoped(&scope, id),
+            })
+    }
+}
+
+impl From<u32> for Item {
+    fn from(id: u32) -> Self {
+        Item::from_id(id)
+    }
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.id)
     }
 }
 
@@ -71,6 +242,27 @@ impl ItemStatus {
     }
 }
 
+/// How many items of a family an outfit contains. Families are `ExactlyOne` unless set otherwise.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Serialize, Deserialize)]
+pub enum CardinalitySpec {
+    #[default]
+    ExactlyOne,
+    AtMostOne,
+    Exactly(usize),
+    Range(usize, usize),
+}
+
+impl CardinalitySpec {
+    pub fn bounds(self) -> (usize, usize) {
+        match self {
+            CardinalitySpec::ExactlyOne => (1, 1),
+            CardinalitySpec::AtMostOne => (0, 1),
+            CardinalitySpec::Exactly(n) => (n, n),
+            CardinalitySpec::Range(lo, hi) => (lo, hi),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Outfit {
     items: Vec<Item>
@@ -81,9 +273,110 @@ impl Outfit {
         Outfit { items }
     }
 
-    pub fn items(&self) -> &Vec<Item> {
+    pub fn items(&self) -> &[Item] {
         &self.items
     }
+
+    pub fn contains(&self, item: &Item) -> bool {
+        self.items.contains(item)
+    }
+
+    /// The items found in every one of `outfits`, sorted. No outfits have no items in common.
+    pub fn intersection(outfits: &[Outfit]) -> Vec<Item> {
+        let (first, rest) = match outfits.split_first() {
+            None => return vec![],
+            Some(split) => split,
+        };
+
+        first.items.iter()
+            .filter(|item| rest.iter().all(|outfit| outfit.contains(item)))
+            .unique()
+            .sorted()
+            .cloned()
+            .collect()
+    }
+
+    /// The items only this outfit has and the items only `other` has, each sorted.
+    pub fn diff(&self, other: &Outfit) -> (Vec<Item>, Vec<Item>) {
+        let only = |outfit: &Outfit, other: &Outfit| outfit.items.iter()
+            .filter(|item| !other.contains(item))
+            .unique()
+            .sorted()
+            .cloned()
+            .collect();
+
+        (only(self, other), only(other, self))
+    }
+}
+
+impl<'a> IntoIterator for &'a Outfit {
+    type Item = &'a Item;
+    type IntoIter = ::std::slice::Iter<'a, Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+/// Orders two items, with the item to prefer ordering first.
+pub type ItemComparison = dyn Fn(&Item, &Item) -> Ordering;
+
+/// Which item `Closet::complete_outfit_with` takes when a family has more than one item left to choose from.
+#[derive(Default)]
+pub enum TieBreak {
+    /// Takes the first item the diagram tests, as `complete_outfit` does.
+    #[default]
+    HighBranch,
+    /// Skips items for as long as the outfit can still be completed without them.
+    LowBranch,
+    /// Takes the completion whose sorted items compare first, whatever order the diagram tests items in.
+    /// An outfit that leaves out an optional item compares before one that ends with it.
+    Lexicographic,
+    /// Takes the item that orders first by the comparison.
+    Custom(Box<ItemComparison>),
+}
+
+impl fmt::Debug for TieBreak {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TieBreak::HighBranch => write!(f, "HighBranch"),
+            TieBreak::LowBranch => write!(f, "LowBranch"),
+            TieBreak::Lexicographic => write!(f, "Lexicographic"),
+            TieBreak::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// Why an item ended up in an outfit completed by `Closet::explain_outfit`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum DecisionReason {
+    /// The item was one of the selections.
+    Selected,
+    /// Leaving the item out would have left no valid outfit.
+    OnlyOption,
+    /// Either choice was possible, and the walk takes the item by default.
+    Default,
+}
+
+/// An item the completion walk added to the outfit, and why.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct BranchDecision {
+    item: Item,
+    reason: DecisionReason,
+}
+
+impl BranchDecision {
+    pub fn new(item: Item, reason: DecisionReason) -> BranchDecision {
+        BranchDecision { item, reason }
+    }
+
+    pub fn item(&self) -> &Item {
+        &self.item
+    }
+
+    pub fn reason(&self) -> DecisionReason {
+        self.reason
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -91,10 +384,234 @@ pub enum OutfitError {
     UnknownItems(Vec<Item>),
     IncompatibleSelections(Vec<Item>),
     MultipleItemsPerFamily(BTreeMap<Family, Vec<Item>>),
+    NoValidCompletion(Vec<Item>),
+    DuplicateSelection(Item),
 }
 
+impl fmt::Display for OutfitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OutfitError::UnknownItems(items) =>
+                write!(f, "unknown items: {}", items.iter().join(", ")),
+            OutfitError::IncompatibleSelections(items) =>
+                write!(f, "incompatible selections: {}", items.iter().join(", ")),
+            OutfitError::MultipleItemsPerFamily(duplicates) =>
+                write!(
+                    f,
+                    "multiple items selected per family: {}",
+                    duplicates.iter()
+                        .map(|(family, items)| format!("{} ({})", family, items.iter().join(", ")))
+                        .join("; ")
+                ),
+            OutfitError::NoValidCompletion(forbidden) =>
+                write!(f, "no valid completion without: {}", forbidden.iter().join(", ")),
+            OutfitError::DuplicateSelection(item) =>
+                write!(f, "item selected more than once: {}", item),
+        }
+    }
+}
+
+impl Error for OutfitError {}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum DiffError {
+    MismatchedFamilies(Vec<Family>, Vec<Family>),
+}
+
+impl fmt::Display for DiffError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DiffError::MismatchedFamilies(families, other_families) =>
+                write!(f, "closets have different families: [{}] and [{}]", families.iter().join(", "), other_families.iter().join(", ")),
+        }
+    }
+}
+
+impl Error for DiffError {}
+
+/// An assignment of every item of a closet, and whether it is a valid outfit.
+pub type TruthTableRow = (BTreeMap<Item, bool>, bool);
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum TruthTableError {
+    TooManyItems(usize, usize),
+}
+
+impl fmt::Display for TruthTableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TruthTableError::TooManyItems(items, max) =>
+                write!(f, "closet has {} items, but truth tables are limited to {}", items, max),
+        }
+    }
+}
+
+impl Error for TruthTableError {}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum SelectItemError {
     ExcludedItem { excluded: Item },
     UnknownItem(Item),
 }
+
+#[cfg(test)]
+mod tests {
+    use core::Family;
+    use core::Item;
+    use core::Outfit;
+    use core::OutfitError;
+    use core::TruthTableError;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn scoped_items_keep_their_name_apart_from_their_family() {
+        let shirts = Family::new("shirts");
+        let shoes = Family::new("shoes");
+
+        let black = Item::scoped(&shirts, "black");
+        assert_eq!("black", black.to_string());
+        assert_eq!(Some(&shirts), black.scope());
+        assert_ne!(Item::new("shirts:black"), black);
+        assert_ne!(Item::new("black"), black);
+        assert_ne!(Item::scoped(&shoes, "black"), black);
+        assert_ne!(Item::scoped(&Family::new("a:b"), "c"), Item::scoped(&Family::new("a"), "b:c"));
+    }
+
+    #[test]
+    fn numeric_ids_round_trip_through_keys() {
+        let item = Item::from_id(42);
+        let family = Family::from(7);
+
+        assert_eq!("42", item.key());
+        assert_eq!(Some(42), item.id());
+        assert_eq!(Item::from(42), item);
+        assert_eq!("7", family.key());
+        assert_eq!(Some(7), family.id());
+        assert_eq!(None, Item::new("shirts:blue").id());
+    }
+
+    #[test]
+    fn outfit_exposes_its_items() {
+        let blue = Item::new("shirts:blue");
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let outfit = Outfit::new(vec![jeans.clone(), blue.clone()]);
+
+        assert_eq!(&[jeans.clone(), blue.clone()], outfit.items());
+        assert!(outfit.contains(&blue));
+        assert!(outfit.contains(&jeans));
+        assert!(!outfit.contains(&slacks));
+    }
+
+    #[test]
+    fn outfit_iterates_over_its_items() {
+        let blue = Item::new("shirts:blue");
+        let jeans = Item::new("pants:jeans");
+
+        let outfit = Outfit::new(vec![jeans.clone(), blue.clone()]);
+
+        let mut items = Vec::new();
+        for item in &outfit {
+            items.push(item.clone());
+        }
+        assert_eq!(vec![jeans, blue], items);
+
+        assert_eq!(0, (&Outfit::new(vec![])).into_iter().count());
+    }
+
+    #[test]
+    fn meta_is_ignored_by_comparisons() {
+        let plain = Item::new("shirts:blue");
+        let priced = Item::with_meta("shirts:blue", 25u32);
+
+        assert_eq!(plain, priced);
+        assert_eq!(Some(&25u32), priced.meta::<u32>());
+        assert_eq!(None, priced.meta::<String>());
+        assert_eq!(None, plain.meta::<u32>());
+        assert_eq!("Item(\"shirts:blue\")", format!("{:?}", priced));
+    }
+
+    #[test]
+    fn intersection_keeps_items_in_every_outfit() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+        let belt = Item::new("belts:leather");
+
+        let outfits = vec![
+            Outfit::new(vec![blue.clone(), jeans.clone(), belt.clone()]),
+            Outfit::new(vec![red.clone(), jeans.clone(), belt.clone()]),
+            Outfit::new(vec![belt.clone(), blue.clone(), slacks.clone()]),
+        ];
+
+        assert_eq!(vec![belt.clone()], Outfit::intersection(&outfits));
+        assert_eq!(vec![belt.clone(), jeans.clone()], Outfit::intersection(&outfits[..2]));
+        assert_eq!(vec![belt, jeans, blue], Outfit::intersection(&outfits[..1]));
+        assert_eq!(Vec::<Item>::new(), Outfit::intersection(&[]));
+    }
+
+    #[test]
+    fn diff_splits_items_unique_to_each_outfit() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+
+        let outfit = Outfit::new(vec![jeans.clone(), red.clone()]);
+        let other = Outfit::new(vec![jeans.clone(), blue.clone()]);
+
+        assert_eq!((vec![red.clone()], vec![blue.clone()]), outfit.diff(&other));
+        assert_eq!((vec![blue], vec![red]), other.diff(&outfit));
+        assert_eq!((vec![], vec![]), outfit.diff(&outfit));
+    }
+
+    #[test]
+    fn too_many_items_message() {
+        let error = TruthTableError::TooManyItems(20, 16);
+
+        assert_eq!("closet has 20 items, but truth tables are limited to 16", error.to_string());
+    }
+
+    #[test]
+    fn unknown_items_message() {
+        let error = OutfitError::UnknownItems(vec![Item::new("hat"), Item::new("scarf")]);
+
+        assert_eq!("unknown items: hat, scarf", error.to_string());
+    }
+
+    #[test]
+    fn incompatible_selections_message() {
+        let error = OutfitError::IncompatibleSelections(vec![Item::new("blue"), Item::new("jeans")]);
+
+        assert_eq!("incompatible selections: blue, jeans", error.to_string());
+    }
+
+    #[test]
+    fn multiple_items_per_family_message() {
+        let mut duplicates = BTreeMap::new();
+        duplicates.insert(Family::new("shirts"), vec![Item::new("blue"), Item::new("red")]);
+        duplicates.insert(Family::new("pants"), vec![Item::new("jeans"), Item::new("slacks")]);
+
+        let error = OutfitError::MultipleItemsPerFamily(duplicates);
+
+        assert_eq!(
+            "multiple items selected per family: pants (jeans, slacks); shirts (blue, red)",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn duplicate_selection_message() {
+        let error = OutfitError::DuplicateSelection(Item::new("blue"));
+
+        assert_eq!("item selected more than once: blue", error.to_string());
+    }
+
+    #[test]
+    fn no_valid_completion_message() {
+        let error = OutfitError::NoValidCompletion(vec![Item::new("blue"), Item::new("red")]);
+
+        assert_eq!("no valid completion without: blue, red", error.to_string());
+    }
+}
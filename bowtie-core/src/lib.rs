@@ -1,9 +1,15 @@
+extern crate im;
 extern crate itertools;
 #[macro_use]
 extern crate lazy_static;
+extern crate rand;
+#[cfg(test)]
+extern crate rand_xoshiro;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+#[cfg(feature = "json")]
+extern crate serde_json;
 
 pub mod closet_builder;
 pub mod core;
@@ -0,0 +1,149 @@
+extern crate bowtie_core;
+extern crate serde_json;
+
+#[cfg(test)]
+mod tests {
+    use bowtie_core::bdd::Closet;
+    use bowtie_core::bdd::ClosetBuilder;
+    use bowtie_core::core::Family;
+    use bowtie_core::core::Item;
+
+    #[test]
+    fn round_trip_preserves_completed_outfits() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+        let grey = Item::new("shirts:grey");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let sneakers = Item::new("shoes:sneakers");
+        let birkenstocks = Item::new("shoes:birkenstocks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+        let shoes = Family::new("shoes");
+
+        let closet_builder = ClosetBuilder::new()
+            .add_item(&shirts, &blue)
+            .add_item(&shirts, &red)
+            .add_item(&shirts, &grey)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .add_item(&shoes, &birkenstocks)
+            .add_item(&shoes, &sneakers)
+            .add_exclusion_rule(&blue, &jeans)
+            .add_exclusion_rule(&grey, &jeans)
+            .add_inclusion_rule(&birkenstocks, &slacks);
+        let closet = closet_builder.must_build();
+
+        let json = serde_json::to_string(&closet).expect("expected Closet to serialize");
+        let restored: Closet = serde_json::from_str(&json).expect("expected Closet to deserialize");
+
+        assert_eq!(closet, restored);
+
+        let selections = vec![
+            vec![],
+            vec![blue.clone()],
+            vec![red.clone()],
+            vec![jeans.clone()],
+            vec![birkenstocks.clone()],
+            vec![grey.clone(), sneakers.clone()],
+            vec![blue.clone(), jeans.clone()],
+            vec![Item::new("hats:fedora")],
+        ];
+        for selection in selections {
+            assert_eq!(
+                closet.complete_outfit(selection.clone()),
+                restored.complete_outfit(selection)
+            );
+        }
+    }
+
+    #[test]
+    fn closet_serialized_without_rules_still_deserializes() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &Item::new("shirts:blue"))
+            .add_item(&shirts, &Item::new("shirts:red"))
+            .add_item(&pants, &Item::new("pants:jeans"))
+            .add_exclusion_rule(&Item::new("shirts:red"), &Item::new("pants:jeans"))
+            .must_build();
+
+        let mut json = serde_json::to_value(&closet).expect("expected Closet to serialize");
+        json.as_object_mut().unwrap().remove("rules");
+        let restored: Closet = serde_json::from_value(json).expect("expected Closet to deserialize");
+
+        assert_eq!(closet, restored);
+        assert_eq!(closet.complete_outfit(vec![]), restored.complete_outfit(vec![]));
+    }
+
+    #[test]
+    fn family_map_is_rebuilt_rather_than_stored() {
+        let shirts = Family::new("shirts");
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &Item::new("shirts:blue"))
+            .add_item(&shirts, &Item::new("shirts:red"))
+            .must_build();
+
+        let json = serde_json::to_value(&closet).expect("expected Closet to serialize");
+        assert!(json.get("family_map").is_none());
+
+        let restored: Closet = serde_json::from_value(json).expect("expected Closet to deserialize");
+        assert_eq!(closet.family_map(), restored.family_map());
+        assert_eq!(Some(&shirts), restored.family_map().get(&Item::new("shirts:red")));
+    }
+
+    #[test]
+    fn variable_order_is_stored_only_once_reordered() {
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+
+        let mut closet = ClosetBuilder::new()
+            .add_items(&shirts, &[Item::new("shirts:blue"), red.clone()])
+            .add_items(&pants, &[jeans.clone(), Item::new("pants:slacks")])
+            .add_exclusion_rule(&red, &jeans)
+            .must_build();
+
+        let json = serde_json::to_value(&closet).expect("expected Closet to serialize");
+        assert!(json.get("order").is_none());
+
+        closet.reorder(&[red, jeans]);
+        let json = serde_json::to_value(&closet).expect("expected Closet to serialize");
+        let restored: Closet = serde_json::from_value(json).expect("expected Closet to deserialize");
+
+        assert_eq!(closet, restored);
+        assert_eq!(closet.variable_order(), restored.variable_order());
+        assert_eq!(closet.outfit_count(), restored.outfit_count());
+        assert_eq!(closet.complete_outfit(vec![]), restored.complete_outfit(vec![]));
+    }
+
+    #[test]
+    fn scoped_items_round_trip() {
+        let shirts = Family::new("shirts");
+        let shoes = Family::new("shoes");
+
+        let closet = ClosetBuilder::new()
+            .add_scoped_item(&shirts, &Item::new("black"))
+            .add_scoped_item(&shirts, &Item::new("white"))
+            .add_scoped_item(&shoes, &Item::new("black"))
+            .add_scoped_item(&shoes, &Item::new("brown"))
+            .add_exclusion_rule(&Item::scoped(&shirts, "black"), &Item::scoped(&shoes, "black"))
+            .must_build();
+
+        let json = serde_json::to_string(&closet).expect("expected Closet to serialize");
+        let restored: Closet = serde_json::from_str(&json).expect("expected Closet to deserialize");
+
+        assert_eq!(closet, restored);
+        assert_eq!(Some(&shoes), restored.get_family(&Item::scoped(&shoes, "black")));
+        assert_eq!(
+            closet.complete_outfit(vec![Item::scoped(&shirts, "black")]),
+            restored.complete_outfit(vec![Item::scoped(&shirts, "black")])
+        );
+    }
+}
@@ -6,6 +6,7 @@ mod tests {
     use bowtie_core::closet_builder::ClosetBuilderError;
     use bowtie_core::core::Family;
     use bowtie_core::core::Item;
+    use bowtie_core::core::Outfit;
 
     #[test]
     fn adding_item_to_two_families_returns_error() {
@@ -138,4 +139,56 @@ mod tests {
             error
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn same_name_scoped_to_two_families_does_not_conflict() {
+        let shirts = Family::new("shirts");
+        let shoes = Family::new("shoes");
+
+        let black_shirt = Item::scoped(&shirts, "black");
+        let white_shirt = Item::scoped(&shirts, "white");
+        let black_shoes = Item::scoped(&shoes, "black");
+        let brown_shoes = Item::scoped(&shoes, "brown");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &black_shirt)
+            .add_item(&shirts, &white_shirt)
+            .add_item(&shoes, &black_shoes)
+            .add_item(&shoes, &brown_shoes)
+            .add_exclusion_rule(&black_shirt, &black_shoes)
+            .build()
+            .expect("expected scoped items to build");
+
+        assert_eq!(Some(&shirts), closet.get_family(&black_shirt));
+        assert_eq!(Some(&shoes), closet.get_family(&black_shoes));
+        assert_eq!(
+            Ok(Outfit::new(vec![black_shirt.clone(), brown_shoes])),
+            closet.complete_outfit(vec![black_shirt])
+        );
+    }
+
+    #[test]
+    fn same_name_added_scoped_to_two_families_does_not_conflict() {
+        let black = Item::new("black");
+        let white = Item::new("white");
+
+        let shirts = Family::new("shirts");
+        let shoes = Family::new("shoes");
+
+        let closet = ClosetBuilder::new()
+            .add_scoped_item(&shirts, &black)
+            .add_scoped_item(&shirts, &white)
+            .add_scoped_item(&shoes, &black)
+            .add_scoped_item(&shoes, &white)
+            .add_exclusion_rule(&Item::scoped(&shirts, "black"), &Item::scoped(&shoes, "black"))
+            .build()
+            .unwrap();
+
+        assert_eq!(vec![&shirts, &shoes], closet.families());
+        assert_eq!(4, closet.items().len());
+        assert_eq!(
+            Ok(Outfit::new(vec![Item::scoped(&shirts, "black"), Item::scoped(&shoes, "white")])),
+            closet.complete_outfit(vec![Item::scoped(&shirts, "black")])
+        );
+    }
+}
@@ -1,4 +1,6 @@
 extern crate bowtie_core;
+extern crate rand;
+extern crate rand_xoshiro;
 
 #[cfg(test)]
 mod no_rules_tests {
@@ -355,3 +357,733 @@ mod all_rules_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod validated_tests {
+    use bowtie_core::bdd::ClosetBuilder;
+    use bowtie_core::core::Family;
+    use bowtie_core::core::Item;
+    use bowtie_core::core::Outfit;
+    use bowtie_core::core::OutfitError::IncompatibleSelections;
+    use bowtie_core::core::OutfitError::MultipleItemsPerFamily;
+    use bowtie_core::core::OutfitError::UnknownItems;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn unknown_and_duplicate_selections_are_both_reported() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &blue)
+            .add_item(&shirts, &red)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .must_build();
+
+        let hat = Item::new("hats:fedora");
+        let selections = vec![blue.clone(), hat.clone(), red.clone()];
+
+        let mut duplicates = BTreeMap::new();
+        duplicates.insert(shirts.clone(), vec![blue.clone(), red.clone()]);
+
+        let expected = Err(vec![
+            UnknownItems(vec![hat]),
+            MultipleItemsPerFamily(duplicates),
+        ]);
+        assert_eq!(expected, closet.complete_outfit_validated(selections));
+    }
+
+    #[test]
+    fn unknown_and_incompatible_selections_are_both_reported() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &blue)
+            .add_item(&shirts, &red)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .add_exclusion_rule(&blue, &jeans)
+            .must_build();
+
+        let hat = Item::new("hats:fedora");
+
+        let expected = Err(vec![
+            UnknownItems(vec![hat.clone()]),
+            IncompatibleSelections(vec![jeans.clone(), blue.clone()]),
+        ]);
+        assert_eq!(expected, closet.complete_outfit_validated(vec![blue.clone(), hat, jeans.clone()]));
+    }
+
+    #[test]
+    fn valid_selections_complete_outfit() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &blue)
+            .add_item(&shirts, &red)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .must_build();
+
+        let expected = Ok(Outfit::new(vec![slacks.clone(), blue.clone()]));
+        assert_eq!(expected, closet.complete_outfit_validated(vec![slacks]));
+    }
+}
+
+#[cfg(test)]
+mod preferred_tests {
+    use bowtie_core::bdd::ClosetBuilder;
+    use bowtie_core::core::Family;
+    use bowtie_core::core::Item;
+    use bowtie_core::core::Outfit;
+    use bowtie_core::core::OutfitError::UnknownItems;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn priorities_choose_default_items() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &blue)
+            .add_item(&shirts, &red)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .must_build();
+
+        assert_eq!(
+            Ok(Outfit::new(vec![jeans.clone(), blue.clone()])),
+            closet.complete_outfit_preferred(vec![], &BTreeMap::new())
+        );
+
+        let mut priorities = BTreeMap::new();
+        priorities.insert(red.clone(), 5);
+        priorities.insert(slacks.clone(), 1);
+
+        assert_eq!(
+            Ok(Outfit::new(vec![slacks.clone(), red.clone()])),
+            closet.complete_outfit_preferred(vec![], &priorities)
+        );
+        assert_eq!(
+            Ok(Outfit::new(vec![jeans.clone(), red.clone()])),
+            closet.complete_outfit_preferred(vec![jeans.clone()], &priorities)
+        );
+    }
+
+    #[test]
+    fn priorities_respect_rules() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &blue)
+            .add_item(&shirts, &red)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .add_exclusion_rule(&red, &slacks)
+            .must_build();
+
+        let mut priorities = BTreeMap::new();
+        priorities.insert(red.clone(), 2);
+        priorities.insert(slacks.clone(), 3);
+
+        assert_eq!(
+            Ok(Outfit::new(vec![slacks.clone(), blue.clone()])),
+            closet.complete_outfit_preferred(vec![], &priorities)
+        );
+
+        let hat = Item::new("hats:fedora");
+        assert_eq!(
+            Err(UnknownItems(vec![hat.clone()])),
+            closet.complete_outfit_preferred(vec![hat], &priorities)
+        );
+    }
+}
+
+#[cfg(test)]
+mod by_family_tests {
+    use bowtie_core::bdd::ClosetBuilder;
+    use bowtie_core::core::CardinalitySpec;
+    use bowtie_core::core::Family;
+    use bowtie_core::core::Item;
+    use bowtie_core::core::OutfitError::MultipleItemsPerFamily;
+    use bowtie_core::core::OutfitError::UnknownItems;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn completed_items_are_keyed_by_family() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &blue)
+            .add_item(&shirts, &red)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .add_exclusion_rule(&red, &jeans)
+            .must_build();
+
+        let mut expected = BTreeMap::new();
+        expected.insert(shirts.clone(), red.clone());
+        expected.insert(pants.clone(), slacks.clone());
+
+        assert_eq!(Ok(expected), closet.complete_outfit_by_family(vec![red]));
+
+        let hat = Item::new("hats:fedora");
+        assert_eq!(
+            Err(UnknownItems(vec![hat.clone()])),
+            closet.complete_outfit_by_family(vec![hat])
+        );
+    }
+
+    #[test]
+    fn families_of_several_items_list_every_chosen_item() {
+        let blue = Item::new("shirts:blue");
+
+        let belt = Item::new("accessories:belt");
+        let scarf = Item::new("accessories:scarf");
+        let watch = Item::new("accessories:watch");
+
+        let shirts = Family::new("shirts");
+        let accessories = Family::new("accessories");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &blue)
+            .add_items(&accessories, &[belt.clone(), scarf.clone(), watch.clone()])
+            .set_family_cardinality(&accessories, CardinalitySpec::Exactly(2))
+            .add_exclusion_rule(&blue, &scarf)
+            .must_build();
+
+        let mut expected = BTreeMap::new();
+        expected.insert(shirts.clone(), vec![blue.clone()]);
+        expected.insert(accessories.clone(), vec![belt.clone(), watch.clone()]);
+
+        assert_eq!(Ok(expected), closet.complete_outfit_grouped(vec![blue.clone()]));
+
+        let mut several = BTreeMap::new();
+        several.insert(accessories.clone(), vec![belt.clone(), watch.clone()]);
+
+        assert_eq!(Err(MultipleItemsPerFamily(several)), closet.complete_outfit_by_family(vec![blue]));
+    }
+}
+
+#[cfg(test)]
+mod ref_tests {
+    use bowtie_core::bdd::ClosetBuilder;
+    use bowtie_core::core::Family;
+    use bowtie_core::core::Item;
+    use bowtie_core::core::Outfit;
+    use bowtie_core::core::OutfitError::IncompatibleSelections;
+
+    #[test]
+    fn borrowed_selections_can_be_completed_repeatedly() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&shirts, &blue)
+            .add_item(&shirts, &red)
+            .add_item(&pants, &jeans)
+            .add_item(&pants, &slacks)
+            .add_exclusion_rule(&red, &jeans)
+            .must_build();
+
+        let selections = [red.clone()];
+        let expected = Ok(Outfit::new(vec![slacks.clone(), red.clone()]));
+
+        assert_eq!(expected, closet.complete_outfit_ref(&selections));
+        assert_eq!(expected, closet.complete_outfit_ref(&selections));
+        assert_eq!(closet.complete_outfit(selections.to_vec()), closet.complete_outfit_ref(&selections));
+
+        let conflicting = [red.clone(), jeans.clone()];
+        assert_eq!(
+            Err(IncompatibleSelections(vec![jeans, red])),
+            closet.complete_outfit_ref(&conflicting)
+        );
+    }
+}
+
+#[cfg(test)]
+mod integer_id_tests {
+    use bowtie_core::bdd::ClosetBuilder;
+    use bowtie_core::core::Family;
+    use bowtie_core::core::Item;
+
+    #[test]
+    fn closet_with_integer_keyed_items() {
+        let shirts = Family::from_id(1);
+        let pants = Family::from_id(2);
+
+        let closet = ClosetBuilder::new()
+            .add_items(&shirts, &[Item::from_id(10), Item::from_id(11)])
+            .add_items(&pants, &[Item::from_id(20), Item::from_id(21)])
+            .add_exclusion_rule(&Item::from_id(10), &Item::from_id(20))
+            .must_build();
+
+        let outfit = closet.complete_outfit(vec![Item::from_id(10)]).unwrap();
+        let ids = outfit.items().iter()
+            .filter_map(|item| item.id())
+            .collect::<Vec<u32>>();
+
+        assert_eq!(vec![10, 21], ids);
+        assert_eq!(Some(&shirts), closet.get_family(&Item::from_id(11)));
+        assert_eq!(Some(2), closet.get_family(&Item::from_id(21)).and_then(|family| family.id()));
+    }
+}
+
+#[cfg(test)]
+mod selection_order_tests {
+    use bowtie_core::bdd::ClosetBuilder;
+    use bowtie_core::core::Family;
+    use bowtie_core::core::Item;
+    use bowtie_core::core::Outfit;
+
+    #[test]
+    fn outfit_does_not_depend_on_selection_order() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let sneakers = Item::new("shoes:sneakers");
+        let boots = Item::new("shoes:boots");
+
+        let hat = Item::new("hats:fedora");
+        let cap = Item::new("hats:cap");
+
+        let shirts = Family::new("shirts");
+        let pants = Family::new("pants");
+        let shoes = Family::new("shoes");
+        let hats = Family::new("hats");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&shirts, &[blue.clone(), red.clone()])
+            .add_items(&pants, &[jeans.clone(), slacks.clone()])
+            .add_items(&shoes, &[sneakers.clone(), boots.clone()])
+            .add_items(&hats, &[hat.clone(), cap.clone()])
+            .add_exclusion_rule(&red, &jeans)
+            .add_inclusion_rule(&boots, &hat)
+            .must_build();
+
+        let orders = vec![
+            vec![red.clone(), boots.clone()],
+            vec![boots.clone(), red.clone()],
+        ];
+        for selections in orders {
+            assert_eq!(
+                Ok(Outfit::new(vec![hat.clone(), slacks.clone(), red.clone(), boots.clone()])),
+                closet.complete_outfit(selections)
+            );
+        }
+
+        let orders = vec![
+            vec![slacks.clone(), sneakers.clone(), blue.clone()],
+            vec![blue.clone(), slacks.clone(), sneakers.clone()],
+            vec![sneakers.clone(), blue.clone(), slacks.clone()],
+        ];
+        for selections in orders {
+            assert_eq!(
+                Ok(Outfit::new(vec![cap.clone(), slacks.clone(), blue.clone(), sneakers.clone()])),
+                closet.complete_outfit(selections)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod empty_closet_tests {
+    use bowtie_core::bdd::Closet;
+    use bowtie_core::bdd::ClosetBuilder;
+    use bowtie_core::bdd::Node;
+    use bowtie_core::core::Item;
+    use bowtie_core::core::Outfit;
+    use bowtie_core::core::OutfitError::UnknownItems;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn empty_closet_without_selections_completes_empty_outfit() {
+        let built = ClosetBuilder::new().must_build();
+        let constructed = Closet::new(BTreeMap::new(), Node::TRUE_LEAF);
+
+        for closet in [built, constructed] {
+            assert_eq!(Ok(Outfit::new(vec![])), closet.complete_outfit(vec![]));
+            assert_eq!(Ok(Outfit::new(vec![])), closet.complete_outfit_ref(&[]));
+        }
+    }
+
+    #[test]
+    fn empty_closet_rejects_selections_as_unknown() {
+        let blue = Item::new("shirts:blue");
+        let jeans = Item::new("pants:jeans");
+
+        let built = ClosetBuilder::new().must_build();
+        let constructed = Closet::new(BTreeMap::new(), Node::TRUE_LEAF);
+
+        for closet in [built, constructed] {
+            assert_eq!(
+                Err(UnknownItems(vec![blue.clone()])),
+                closet.complete_outfit(vec![blue.clone()])
+            );
+            assert_eq!(
+                Err(UnknownItems(vec![blue.clone(), jeans.clone()])),
+                closet.complete_outfit(vec![blue.clone(), jeans.clone()])
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod excluding_tests {
+    use bowtie_core::bdd::ClosetBuilder;
+    use bowtie_core::core::Family;
+    use bowtie_core::core::Item;
+    use bowtie_core::core::Outfit;
+    use bowtie_core::core::OutfitError::NoValidCompletion;
+    use bowtie_core::core::OutfitError::UnknownItems;
+
+    #[test]
+    fn forbidden_item_is_replaced_by_sibling() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&Family::new("shirts"), &[blue.clone(), red.clone()])
+            .add_items(&Family::new("pants"), &[jeans.clone(), slacks.clone()])
+            .must_build();
+
+        assert_eq!(
+            Ok(Outfit::new(vec![jeans.clone(), blue.clone()])),
+            closet.complete_outfit(vec![])
+        );
+        assert_eq!(
+            Ok(Outfit::new(vec![jeans.clone(), red.clone()])),
+            closet.complete_outfit_excluding(vec![], vec![blue.clone()])
+        );
+        assert_eq!(
+            Ok(Outfit::new(vec![slacks.clone(), red.clone()])),
+            closet.complete_outfit_excluding(vec![slacks], vec![blue])
+        );
+    }
+
+    #[test]
+    fn forbidding_every_item_of_family_fails() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&Family::new("shirts"), &[blue.clone(), red.clone()])
+            .add_items(&Family::new("pants"), &[jeans.clone(), slacks.clone()])
+            .add_exclusion_rule(&red, &slacks)
+            .must_build();
+
+        assert_eq!(
+            Err(NoValidCompletion(vec![blue.clone(), red.clone()])),
+            closet.complete_outfit_excluding(vec![], vec![red.clone(), blue.clone()])
+        );
+        assert_eq!(
+            Err(NoValidCompletion(vec![blue.clone()])),
+            closet.complete_outfit_excluding(vec![slacks.clone()], vec![blue.clone()])
+        );
+        assert_eq!(
+            Err(NoValidCompletion(vec![jeans.clone()])),
+            closet.complete_outfit_excluding(vec![jeans.clone()], vec![jeans])
+        );
+    }
+
+    #[test]
+    fn unknown_forbidden_items_are_rejected() {
+        let blue = Item::new("shirts:blue");
+        let hat = Item::new("hats:fedora");
+
+        let closet = ClosetBuilder::new()
+            .add_item(&Family::new("shirts"), &blue)
+            .must_build();
+
+        assert_eq!(
+            Err(UnknownItems(vec![hat.clone()])),
+            closet.complete_outfit_excluding(vec![], vec![hat])
+        );
+    }
+}
+
+#[cfg(test)]
+mod meta_tests {
+    use bowtie_core::bdd::ClosetBuilder;
+    use bowtie_core::core::Family;
+    use bowtie_core::core::Item;
+    use bowtie_core::core::Outfit;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256StarStar;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq)]
+    struct Listing {
+        price: u32,
+        color: &'static str,
+    }
+
+    #[test]
+    fn completed_items_carry_meta() {
+        let blue = Item::with_meta("shirts:blue", Listing { price: 25, color: "#0000ff" });
+        let red = Item::with_meta("shirts:red", Listing { price: 30, color: "#ff0000" });
+
+        let jeans = Item::with_meta("pants:jeans", Listing { price: 60, color: "#1560bd" });
+        let slacks = Item::new("pants:slacks");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&Family::new("shirts"), &[blue, red])
+            .add_items(&Family::new("pants"), &[jeans, slacks])
+            .add_exclusion_rule(&Item::new("shirts:red"), &Item::new("pants:jeans"))
+            .must_build();
+
+        let outfit = closet.complete_outfit(vec![Item::new("shirts:red")]).unwrap();
+        let prices = outfit.items().iter()
+            .map(|item| item.meta::<Listing>().map(|listing| listing.price))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![None, Some(30)], prices);
+
+        let outfit = closet.complete_outfit(vec![]).unwrap();
+        let listings = outfit.items().iter()
+            .map(|item| item.meta::<Listing>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                Some(&Listing { price: 60, color: "#1560bd" }),
+                Some(&Listing { price: 25, color: "#0000ff" }),
+            ],
+            listings
+        );
+    }
+
+    fn priced_closet(base: u32) -> ClosetBuilder {
+        let priced = |id: &str, offset: u32| Item::with_meta(id, Listing { price: base + offset, color: "#000000" });
+
+        ClosetBuilder::new()
+            .add_items(&Family::new("shirts"), &[priced("shirts:blue", 1), priced("shirts:red", 2)])
+            .add_items(&Family::new("pants"), &[priced("pants:jeans", 3), priced("pants:slacks", 4)])
+    }
+
+    fn prices(outfit: &Outfit) -> Vec<u32> {
+        outfit.items().iter()
+            .map(|item| item.meta::<Listing>().expect("expected every item to carry a listing").price)
+            .collect()
+    }
+
+    #[test]
+    fn every_result_carries_meta_of_its_own_closet() {
+        let red = Item::new("shirts:red");
+        let jeans = Item::new("pants:jeans");
+
+        let cheap = priced_closet(10)
+            .add_soft_exclusion(&red, &jeans, 5)
+            .must_build();
+        let dear = priced_closet(100)
+            .add_exclusion_rule(&red, &jeans)
+            .must_build();
+
+        for outfit in cheap.completions(vec![]).unwrap() {
+            assert!(prices(&outfit).iter().all(|price| *price < 100), "{:?}", outfit);
+        }
+        for outfit in cheap.sample_outfits(4, &mut Xoshiro256StarStar::seed_from_u64(7)) {
+            assert!(prices(&outfit).iter().all(|price| *price < 100), "{:?}", outfit);
+        }
+        let weighted = cheap.weighted_sample(&BTreeMap::new(), &mut Xoshiro256StarStar::seed_from_u64(7)).unwrap();
+        assert!(prices(&weighted).iter().all(|price| *price < 100));
+
+        assert_eq!(vec![14, 12], prices(&cheap.complete_outfit_soft(vec![red.clone()]).unwrap()));
+
+        let (_, removed) = cheap.diff(&dear).unwrap();
+        assert_eq!(vec![vec![13, 12]], removed.iter().map(prices).collect::<Vec<_>>());
+        let (added, _) = dear.diff(&cheap).unwrap();
+        assert_eq!(vec![vec![13, 12]], added.iter().map(prices).collect::<Vec<_>>());
+
+        let neighbors = dear.neighbors(&Outfit::new(vec![Item::new("pants:slacks"), Item::new("shirts:blue")]));
+        assert_eq!(vec![vec![103, 101], vec![104, 102]], neighbors.iter().map(prices).collect::<Vec<_>>());
+    }
+}
+
+#[cfg(test)]
+mod duplicate_selection_tests {
+    use bowtie_core::bdd::ClosetBuilder;
+    use bowtie_core::core::Family;
+    use bowtie_core::core::Item;
+    use bowtie_core::core::Outfit;
+    use bowtie_core::core::OutfitError::DuplicateSelection;
+
+    #[test]
+    fn same_item_selected_twice_is_reported_as_duplicate() {
+        let blue = Item::new("shirts:blue");
+        let red = Item::new("shirts:red");
+
+        let jeans = Item::new("pants:jeans");
+        let slacks = Item::new("pants:slacks");
+
+        let closet = ClosetBuilder::new()
+            .add_items(&Family::new("shirts"), &[blue.clone(), red.clone()])
+            .add_items(&Family::new("pants"), &[jeans.clone(), slacks.clone()])
+            .must_build();
+
+        assert_eq!(
+            Err(DuplicateSelection(blue.clone())),
+            closet.complete_outfit(vec![blue.clone(), blue.clone()])
+        );
+        assert_eq!(
+            Err(DuplicateSelection(jeans.clone())),
+            closet.complete_outfit(vec![jeans.clone(), blue.clone(), jeans.clone()])
+        );
+        assert_eq!(
+            Err(vec![DuplicateSelection(blue.clone())]),
+            closet.complete_outfit_validated(vec![blue.clone(), blue.clone()])
+        );
+        assert_eq!(
+            Ok(Outfit::new(vec![jeans, blue.clone()])),
+            closet.complete_outfit(vec![blue])
+        );
+    }
+}
+
+#[cfg(test)]
+mod tie_break_tests {
+    use bowtie_core::bdd::Closet;
+    use bowtie_core::bdd::ClosetBuilder;
+    use bowtie_core::core::CardinalitySpec;
+    use bowtie_core::core::Family;
+    use bowtie_core::core::Item;
+    use bowtie_core::core::Outfit;
+    use bowtie_core::core::TieBreak;
+
+    fn closet() -> Closet {
+        ClosetBuilder::new()
+            .add_items(&Family::new("shirts"), &[Item::new("shirts:blue"), Item::new("shirts:green"), Item::new("shirts:red")])
+            .add_items(&Family::new("pants"), &[Item::new("pants:jeans"), Item::new("pants:slacks")])
+            .add_exclusion_rule(&Item::new("shirts:green"), &Item::new("pants:jeans"))
+            .must_build()
+    }
+
+    #[test]
+    fn high_branch_matches_complete_outfit() {
+        let closet = closet();
+        let selections = vec![Item::new("shirts:green")];
+
+        assert_eq!(closet.complete_outfit(selections.clone()), closet.complete_outfit_with(selections, &TieBreak::HighBranch));
+    }
+
+    #[test]
+    fn low_branch_takes_last_item_of_each_family() {
+        assert_eq!(
+            Ok(Outfit::new(vec![Item::new("pants:slacks"), Item::new("shirts:red")])),
+            closet().complete_outfit_with(vec![], &TieBreak::LowBranch)
+        );
+    }
+
+    #[test]
+    fn lexicographic_takes_first_completion_in_sorted_order() {
+        let closet = closet();
+
+        for selections in [vec![], vec![Item::new("shirts:green")], vec![Item::new("pants:slacks")]] {
+            let first = closet.completions(selections.clone()).unwrap()
+                .min_by_key(|outfit| outfit.items().to_vec());
+
+            assert_eq!(Ok(first.unwrap()), closet.complete_outfit_with(selections, &TieBreak::Lexicographic));
+        }
+    }
+
+    #[test]
+    fn lexicographic_skips_items_excluded_by_selections() {
+        let closet = closet();
+
+        assert_eq!(
+            Ok(Outfit::new(vec![Item::new("pants:jeans"), Item::new("shirts:blue")])),
+            closet.complete_outfit_with(vec![], &TieBreak::Lexicographic)
+        );
+        assert_eq!(
+            Ok(Outfit::new(vec![Item::new("pants:slacks"), Item::new("shirts:green")])),
+            closet.complete_outfit_with(vec![Item::new("shirts:green")], &TieBreak::Lexicographic)
+        );
+    }
+
+    #[test]
+    fn lexicographic_leaves_out_optional_items_high_branch_takes() {
+        let watches = Family::new("watches");
+        let closet = ClosetBuilder::new()
+            .add_items(&Family::new("shirts"), &[Item::new("shirts:blue"), Item::new("shirts:red")])
+            .add_items(&watches, &[Item::new("watches:gold"), Item::new("watches:steel")])
+            .set_family_cardinality(&watches, CardinalitySpec::AtMostOne)
+            .must_build();
+
+        assert_eq!(
+            Ok(Outfit::new(vec![Item::new("shirts:blue"), Item::new("watches:gold")])),
+            closet.complete_outfit_with(vec![], &TieBreak::HighBranch)
+        );
+        assert_eq!(
+            Ok(Outfit::new(vec![Item::new("shirts:blue")])),
+            closet.complete_outfit_with(vec![], &TieBreak::Lexicographic)
+        );
+        assert_eq!(
+            Ok(Outfit::new(vec![Item::new("shirts:blue"), Item::new("watches:steel")])),
+            closet.complete_outfit_with(vec![Item::new("watches:steel")], &TieBreak::Lexicographic)
+        );
+    }
+
+    #[test]
+    fn custom_comparison_picks_first_item_it_orders() {
+        let reversed = TieBreak::Custom(Box::new(|item, other| other.cmp(item)));
+
+        assert_eq!(
+            Ok(Outfit::new(vec![Item::new("pants:slacks"), Item::new("shirts:red")])),
+            closet().complete_outfit_with(vec![], &reversed)
+        );
+    }
+}
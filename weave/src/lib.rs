@@ -7,6 +7,7 @@ extern crate lazy_static;
 extern crate maplit;
 #[macro_use]
 extern crate nom;
+#[cfg(feature = "parallel")]
 extern crate rayon;
 extern crate serde;
 #[macro_use]
@@ -21,6 +22,7 @@ pub use zdd::*;
 
 mod types;
 mod status;
+mod sets;
 pub mod zdd;
 pub mod zdd2;
 pub mod matrix;
@@ -1,6 +1,6 @@
 use std::hash::Hash;
 
-use rayon::iter::ParallelIterator;
+use sets;
 
 use super::Forest;
 
@@ -17,7 +17,7 @@ pub fn intersect<T: Hash + Eq + Clone + Ord + Sync + Send>(forest1: Forest<T>, f
         (Forest::Unit(set), Forest::Many(matrix)) if matrix.contains(set) => forest1.clone(),
 
         (Forest::Many(matrix1), Forest::Many(matrix2)) =>
-            Forest::many(&matrix1.par_intersection(matrix2).cloned().collect::<Vec<_>>()),
+            Forest::many(&sets::intersection(matrix1, matrix2)),
 
         (_, _) => Forest::empty()
     }
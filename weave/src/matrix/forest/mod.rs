@@ -142,6 +142,10 @@ impl<T: Hash + Eq + Clone + Ord + Sync + Send> Forest<T> {
     pub fn subset_none(self, elements: &[T]) -> Self {
         subset::subset_none(self, elements)
     }
+
+    pub fn subset_any(self, elements: &[T]) -> Self {
+        subset::subset_any(self, elements)
+    }
 }
 
 #[cfg(test)]
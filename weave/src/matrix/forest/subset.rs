@@ -80,6 +80,26 @@ pub fn subset_none<T: Hash + Eq + Clone + Ord + Sync + Send>(forest: Forest<T>,
     }
 }
 
+pub fn subset_any<T: Hash + Eq + Clone + Ord + Sync + Send>(forest: Forest<T>, elements: &[T]) -> Forest<T> {
+    match &forest {
+        Forest::Unit(set) =>
+            if elements.iter().any(|e| set.contains(e)) {
+                forest.clone()
+            } else {
+                Forest::empty()
+            },
+        Forest::Many(matrix) => {
+            let forest: Vec<Vec<T>> = matrix.iter()
+                .filter(|set| elements.iter().any(|e| set.contains(e)))
+                .cloned()
+                .collect();
+
+            Forest::many(&forest)
+        }
+        _ => Forest::empty()
+    }
+}
+
 #[cfg(test)]
 mod subset_tests {
     use super::Forest;
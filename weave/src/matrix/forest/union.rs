@@ -1,6 +1,6 @@
 use std::hash::Hash;
 
-use rayon::iter::ParallelIterator;
+use sets;
 
 use super::Forest;
 
@@ -33,14 +33,14 @@ pub fn union<T: Hash + Eq + Clone + Ord + Sync + Send>(forest1: Forest<T>, fores
             Forest::many(&matrix)
         }
 
-        (Forest::Many(matrix1), Forest::Many(matrix2)) if matrix1.par_is_subset(matrix2) =>
+        (Forest::Many(matrix1), Forest::Many(matrix2)) if sets::is_subset(matrix1, matrix2) =>
             forest2.clone(),
 
-        (Forest::Many(matrix1), Forest::Many(matrix2)) if matrix1.par_is_superset(matrix2) =>
+        (Forest::Many(matrix1), Forest::Many(matrix2)) if sets::is_superset(matrix1, matrix2) =>
             forest1.clone(),
 
         (Forest::Many(matrix1), Forest::Many(matrix2)) =>
-            Forest::many(&matrix1.par_union(matrix2).cloned().collect::<Vec<_>>()),
+            Forest::many(&sets::union(matrix1, matrix2)),
     }
 }
 
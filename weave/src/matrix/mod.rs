@@ -68,6 +68,10 @@ impl<T: Hash + Eq + Clone + Ord + Sync + Send> types::Forest<T> for Forest<T> {
     fn subset_none(self, elements: &[T]) -> Self {
         Forest::subset_none(self, elements)
     }
+
+    fn subset_any(self, elements: &[T]) -> Self {
+        Forest::subset_any(self, elements)
+    }
 }
 
 impl<T: Hash + Eq + Clone + Ord + Sync + Send> types::Tree<T> for Tree<T> {
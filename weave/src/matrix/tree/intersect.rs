@@ -1,6 +1,6 @@
 use std::hash::Hash;
 
-use rayon::iter::ParallelIterator;
+use sets;
 
 use super::Tree;
 
@@ -19,14 +19,14 @@ pub fn intersect<T: Hash + Clone + Ord + Sync + Send>(tree1: Tree<T>, tree2: Tre
         (Tree::One(element), Tree::Many(set)) if set.contains(element) =>
             tree1.clone(),
 
-        (Tree::Many(set1), Tree::Many(set2)) if set1.par_is_subset(set2) =>
+        (Tree::Many(set1), Tree::Many(set2)) if sets::is_subset(set1, set2) =>
             tree1.clone(),
 
-        (Tree::Many(set1), Tree::Many(set2)) if set1.par_is_superset(set2) =>
+        (Tree::Many(set1), Tree::Many(set2)) if sets::is_superset(set1, set2) =>
             tree2.clone(),
 
         (Tree::Many(set1), Tree::Many(set2)) =>
-            Tree::many(&set1.par_intersection(set2).cloned().collect::<Vec<_>>()),
+            Tree::many(&sets::intersection(set1, set2)),
 
         (_, _) => Tree::Empty,
     }
@@ -0,0 +1,45 @@
+use std::hash::Hash;
+
+use hashbrown::HashSet;
+#[cfg(feature = "parallel")]
+use rayon::iter::ParallelIterator;
+
+#[cfg(feature = "parallel")]
+pub fn is_subset<T: Hash + Eq + Sync>(set1: &HashSet<T>, set2: &HashSet<T>) -> bool {
+    set1.par_is_subset(set2)
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn is_subset<T: Hash + Eq>(set1: &HashSet<T>, set2: &HashSet<T>) -> bool {
+    set1.is_subset(set2)
+}
+
+#[cfg(feature = "parallel")]
+pub fn is_superset<T: Hash + Eq + Sync>(set1: &HashSet<T>, set2: &HashSet<T>) -> bool {
+    set1.par_is_superset(set2)
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn is_superset<T: Hash + Eq>(set1: &HashSet<T>, set2: &HashSet<T>) -> bool {
+    set1.is_superset(set2)
+}
+
+#[cfg(feature = "parallel")]
+pub fn intersection<T: Hash + Eq + Clone + Sync + Send>(set1: &HashSet<T>, set2: &HashSet<T>) -> Vec<T> {
+    set1.par_intersection(set2).cloned().collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn intersection<T: Hash + Eq + Clone>(set1: &HashSet<T>, set2: &HashSet<T>) -> Vec<T> {
+    set1.intersection(set2).cloned().collect()
+}
+
+#[cfg(feature = "parallel")]
+pub fn union<T: Hash + Eq + Clone + Sync + Send>(set1: &HashSet<T>, set2: &HashSet<T>) -> Vec<T> {
+    set1.par_union(set2).cloned().collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn union<T: Hash + Eq + Clone>(set1: &HashSet<T>, set2: &HashSet<T>) -> Vec<T> {
+    set1.union(set2).cloned().collect()
+}
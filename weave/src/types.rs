@@ -20,6 +20,7 @@ pub trait Forest<T: Clone> {
     fn subset_not(self, element: T) -> Self;
     fn subset_all(self, elements: &[T]) -> Self;
     fn subset_none(self, elements: &[T]) -> Self;
+    fn subset_any(self, elements: &[T]) -> Self;
 }
 
 /// Tree is an immutable set of elements
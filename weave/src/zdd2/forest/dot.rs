@@ -0,0 +1,86 @@
+use std::fmt;
+use std::hash::Hash;
+
+use hashbrown::HashMap;
+
+use super::Forest;
+use super::node::Node;
+use super::node::NodeId;
+
+impl<T: Hash + Eq + Clone + Ord + fmt::Display> Forest<T> {
+    pub fn to_dot(&self) -> String {
+        let mut labels: HashMap<NodeId, usize> = HashMap::new();
+        let mut nodes = vec![];
+        let mut edges = vec![];
+
+        let mut queue = vec![self.root];
+        while let Some(node_id) = queue.pop() {
+            if labels.contains_key(&node_id) {
+                continue;
+            }
+            let label = labels.len();
+            labels.insert(node_id, label);
+
+            match Node::from(node_id) {
+                Node::Branch(id, low, high) => {
+                    let item = self.universe.get_item(id)
+                        .map(|item| item.to_string().replace('"', "\\\""))
+                        .unwrap_or_default();
+                    nodes.push((label, format!("[label=\"{}\"]", item)));
+                    edges.push((node_id, low, true));
+                    edges.push((node_id, high, false));
+
+                    queue.push(high);
+                    queue.push(low);
+                }
+                Node::Always => nodes.push((label, String::from("[label=\"Always\", shape=box]"))),
+                Node::Never => nodes.push((label, String::from("[label=\"Never\", shape=box]"))),
+            }
+        }
+
+        let nodes = nodes.into_iter()
+            .map(|(label, attributes)| format!("    n{} {};", label, attributes))
+            .collect::<Vec<_>>();
+        let edges = edges.into_iter()
+            .map(|(from, to, is_low)| {
+                let style = if is_low { " [style=dashed]" } else { "" };
+                format!("    n{} -> n{}{};", labels[&from], labels[&to], style)
+            })
+            .collect::<Vec<_>>();
+
+        format!("digraph {{\n{}\n{}\n}}\n", nodes.join("\n"), edges.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Forest;
+
+    #[test]
+    fn empty_forest_is_a_single_terminal() {
+        let forest = Forest::<&str>::empty();
+
+        let dot = forest.to_dot();
+
+        assert!(dot.contains("[label=\"Never\", shape=box]"));
+        assert_eq!(1, dot.matches("[label=").count());
+        assert_eq!(0, dot.matches("->").count());
+    }
+
+    #[test]
+    fn union_forest_shares_terminals() {
+        let forest = Forest::unit(&["1", "2"])
+            .union(Forest::unit(&["2", "3"]));
+
+        let dot = forest.to_dot();
+
+        assert!(dot.starts_with("digraph {"));
+        assert_eq!(5, dot.matches("[label=").count());
+        assert_eq!(2, dot.matches("shape=box").count());
+        assert_eq!(6, dot.matches("->").count());
+        assert_eq!(3, dot.matches("style=dashed").count());
+        assert!(dot.contains("[label=\"1\"]"));
+        assert!(dot.contains("[label=\"2\"]"));
+        assert!(dot.contains("[label=\"3\"]"));
+    }
+}
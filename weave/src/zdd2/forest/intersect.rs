@@ -161,4 +161,44 @@ mod tests {
             Forest::intersect(tree1, tree2)
         );
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod intersect_counted_tests {
+    use super::super::Forest;
+
+    #[test]
+    fn dropped_count_is_trees_missing_from_intersection() {
+        let forest1 = Forest::many(&[
+            vec!["1", "2"],
+            vec!["2", "3"],
+            vec!["3", "4"],
+            vec!["4", "5"],
+        ]);
+        let forest2 = Forest::many(&[
+            vec!["2", "3"],
+            vec!["4", "5"],
+            vec!["5", "6"],
+        ]);
+        let len = forest1.len();
+
+        let (intersection, dropped) = forest1.clone().intersect_counted(forest2.clone());
+
+        assert_eq!(Forest::intersect(forest1, forest2), intersection);
+        assert_eq!(2, dropped);
+        assert_eq!(len - intersection.len(), dropped);
+    }
+
+    #[test]
+    fn intersect_with_empty_drops_every_tree() {
+        let forest = Forest::many(&[
+            vec!["1", "2"],
+            vec!["2", "3"],
+        ]);
+
+        let (intersection, dropped) = forest.intersect_counted(Forest::empty());
+
+        assert_eq!(Forest::empty(), intersection);
+        assert_eq!(2, dropped);
+    }
+}
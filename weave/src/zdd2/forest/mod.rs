@@ -1,17 +1,23 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::fmt;
 use std::hash::Hash;
+use std::mem;
 
 use itertools::Itertools;
 
-use self::node::Node;
-use self::node::NodeId;
-use self::node::Priority;
+pub use self::node::CacheStats;
+pub use self::node::Node;
+pub use self::node::NodeId;
+pub use self::node::Priority;
 use self::universe::Universe;
 
 #[macro_use]
 mod node;
+mod dot;
 mod universe;
 mod trees;
+mod size;
 
 #[cfg(test)]
 mod union;
@@ -22,7 +28,9 @@ mod subset;
 mod product;
 
 /// Forest is an immutable set of sets
-#[derive(Clone, Eq, PartialEq, Serialize, Deserialize)]
+///
+/// Every operation rebuilds its result canonically, so forests holding the same trees compare and hash equal
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct Forest<T: Hash + Eq + Clone + Ord> {
     root: NodeId,
     universe: Universe<T>,
@@ -61,6 +69,23 @@ impl<T: Hash + Eq + Clone + Ord + Sync + Send> Forest<T> {
         Forest { root, universe }
     }
 
+    /// The forest containing only the empty set, the identity for `product`
+    pub fn always() -> Self {
+        let universe = Universe::default();
+        let root = Node::ALWAYS;
+
+        Forest { root, universe }
+    }
+
+    /// The forest containing exactly `set`, which may be the empty set
+    pub fn single(set: Vec<T>) -> Self {
+        if set.is_empty() {
+            return Self::always();
+        }
+
+        Self::unit(&set)
+    }
+
     pub fn unit(items: &[T]) -> Self {
         let universe = Universe::from_items(items);
         let root = universe.get_priorities::<Node>(items);
@@ -78,6 +103,12 @@ impl<T: Hash + Eq + Clone + Ord + Sync + Send> Forest<T> {
         Forest { root: root.into(), universe }
     }
 
+    pub fn from_sets<I: IntoIterator<Item=Vec<T>>>(sets: I) -> Self {
+        let matrix = sets.into_iter().collect::<Vec<_>>();
+
+        Self::from_trees(&matrix)
+    }
+
     pub fn unique(set: &[T]) -> Self {
         let universe = Universe::from_items(set);
 
@@ -88,13 +119,53 @@ impl<T: Hash + Eq + Clone + Ord + Sync + Send> Forest<T> {
         Forest { root: root.into(), universe }
     }
 
+    /// Moves the diagram into the universe its own trees would produce, so equal forests end up with equal roots
     fn canonical(root: impl Into<NodeId>, universe: Universe<T>) -> Self {
-        let trees = trees::trees(root.into())
+        let root = root.into();
+
+        let occurrences = size::occurrences(root)
             .into_iter()
-            .map(|set| universe.get_items::<Vec<_>>(&set))
-            .collect::<Vec<_>>();
+            .filter_map(|(id, count)| universe.get_item(id).map(|item| (item.clone(), count)))
+            .collect();
+        let canonical = Universe::from_occurrences(occurrences);
+
+        let root = translate_root(&universe, &canonical, root.into());
+
+        Forest { root: root.into(), universe: canonical }
+    }
+
+    fn from_trees(trees: &[Vec<T>]) -> Self {
+        let forest = Self::many(trees);
+        if trees.iter().any(|tree| tree.is_empty()) {
+            let root = Node::union(forest.root.into(), Node::Always);
+            return Forest { root: root.into(), universe: forest.universe };
+        }
+        forest
+    }
 
-        Self::many(&trees)
+    /// The root of the underlying diagram, resolved to a `Node` with `Node::from`
+    ///
+    /// ```
+    /// use weave::zdd2::{Forest, Node};
+    ///
+    /// let forest = Forest::unit(&["a"]);
+    ///
+    /// match Node::from(forest.root()) {
+    ///     Node::Branch(priority, low, high) => {
+    ///         assert_eq!(Some(&"a"), forest.item(priority));
+    ///         assert_eq!(Node::Never, Node::from(low));
+    ///         assert_eq!(Node::Always, Node::from(high));
+    ///     }
+    ///     _ => panic!("expected a branch"),
+    /// }
+    /// ```
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    /// The element a branch with the given priority tests for
+    pub fn item(&self, priority: Priority) -> Option<&T> {
+        self.universe.get_item(priority)
     }
 
     pub fn len(&self) -> usize {
@@ -105,6 +176,41 @@ impl<T: Hash + Eq + Clone + Ord + Sync + Send> Forest<T> {
         trees::trees(self.root).is_empty()
     }
 
+    /// The number of distinct nodes in the underlying diagram, `Always` and `Never` included
+    pub fn node_count(&self) -> usize {
+        size::node_count(self.root)
+    }
+
+    /// The number of trees of each size, computed from the diagram without enumerating the trees
+    pub fn size_histogram(&self) -> BTreeMap<usize, u64> {
+        size::size_histogram(self.root)
+    }
+
+    /// A rough estimate of the heap memory held by this forest, in bytes
+    ///
+    /// Counts the diagram's nodes and the universe's lookups, but not memory owned by the elements themselves
+    pub fn heap_bytes(&self) -> usize {
+        let nodes = self.node_count() * mem::size_of::<Node>();
+        let index = mem::size_of::<T>();
+        let priorities = mem::size_of::<(T, Priority)>();
+        let occurrences = mem::size_of::<(T, usize)>();
+        let universe = self.universe.len() * (index + priorities + occurrences);
+
+        nodes + universe
+    }
+
+    /// Hits, misses and entries of the memo tables behind `union` and `product`
+    ///
+    /// The tables are shared by every forest on every thread, rayon's workers included, so these are not specific to `self`
+    pub fn cache_stats(&self) -> CacheStats {
+        Node::cache_stats()
+    }
+
+    /// Empties the memo tables for every thread and resets their counts, trading later speed for memory
+    pub fn clear_caches(&self) {
+        Node::clear_caches()
+    }
+
     pub fn trees(&self) -> Vec<Vec<T>> {
         trees::trees(self.root)
             .into_iter()
@@ -112,6 +218,24 @@ impl<T: Hash + Eq + Clone + Ord + Sync + Send> Forest<T> {
             .collect()
     }
 
+    /// The trees of this forest in a defined order: each tree is sorted, then the trees are sorted lexicographically
+    pub fn sorted_sets(&self) -> Vec<Vec<T>> {
+        self.trees()
+            .into_iter()
+            .map(|tree| tree.into_iter().sorted().collect::<Vec<_>>())
+            .sorted()
+            .collect()
+    }
+
+    /// Every element the forest knows of, sorted, taken from its interning table rather than its trees
+    pub fn universe(&self) -> Vec<T> {
+        self.universe.items()
+            .iter()
+            .cloned()
+            .sorted()
+            .collect()
+    }
+
     pub fn occurrences(&self) -> Vec<(T, usize)> {
         self.universe.occurrences()
             .clone()
@@ -130,6 +254,15 @@ impl<T: Hash + Eq + Clone + Ord + Sync + Send> Forest<T> {
         Self::canonical(root, universe)
     }
 
+    /// Intersects the forests, also returning how many of `self`'s trees the intersection dropped
+    pub fn intersect_counted(self, other: Self) -> (Self, usize) {
+        let before = size::tree_count(self.root);
+        let forest = self.intersect(other);
+        let dropped = before - size::tree_count(forest.root);
+
+        (forest, dropped)
+    }
+
     pub fn union(self, other: Self) -> Self {
         let (universe, self_root, other_root) = translate_roots(
             (&self.universe, self.root.into()),
@@ -140,6 +273,9 @@ impl<T: Hash + Eq + Clone + Ord + Sync + Send> Forest<T> {
         Self::canonical(root, universe)
     }
 
+    /// Every union of one tree from each forest.
+    ///
+    /// The result is rebuilt canonically, so `product` is commutative and associative up to `==`
     pub fn product(self, other: Self) -> Self {
         let (universe, self_root, other_root) = translate_roots(
             (&self.universe, self.root.into()),
@@ -150,6 +286,54 @@ impl<T: Hash + Eq + Clone + Ord + Sync + Send> Forest<T> {
         Self::canonical(root, universe)
     }
 
+    /// Every subset of `universe` that is not a tree of this forest.
+    /// Trees holding elements outside of `universe` are not subsets of it, so they are ignored.
+    ///
+    /// The power set is built as a diagram with one node per element and the forest is subtracted from it,
+    /// so the subsets are never enumerated
+    pub fn complement(self, universe: &[T]) -> Self {
+        let elements = universe;
+        let universe = Universe::merge(&self.universe, &Universe::from_items(elements));
+        let self_root = translate_root(&self.universe, &universe, self.root.into());
+
+        let power_set = universe.get_priorities::<Vec<_>>(elements)
+            .into_iter()
+            .unique()
+            .sorted()
+            .rev()
+            .fold(Node::Always, |power_set, id| Node::branch(id, power_set, power_set));
+
+        let root = Node::difference(power_set, self_root);
+
+        Self::canonical(root, universe)
+    }
+
+    /// Keeps the trees for which `predicate` holds.
+    /// Every tree is enumerated and tested; the walk does not short-circuit.
+    pub fn filter<F: Fn(&[T]) -> bool>(&self, predicate: F) -> Self {
+        let trees = self.trees()
+            .into_iter()
+            .filter(|tree| predicate(tree))
+            .collect::<Vec<_>>();
+
+        Self::from_trees(&trees)
+    }
+
+    /// Replaces every element of every tree with the elements `f` expands it to.
+    /// Elements expanding to nothing are dropped; repeated elements and trees that end up equal are merged.
+    pub fn expand<U: Hash + Eq + Clone + Ord + Sync + Send, F: Fn(&T) -> Vec<U>>(&self, f: F) -> Forest<U> {
+        let trees = self.trees()
+            .into_iter()
+            .map(|tree| tree.iter()
+                .flat_map(&f)
+                .sorted()
+                .dedup()
+                .collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        Forest::from_trees(&trees)
+    }
+
     pub fn subset(self, element: T) -> Self {
         subset::subset(self, element)
     }
@@ -158,6 +342,12 @@ impl<T: Hash + Eq + Clone + Ord + Sync + Send> Forest<T> {
         subset::subset_not(self, element)
     }
 
+    /// Keeps the trees that don't contain `element`; the same as `subset_not`.
+    /// Together with `subset` on the same element, this partitions the forest.
+    pub fn without(self, element: T) -> Self {
+        subset::subset_not(self, element)
+    }
+
     pub fn subset_all(self, elements: &[T]) -> Self {
         subset::subset_many(
             self,
@@ -175,6 +365,12 @@ impl<T: Hash + Eq + Clone + Ord + Sync + Send> Forest<T> {
             Node::subset_none,
         )
     }
+
+    /// Keeps the trees containing at least one of `elements`.
+    /// Elements outside the universe match nothing, so no elements at all gives the empty forest.
+    pub fn subset_any(self, elements: &[T]) -> Self {
+        subset::subset_any(self, elements)
+    }
 }
 
 fn translate_roots<T: Hash + Eq + Clone + Ord>((self_universe, self_root): (&Universe<T>, Node), (other_universe, other_root): (&Universe<T>, Node)) -> (Universe<T>, Node, Node) {
@@ -187,22 +383,37 @@ fn translate_roots<T: Hash + Eq + Clone + Ord>((self_universe, self_root): (&Uni
 }
 
 fn translate_root<T: Hash + Eq + Clone + Ord>(old_universe: &Universe<T>, new_universe: &Universe<T>, root: Node) -> Node {
-    match root {
+    translate_root_inner(old_universe, new_universe, root, &mut HashMap::new())
+}
+
+fn translate_root_inner<T: Hash + Eq + Clone + Ord>(old_universe: &Universe<T>, new_universe: &Universe<T>, root: Node, cache: &mut HashMap<Node, Node>) -> Node {
+    if let Some(translated) = cache.get(&root) {
+        return *translated;
+    }
+
+    let translated = match root {
         Node::Branch(id, low, high) => {
-            let low = translate_root(old_universe, new_universe, low.into());
-            let high = translate_root(old_universe, new_universe, high.into());
+            let low = translate_root_inner(old_universe, new_universe, low.into(), cache);
+            let high = translate_root_inner(old_universe, new_universe, high.into(), cache);
 
             let item = old_universe.get_item(id).unwrap();
             let id = new_universe.get_priority(item).unwrap();
 
-            Node::branch(id, low, high)
+            // The new universe may rank items differently, so the children can sort before `id`;
+            // joining `high` with `{id}` keeps the diagram ordered instead of reordering in place
+            Node::union(low, Node::product(high, Node::branch(id, Node::Never, Node::Always)))
         }
         _ => root
-    }
+    };
+
+    cache.insert(root, translated);
+    translated
 }
 
 #[cfg(test)]
 mod eq_forest_tests {
+    use std::collections::HashMap;
+
     use super::Forest;
 
     #[test]
@@ -252,6 +463,42 @@ mod eq_forest_tests {
 
         assert_eq!(forest1, forest2);
     }
+
+    #[test]
+    fn union_is_commutative() {
+        let forest1: Forest<&str> = Forest::many(&[vec!["1", "2"], vec!["3"]]);
+        let forest2: Forest<&str> = Forest::many(&[vec!["2", "3"], vec!["4"]]);
+
+        assert_eq!(
+            forest1.clone().union(forest2.clone()),
+            forest2.union(forest1)
+        );
+    }
+
+    #[test]
+    fn differently_built_forests_are_equal() {
+        let forest1 = Forest::unique(&["1", "2"])
+            .product(Forest::unique(&["3", "4"]));
+        let forest2 = Forest::many(&[
+            vec!["2", "4"],
+            vec!["1", "3"],
+            vec!["2", "3"],
+            vec!["1", "4"],
+        ]);
+
+        assert_eq!(forest1, forest2);
+    }
+
+    #[test]
+    fn equal_forests_share_hash_map_key() {
+        let mut forests = HashMap::new();
+        forests.insert(Forest::many(&[vec!["1", "2"], vec!["3"]]), 1);
+        forests.insert(Forest::unit(&["3"]).union(Forest::unit(&["2", "1"])), 2);
+        forests.insert(Forest::unit(&["3"]), 3);
+
+        assert_eq!(2, forests.len());
+        assert_eq!(Some(&2), forests.get(&Forest::many(&[vec!["3"], vec!["1", "2"]])));
+    }
 }
 
 #[cfg(test)]
@@ -369,6 +616,416 @@ mod many_forest_tests {
     }
 }
 
+#[cfg(test)]
+mod always_forest_tests {
+    use super::Forest;
+
+    #[test]
+    fn always_forest_contains_empty_set() {
+        let forest: Forest<&str> = Forest::always();
+        let expected: Vec<Vec<&str>> = vec![vec![]];
+
+        assert_eq!(1, forest.len());
+        assert_eq!(expected, forest.trees());
+    }
+
+    #[test]
+    fn single_forest() {
+        assert_eq!(Forest::<&str>::always(), Forest::single(vec![]));
+        assert_eq!(Forest::unit(&["1", "2"]), Forest::single(vec!["2", "1"]));
+    }
+
+    #[test]
+    fn always_is_identity_for_product() {
+        let forest: Forest<&str> = Forest::many(&[
+            vec!["1", "2"],
+            vec!["2", "3"]
+        ]);
+
+        assert_eq!(forest.clone(), Forest::always().product(forest.clone()));
+        assert_eq!(forest.clone(), forest.clone().product(Forest::always()));
+    }
+
+    #[test]
+    fn empty_is_identity_for_union() {
+        let forest: Forest<&str> = Forest::many(&[
+            vec!["1", "2"],
+            vec!["2", "3"]
+        ]);
+
+        assert_eq!(forest.clone(), Forest::empty().union(forest.clone()));
+        assert_eq!(forest.clone(), forest.clone().union(Forest::empty()));
+    }
+
+    #[test]
+    fn union_with_always_keeps_empty_set() {
+        let forest = Forest::unit(&["1"]).union(Forest::always());
+        let expected: Vec<Vec<&str>> = vec![vec!["1"], vec![]];
+
+        assert_eq!(2, forest.len());
+        assert_eq!(expected, forest.trees());
+    }
+}
+
+#[cfg(test)]
+mod complement_forest_tests {
+    use super::Forest;
+
+    #[test]
+    fn complement_of_one_set_over_two_elements() {
+        let forest: Forest<&str> = Forest::unit(&["1"]);
+
+        let expected = Forest::from_sets(vec![
+            vec![],
+            vec!["2"],
+            vec!["1", "2"],
+        ]);
+
+        assert_eq!(expected, forest.complement(&["1", "2"]));
+    }
+
+    #[test]
+    fn complement_of_empty_forest_is_power_set() {
+        let forest: Forest<&str> = Forest::empty();
+
+        assert_eq!(8, forest.complement(&["1", "2", "3"]).len());
+    }
+
+    #[test]
+    fn complement_ignores_sets_outside_universe() {
+        let forest: Forest<&str> = Forest::many(&[
+            vec!["1"],
+            vec!["1", "3"],
+        ]);
+
+        let expected = Forest::from_sets(vec![
+            vec![],
+            vec!["2"],
+            vec!["1", "2"],
+        ]);
+
+        assert_eq!(expected, forest.complement(&["1", "2"]));
+    }
+
+    #[test]
+    fn complement_of_power_set_is_empty() {
+        let forest: Forest<&str> = Forest::from_sets(vec![
+            vec![],
+            vec!["1"],
+        ]);
+
+        assert_eq!(Forest::empty(), forest.complement(&["1"]));
+    }
+
+    #[test]
+    fn complement_of_large_universe_is_not_enumerated() {
+        let universe = (0..40).collect::<Vec<u32>>();
+        let forest = Forest::unit(&universe);
+
+        let complement = forest.complement(&universe);
+
+        let histogram = complement.size_histogram();
+
+        assert_eq!((1 << 40) - 1, histogram.values().sum::<u64>());
+        assert_eq!(None, histogram.get(&40));
+        assert!(complement.node_count() <= 2 * universe.len() + 2);
+    }
+
+    #[test]
+    fn complement_twice_is_identity_within_universe() {
+        let forest: Forest<&str> = Forest::many(&[
+            vec!["1"],
+            vec!["2", "3"],
+        ]);
+
+        assert_eq!(forest.clone(), forest.complement(&["1", "2", "3"]).complement(&["1", "2", "3"]));
+    }
+}
+
+#[cfg(test)]
+mod filter_forest_tests {
+    use super::Forest;
+
+    #[test]
+    fn filter_by_element_matches_subset() {
+        let forest = Forest::unique(&["1-1", "1-2", "1-3"])
+            .product(Forest::unique(&["2-1", "2-2"]));
+
+        let filtered = forest.filter(|tree| tree.contains(&"2-1"));
+
+        assert_eq!(3, filtered.len());
+        assert_eq!(forest.subset("2-1"), filtered);
+    }
+
+    #[test]
+    fn filter_rejecting_everything_is_empty() {
+        let forest = Forest::unique(&["1", "2"]);
+
+        assert_eq!(Forest::empty(), forest.filter(|_| false));
+        assert_eq!(forest.clone(), forest.filter(|_| true));
+    }
+}
+
+#[cfg(test)]
+mod expand_forest_tests {
+    use super::Forest;
+
+    #[test]
+    fn expanding_one_element_into_two_grows_its_trees() {
+        let forest = Forest::many(&[
+            vec!["suit", "tie"],
+            vec!["shirt"],
+        ]);
+
+        let expanded = forest.expand(|element| match *element {
+            "suit" => vec!["jacket", "trousers"],
+            element => vec![element],
+        });
+
+        let expected: Vec<Vec<&str>> = vec![
+            vec!["jacket", "tie", "trousers"],
+            vec!["shirt"],
+        ];
+        assert_eq!(expected, expanded.sorted_sets());
+        assert_eq!(btreemap! { 1 => 1, 3 => 1 }, expanded.size_histogram());
+    }
+
+    #[test]
+    fn overlapping_expansions_are_merged() {
+        let forest = Forest::many(&[
+            vec!["suit", "blazer"],
+            vec!["suit"],
+            vec!["blazer"],
+        ]);
+
+        let expanded = forest.expand(|element| match *element {
+            "suit" => vec!["jacket", "trousers"],
+            _ => vec!["jacket"],
+        });
+
+        let expected: Vec<Vec<&str>> = vec![
+            vec!["jacket"],
+            vec!["jacket", "trousers"],
+        ];
+        assert_eq!(expected, expanded.sorted_sets());
+    }
+
+    #[test]
+    fn elements_expanding_to_nothing_are_dropped() {
+        let forest = Forest::unique(&["1", "2"]);
+
+        let expanded = forest.expand(|element| if *element == "1" { vec![] } else { vec![element.len()] });
+
+        assert_eq!(Forest::unit(&[1]).union(Forest::always()), expanded);
+    }
+}
+
+#[cfg(test)]
+mod universe_forest_tests {
+    use std::collections::BTreeSet;
+
+    use super::Forest;
+
+    #[test]
+    fn universe_of_empty_forest_is_empty() {
+        assert_eq!(Vec::<&str>::new(), Forest::<&str>::empty().universe());
+        assert_eq!(Vec::<&str>::new(), Forest::<&str>::always().universe());
+    }
+
+    #[test]
+    fn universe_is_union_of_tree_elements() {
+        let forest = Forest::many(&[
+            vec!["3", "1"],
+            vec!["2", "3"],
+            vec!["4"],
+        ]);
+
+        let elements = forest.trees()
+            .into_iter()
+            .flatten()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec!["1", "2", "3", "4"], forest.universe());
+        assert_eq!(elements, forest.universe());
+    }
+
+    #[test]
+    fn universe_follows_operations() {
+        let forest = Forest::unique(&["1", "2"])
+            .product(Forest::unique(&["3"]))
+            .subset_not("1");
+
+        assert_eq!(vec!["2", "3"], forest.universe());
+    }
+}
+
+#[cfg(test)]
+mod from_sets_forest_tests {
+    use super::Forest;
+
+    #[test]
+    fn from_sets_reproduces_trees() {
+        let forest: Forest<&str> = Forest::many(&[
+            vec!["1", "2"],
+            vec!["2", "3"],
+            vec!["4"],
+        ]);
+
+        let rebuilt = Forest::from_sets(forest.trees());
+
+        assert_eq!(forest, rebuilt);
+        assert_eq!(forest.trees(), rebuilt.trees());
+    }
+
+    #[test]
+    fn from_sets_collapses_duplicate_sets() {
+        let forest: Forest<&str> = Forest::from_sets(vec![
+            vec!["1", "2"],
+            vec!["2", "1"],
+            vec!["3"],
+        ]);
+        let expected: Vec<Vec<&str>> = vec![
+            vec!["1", "2"],
+            vec!["3"],
+        ];
+
+        assert_eq!(expected, forest.trees());
+    }
+
+    #[test]
+    fn from_sets_keeps_empty_set() {
+        let forest: Forest<&str> = Forest::from_sets(vec![vec![], vec!["1"]]);
+
+        assert_eq!(Forest::unit(&["1"]).union(Forest::always()), forest);
+    }
+
+    #[test]
+    fn from_sets_with_none_is_empty() {
+        let forest = Forest::<&str>::from_sets(vec![]);
+
+        assert_eq!(Forest::empty(), forest);
+    }
+}
+
+#[cfg(test)]
+mod sorted_sets_forest_tests {
+    use super::Forest;
+
+    #[test]
+    fn sorted_sets_of_product_forest() {
+        let forest = Forest::unique(&["b-2", "b-1"])
+            .product(Forest::unique(&["a-2", "a-1", "a-3"]));
+        let expected: Vec<Vec<&str>> = vec![
+            vec!["a-1", "b-1"],
+            vec!["a-1", "b-2"],
+            vec!["a-2", "b-1"],
+            vec!["a-2", "b-2"],
+            vec!["a-3", "b-1"],
+            vec!["a-3", "b-2"],
+        ];
+
+        assert_eq!(expected, forest.sorted_sets());
+    }
+
+    #[test]
+    fn sorted_sets_orders_shorter_prefix_first() {
+        let forest = Forest::from_sets(vec![
+            vec!["2"],
+            vec!["1", "2"],
+            vec!["1"],
+            vec![],
+        ]);
+        let expected: Vec<Vec<&str>> = vec![
+            vec![],
+            vec!["1"],
+            vec!["1", "2"],
+            vec!["2"],
+        ];
+
+        assert_eq!(expected, forest.sorted_sets());
+    }
+}
+
+#[cfg(test)]
+mod cache_stats_forest_tests {
+    use super::Forest;
+
+    #[test]
+    fn product_fills_caches_until_cleared() {
+        let _lock = super::node::cache::TEST_LOCK.lock().unwrap();
+        let forest = Forest::unique(&["1", "2", "3"]);
+        forest.clear_caches();
+
+        let product = forest.clone().product(Forest::unique(&["4", "5", "6"]));
+        let stats = product.cache_stats();
+        assert!(stats.entries > 0);
+        assert!(stats.misses > 0);
+
+        forest.clone().product(Forest::unique(&["4", "5", "6"]));
+        assert!(product.cache_stats().hits > stats.hits);
+
+        // Other tests share the tables, so exact counts after clearing are checked on a table of their own in `cache`
+        product.clear_caches();
+        forest.clone().product(Forest::unique(&["4", "5", "6"]));
+        assert!(product.cache_stats().misses > 0);
+    }
+}
+
+#[cfg(test)]
+mod product_algebra_tests {
+    use super::Forest;
+
+    fn fixtures() -> Vec<Forest<&'static str>> {
+        vec![
+            Forest::empty(),
+            Forest::always(),
+            Forest::unit(&["1"]),
+            Forest::unique(&["1", "2", "3"]),
+            Forest::many(&[vec!["1", "2"], vec!["2", "3"], vec!["4"]]),
+            Forest::many(&[vec!["3"], vec!["4", "5"]]),
+            Forest::from_sets(vec![vec![], vec!["5"], vec!["1", "5"]]),
+        ]
+    }
+
+    #[test]
+    fn product_is_commutative() {
+        for a in fixtures() {
+            for b in fixtures() {
+                assert_eq!(
+                    a.clone().product(b.clone()),
+                    b.clone().product(a.clone()),
+                    "{:?} x {:?}", a.sorted_sets(), b.sorted_sets()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn product_is_associative() {
+        for a in fixtures() {
+            for b in fixtures() {
+                for c in fixtures() {
+                    assert_eq!(
+                        a.clone().product(b.clone()).product(c.clone()),
+                        a.clone().product(b.clone().product(c.clone())),
+                        "{:?} x {:?} x {:?}", a.sorted_sets(), b.sorted_sets(), c.sorted_sets()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn always_is_the_identity_and_empty_annihilates() {
+        for a in fixtures() {
+            assert_eq!(a, a.clone().product(Forest::always()));
+            assert_eq!(Forest::empty(), a.clone().product(Forest::empty()));
+        }
+    }
+}
+
 #[cfg(test)]
 mod random_tests {
     use super::Forest;
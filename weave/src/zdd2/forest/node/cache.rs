@@ -0,0 +1,139 @@
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+#[cfg(test)]
+use std::sync::Mutex;
+use std::sync::RwLock;
+
+use hashbrown::HashMap;
+
+use super::Node;
+
+/// Hit and miss counts of the operation caches, along with how many results they hold
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Operation {
+    Union,
+    Difference,
+    Product,
+}
+
+/// A memo table shared between threads, so that results computed on one of rayon's workers are reused by the others
+#[derive(Default)]
+struct OperationCache {
+    results: RwLock<HashMap<(Operation, Node, Node), Node>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+lazy_static! {
+    static ref CACHE: OperationCache = OperationCache::default();
+}
+
+#[cfg(test)]
+lazy_static! {
+    /// Held by tests that clear the shared table or expect its entries to stay, since tests run concurrently
+    pub static ref TEST_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Looks up the result of `operation` on the nodes, computing and remembering it on a miss.
+/// Only pairs of branches are cached; anything involving a leaf is cheap enough to compute directly.
+pub fn cached<F: FnOnce(Node, Node) -> Node>(operation: Operation, node1: Node, node2: Node, compute: F) -> Node {
+    match (node1, node2) {
+        (Node::Branch(..), Node::Branch(..)) => CACHE.cached(operation, node1, node2, compute),
+        _ => compute(node1, node2),
+    }
+}
+
+pub fn stats() -> CacheStats {
+    CACHE.stats()
+}
+
+pub fn clear() {
+    CACHE.clear()
+}
+
+impl OperationCache {
+    /// The lock is released while computing, since `compute` recurses back into the cache.
+    fn cached<F: FnOnce(Node, Node) -> Node>(&self, operation: Operation, node1: Node, node2: Node, compute: F) -> Node {
+        let key = (operation, node1, node2);
+
+        if let Some(result) = self.results.read().unwrap().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return *result;
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let result = compute(node1, node2);
+        self.results.write().unwrap().insert(key, result);
+        result
+    }
+
+    fn stats(&self) -> CacheStats {
+        let results = self.results.read().unwrap();
+
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: results.len(),
+        }
+    }
+
+    fn clear(&self) {
+        let mut results = self.results.write().unwrap();
+
+        results.clear();
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Node;
+    use super::super::Priority;
+    use super::CacheStats;
+    use super::Operation;
+    use super::OperationCache;
+
+    #[test]
+    fn cache_counts_hits_and_misses_until_cleared() {
+        let cache = OperationCache::default();
+        let node1 = Node::branch(Priority(0), Node::Never, Node::Always);
+        let node2 = Node::branch(Priority(1), Node::Never, Node::Always);
+
+        let result = cache.cached(Operation::Union, node1, node2, Node::union);
+        assert_eq!(result, cache.cached(Operation::Union, node1, node2, |_, _| panic!("expected a hit")));
+        assert_eq!(CacheStats { hits: 1, misses: 1, entries: 1 }, cache.stats());
+
+        cache.clear();
+        assert_eq!(CacheStats { hits: 0, misses: 0, entries: 0 }, cache.stats());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn results_computed_on_one_thread_are_hits_on_another() {
+        let cache = OperationCache::default();
+        let node1 = Node::branch(Priority(0), Node::Never, Node::Always);
+        let node2 = Node::branch(Priority(1), Node::Never, Node::Always);
+
+        let (first, second) = ::rayon::join(
+            || cache.cached(Operation::Union, node1, node2, Node::union),
+            || cache.cached(Operation::Union, node1, node2, Node::union),
+        );
+        assert_eq!(first, second);
+        assert_eq!(first, cache.cached(Operation::Union, node1, node2, |_, _| panic!("expected a hit")));
+
+        let stats = cache.stats();
+        assert_eq!(1, stats.entries);
+        assert_eq!(3, stats.hits + stats.misses);
+
+        cache.clear();
+        assert_eq!(CacheStats { hits: 0, misses: 0, entries: 0 }, cache.stats());
+    }
+}
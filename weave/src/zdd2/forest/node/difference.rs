@@ -0,0 +1,45 @@
+use super::cache;
+use super::cache::Operation;
+use super::Node;
+
+pub fn difference(node1: Node, node2: Node) -> Node {
+    cache::cached(Operation::Difference, node1, node2, difference_nodes)
+}
+
+fn difference_nodes(node1: Node, node2: Node) -> Node {
+    if node1 == node2 {
+        return Node::Never;
+    }
+
+    let (id, low, high) = match (node1, node2) {
+        (Node::Never, _) => return Node::Never,
+        (_, Node::Never) => return node1,
+
+        (Node::Always, Node::Branch(_, low, _)) => return difference(node1, low.into()),
+        (Node::Branch(id, low, high), Node::Always) => {
+            let low = difference(low.into(), node2);
+            let high = Node::from(high);
+
+            (id, low, high)
+        }
+
+        (Node::Branch(id_1, low_1, high_1), Node::Branch(id_2, _, _)) if id_1 < id_2 => {
+            let low = difference(low_1.into(), node2);
+            let high = Node::from(high_1);
+
+            (id_1, low, high)
+        }
+        (Node::Branch(id_1, _, _), Node::Branch(id_2, low_2, _)) if id_1 > id_2 =>
+            return difference(node1, low_2.into()),
+        (Node::Branch(id_1, low_1, high_1), Node::Branch(_, low_2, high_2)) => {
+            let low = difference(low_1.into(), low_2.into());
+            let high = difference(high_1.into(), high_2.into());
+
+            (id_1, low, high)
+        }
+
+        (Node::Always, Node::Always) => return Node::Never,
+    };
+
+    Node::branch(id, low, high)
+}
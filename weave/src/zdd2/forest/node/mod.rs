@@ -3,8 +3,10 @@ use std::iter::FromIterator;
 use std::sync::RwLock;
 
 use self::arena::*;
+pub use self::cache::CacheStats;
 
 mod arena;
+pub(crate) mod cache;
 
 #[macro_use]
 mod macros;
@@ -13,6 +15,7 @@ mod serialize;
 
 mod intersect;
 mod union;
+mod difference;
 mod product;
 mod subset;
 
@@ -61,10 +64,9 @@ impl Node {
 
 impl Node {
     pub const NEVER: NodeId = NodeId(0);
-    #[cfg(test)]
     pub const ALWAYS: NodeId = NodeId(1);
 
-    pub fn branch<L, H>(id: Priority, low: L, high: H) -> Self where L: Into<NodeId>, H: Into<NodeId> {
+    pub(crate) fn branch<L, H>(id: Priority, low: L, high: H) -> Self where L: Into<NodeId>, H: Into<NodeId> {
         let low = Node::from(low.into());
         let high = Node::from(high.into());
 
@@ -95,33 +97,49 @@ impl Node {
         }
     }
 
-    pub fn intersect(self, other: Self) -> Self {
+    pub(crate) fn intersect(self, other: Self) -> Self {
         intersect::intersect(self, other)
     }
 
-    pub fn union(self, other: Self) -> Self {
+    pub(crate) fn union(self, other: Self) -> Self {
         union::union(self, other)
     }
 
-    pub fn product(self, other: Self) -> Self {
+    pub(crate) fn difference(self, other: Self) -> Self {
+        difference::difference(self, other)
+    }
+
+    pub(crate) fn product(self, other: Self) -> Self {
         product::product(self, other)
     }
 
-    pub fn subset(self, element: Priority) -> Self {
+    pub(crate) fn cache_stats() -> CacheStats {
+        cache::stats()
+    }
+
+    pub(crate) fn clear_caches() {
+        cache::clear()
+    }
+
+    pub(crate) fn subset(self, element: Priority) -> Self {
         subset::subset(self, element)
     }
 
-    pub fn subset_not(self, element: Priority) -> Self {
+    pub(crate) fn subset_not(self, element: Priority) -> Self {
         subset::subset_not(self, element)
     }
 
-    pub fn subset_all(self, elements: &[Priority]) -> Self {
+    pub(crate) fn subset_all(self, elements: &[Priority]) -> Self {
         subset::subset_all(self, elements)
     }
 
-    pub fn subset_none(self, elements: &[Priority]) -> Self {
+    pub(crate) fn subset_none(self, elements: &[Priority]) -> Self {
         subset::subset_none(self, elements)
     }
+
+    pub(crate) fn subset_any(self, elements: &[Priority]) -> Self {
+        subset::subset_any(self, elements)
+    }
 }
 
 impl<'a> From<Node> for NodeId {
@@ -1,10 +1,43 @@
+use super::cache;
+use super::cache::Operation;
 use super::Node;
 
+type Product<'a> = &'a (dyn Fn(Node, Node) -> Node + Sync);
+type Join = fn(&(dyn Fn() -> Node + Sync), &(dyn Fn() -> Node + Sync)) -> (Node, Node);
+
+/// Levels of recursion that are split across rayon's threads; deeper products are small enough
+/// that spawning costs more than it saves, so they run sequentially
+#[cfg(feature = "parallel")]
+const PARALLEL_DEPTH: usize = 8;
+
+#[cfg(not(feature = "parallel"))]
+pub fn product(node1: Node, node2: Node) -> Node {
+    product_sequential(node1, node2)
+}
+
+#[cfg(feature = "parallel")]
 pub fn product(node1: Node, node2: Node) -> Node {
-    if node1 == node2 {
-        return node1;
+    product_parallel(node1, node2, 0)
+}
+
+fn product_sequential(node1: Node, node2: Node) -> Node {
+    cache::cached(Operation::Product, node1, node2, |node1, node2| {
+        product_with(node1, node2, &product_sequential, |a, b| (a(), b()))
+    })
+}
+
+#[cfg(feature = "parallel")]
+fn product_parallel(node1: Node, node2: Node, depth: usize) -> Node {
+    if depth >= PARALLEL_DEPTH {
+        return product_sequential(node1, node2);
     }
 
+    cache::cached(Operation::Product, node1, node2, |node1, node2| {
+        product_with(node1, node2, &|node1, node2| product_parallel(node1, node2, depth + 1), |a, b| ::rayon::join(a, b))
+    })
+}
+
+fn product_with(node1: Node, node2: Node, product: Product, join: Join) -> Node {
     let (id, low, high) = match (node1, node2) {
         (_, Node::Always) => return node1,
         (Node::Always, _) => return node2,
@@ -13,33 +46,111 @@ pub fn product(node1: Node, node2: Node) -> Node {
         (Node::Never, _) => return Node::Never,
 
         (Node::Branch(id_1, low_1, high_1), Node::Branch(id_2, _, _)) if id_1 < id_2 => {
-            let low = product(low_1.into(), node2);
-            let high = product(high_1.into(), node2);
+            let (low, high) = join(
+                &|| product(low_1.into(), node2),
+                &|| product(high_1.into(), node2),
+            );
 
             (id_1, low, high)
         }
         (Node::Branch(id_1, _, _), Node::Branch(id_2, low_2, high_2)) if id_1 > id_2 => {
-            let low = product(node1, low_2.into());
-            let high = product(node1, high_2.into());
+            let (low, high) = join(
+                &|| product(node1, low_2.into()),
+                &|| product(node1, high_2.into()),
+            );
 
             (id_2, low, high)
         }
         (Node::Branch(id_1, low_1, high_1), Node::Branch(_, low_2, high_2)) => {
-            let low_1_low_2 = product(low_1.into(), low_2.into());
-            let low_1_high_2 = product(low_1.into(), high_2.into());
-
-            let new_high = {
-                let high_1_low_2 = product(high_1.into(), low_2.into());
-                let high_1_high_2 = product(high_1.into(), high_2.into());
+            let (low_1_low_2, high) = join(
+                &|| product(low_1.into(), low_2.into()),
+                &|| {
+                    let (low_1_high_2, new_high) = join(
+                        &|| product(low_1.into(), high_2.into()),
+                        &|| {
+                            let (high_1_low_2, high_1_high_2) = join(
+                                &|| product(high_1.into(), low_2.into()),
+                                &|| product(high_1.into(), high_2.into()),
+                            );
 
-                Node::union(high_1_low_2, high_1_high_2)
-            };
+                            Node::union(high_1_low_2, high_1_high_2)
+                        },
+                    );
 
-            let high = Node::union(low_1_high_2, new_high);
+                    Node::union(low_1_high_2, new_high)
+                },
+            );
 
             (id_1, low_1_low_2, high)
         }
     };
 
     Node::branch(id, low, high)
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, feature = "parallel"))]
+mod tests {
+    use super::super::cache;
+    use super::super::cache::Operation;
+    use super::super::Node;
+    use super::super::Priority;
+    use super::product_parallel;
+    use super::product_sequential;
+    use super::PARALLEL_DEPTH;
+
+    fn unique(ids: &[usize]) -> Node {
+        ids.iter()
+            .fold(Node::Never, |root, id| Node::branch(Priority(*id), root, Node::Always))
+    }
+
+    #[test]
+    fn parallel_and_sequential_products_are_equal() {
+        let forest = (0..6)
+            .map(|family| unique(&[family * 4, family * 4 + 1, family * 4 + 2, family * 4 + 3]))
+            .fold(Node::Always, product_sequential);
+
+        let other = unique(&[1, 5, 9, 13, 17, 21, 24, 25]);
+
+        assert_eq!(
+            product_sequential(forest, other),
+            product_parallel(forest, other, 0)
+        );
+        assert_eq!(
+            product_sequential(other, forest),
+            product_parallel(other, forest, 0)
+        );
+    }
+
+    #[test]
+    fn products_past_parallel_depth_run_sequentially() {
+        let forest = unique(&[0, 1]);
+        let other = unique(&[2, 3]);
+
+        assert_eq!(
+            product_sequential(forest, other),
+            product_parallel(forest, other, PARALLEL_DEPTH)
+        );
+    }
+
+    #[test]
+    fn parallel_product_fills_the_shared_cache() {
+        let _lock = cache::TEST_LOCK.lock().unwrap();
+
+        let forest = (0..6)
+            .map(|family| unique(&[family * 4, family * 4 + 1, family * 4 + 2, family * 4 + 3]))
+            .fold(Node::Always, product_sequential);
+        let other = unique(&[1, 5, 9, 13, 17, 21, 24, 25]);
+
+        let product = product_parallel(forest, other, 0);
+
+        // The halves are joined on rayon's workers, not on this thread
+        let (low, high) = match forest {
+            Node::Branch(_, low, high) => (Node::from(low), Node::from(high)),
+            _ => panic!("expected forest to be a branch"),
+        };
+        for half in &[low, high] {
+            cache::cached(Operation::Product, *half, other, |_, _| panic!("expected a product computed on a worker to be cached"));
+        }
+        assert_eq!(product, cache::cached(Operation::Product, forest, other, |_, _| panic!("expected the product to be cached")));
+    }
+}
@@ -57,6 +57,12 @@ pub fn subset_none(root: Node, elements: &[Priority]) -> Node {
         .fold(Node::Always, Node::intersect)
 }
 
+pub fn subset_any(root: Node, elements: &[Priority]) -> Node {
+    elements.iter()
+        .map(|element| subset(root, element.to_owned()))
+        .fold(Node::Never, Node::union)
+}
+
 fn reduce_branch((root, keep): Matching) -> Matching {
     match (root, keep) {
         (_root, false) => (Node::Never, keep),
@@ -1,6 +1,12 @@
+use super::cache;
+use super::cache::Operation;
 use super::Node;
 
 pub fn union(node1: Node, node2: Node) -> Node {
+    cache::cached(Operation::Union, node1, node2, union_nodes)
+}
+
+fn union_nodes(node1: Node, node2: Node) -> Node {
     if node1 == node2 {
         return node1;
     }
@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+
+use hashbrown::HashMap;
+use hashbrown::HashSet;
+
+use super::node::Node;
+use super::node::NodeId;
+use super::Priority;
+
+pub fn node_count(root: NodeId) -> usize {
+    let mut seen = HashSet::new();
+
+    let mut queue = vec![root];
+    while let Some(node_id) = queue.pop() {
+        if !seen.insert(node_id) {
+            continue;
+        }
+
+        if let Node::Branch(_id, low, high) = Node::from(node_id) {
+            queue.push(low);
+            queue.push(high);
+        }
+    }
+
+    seen.len()
+}
+
+pub fn tree_count(root: NodeId) -> usize {
+    tree_count_inner(root, &mut HashMap::new())
+}
+
+fn tree_count_inner(root: NodeId, cache: &mut HashMap<NodeId, usize>) -> usize {
+    if let Some(count) = cache.get(&root) {
+        return *count;
+    }
+
+    let count = match Node::from(root) {
+        Node::Always => 1,
+        Node::Never => 0,
+        Node::Branch(_id, low, high) => tree_count_inner(low, cache) + tree_count_inner(high, cache),
+    };
+
+    cache.insert(root, count);
+    count
+}
+
+pub fn size_histogram(root: NodeId) -> BTreeMap<usize, u64> {
+    size_histogram_inner(root, &mut HashMap::new())
+}
+
+fn size_histogram_inner(root: NodeId, cache: &mut HashMap<NodeId, BTreeMap<usize, u64>>) -> BTreeMap<usize, u64> {
+    if let Some(histogram) = cache.get(&root) {
+        return histogram.clone();
+    }
+
+    let histogram = match Node::from(root) {
+        Node::Always => {
+            let mut histogram = BTreeMap::new();
+            histogram.insert(0, 1);
+            histogram
+        }
+        Node::Never => BTreeMap::new(),
+        Node::Branch(_id, low, high) => {
+            let high = size_histogram_inner(high, cache);
+
+            high.into_iter()
+                .fold(size_histogram_inner(low, cache), |mut histogram, (size, count)| {
+                    *histogram.entry(size + 1).or_insert(0) += count;
+                    histogram
+                })
+        }
+    };
+
+    cache.insert(root, histogram.clone());
+    histogram
+}
+
+/// How many trees hold each element, counted on the diagram rather than by enumerating the trees
+pub fn occurrences(root: NodeId) -> HashMap<Priority, usize> {
+    occurrences_inner(root, &mut HashMap::new()).1
+}
+
+fn occurrences_inner(root: NodeId, cache: &mut HashMap<NodeId, (usize, HashMap<Priority, usize>)>) -> (usize, HashMap<Priority, usize>) {
+    if let Some(counts) = cache.get(&root) {
+        return counts.clone();
+    }
+
+    let counts = match Node::from(root) {
+        Node::Always => (1, HashMap::new()),
+        Node::Never => (0, HashMap::new()),
+        Node::Branch(id, low, high) => {
+            let (low_trees, low_occurrences) = occurrences_inner(low, cache);
+            let (high_trees, high_occurrences) = occurrences_inner(high, cache);
+
+            let mut occurrences = low_occurrences;
+            for (element, count) in high_occurrences {
+                let total = occurrences.entry(element).or_insert(0);
+                *total = total.saturating_add(count);
+            }
+            let total = occurrences.entry(id).or_insert(0);
+            *total = total.saturating_add(high_trees);
+
+            (low_trees.saturating_add(high_trees), occurrences)
+        }
+    };
+
+    cache.insert(root, counts.clone());
+    counts
+}
+
+#[cfg(test)]
+mod node_count_tests {
+    use super::super::Forest;
+
+    #[test]
+    fn node_count_of_leaves() {
+        assert_eq!(1, Forest::<&str>::empty().node_count());
+        assert_eq!(1, Forest::<&str>::always().node_count());
+    }
+
+    #[test]
+    fn node_count_of_unit() {
+        // "1", "2", Never and Always
+        assert_eq!(4, Forest::unit(&["1", "2"]).node_count());
+    }
+
+    #[test]
+    fn node_count_shares_sub_diagrams() {
+        let forest = Forest::many(&[
+            vec!["1", "3"],
+            vec!["2", "3"],
+        ]);
+
+        // "1", "2", a single shared "3", Never and Always
+        assert_eq!(5, forest.node_count());
+    }
+
+    #[test]
+    fn heap_bytes_grow_with_forest() {
+        let small = Forest::unit(&["1"]);
+        let large = Forest::many(&[
+            vec!["1", "3"],
+            vec!["2", "3"],
+        ]);
+
+        assert!(0 < small.heap_bytes());
+        assert!(small.heap_bytes() < large.heap_bytes());
+    }
+}
+
+#[cfg(test)]
+mod size_histogram_tests {
+    use std::collections::BTreeMap;
+
+    use super::super::Forest;
+
+    #[test]
+    fn size_histogram_of_leaves() {
+        assert_eq!(BTreeMap::new(), Forest::<&str>::empty().size_histogram());
+        assert_eq!(btreemap! { 0 => 1 }, Forest::<&str>::always().size_histogram());
+    }
+
+    #[test]
+    fn size_histogram_of_product_with_mixed_sizes() {
+        let shirts = Forest::many(&[
+            vec!["shirts:blue"],
+            vec!["shirts:red", "ties:silk"],
+        ]);
+        let pants = Forest::many(&[
+            vec!["pants:jeans"],
+            vec!["pants:slacks", "belts:leather"],
+            vec!["pants:shorts"],
+        ]);
+
+        let outfits = shirts.product(pants);
+        let histogram = outfits.size_histogram();
+
+        assert_eq!(btreemap! { 2 => 2, 3 => 3, 4 => 1 }, histogram);
+        assert_eq!(outfits.len() as u64, histogram.values().sum::<u64>());
+
+        let trees_by_size = outfits.trees()
+            .into_iter()
+            .fold(BTreeMap::new(), |mut sizes, tree| {
+                *sizes.entry(tree.len()).or_insert(0u64) += 1;
+                sizes
+            });
+        assert_eq!(trees_by_size, histogram);
+    }
+}
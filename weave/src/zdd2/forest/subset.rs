@@ -26,6 +26,17 @@ pub fn subset_not<T: Hash + Eq + Clone + Ord + Sync + Send>(forest: Forest<T>, e
     Forest::canonical(root, forest.universe)
 }
 
+pub fn subset_any<T: Hash + Eq + Clone + Ord + Sync + Send>(forest: Forest<T>, elements: &[T]) -> Forest<T> {
+    let known_elements: Vec<_> = forest.universe.get_priorities(elements);
+    if known_elements.is_empty() {
+        return Forest::empty();
+    }
+
+    let root = Node::subset_any(forest.root.into(), &known_elements);
+
+    Forest::canonical(root, forest.universe)
+}
+
 pub fn subset_many<T: Hash + Eq + Clone + Ord + Sync + Send>(
     forest: Forest<T>,
     elements: &[T],
@@ -209,3 +220,101 @@ mod subset_all_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod subset_composition_tests {
+    use super::super::Forest;
+
+    fn outfits() -> Forest<&'static str> {
+        Forest::many(&[
+            vec!["shirts:blue", "belts:leather", "pants:jeans"],
+            vec!["shirts:blue", "belts:canvas", "pants:jeans"],
+            vec!["shirts:red", "belts:leather", "pants:slacks"],
+            vec!["shirts:blue", "pants:slacks"],
+        ])
+    }
+
+    #[test]
+    fn subset_all_matches_repeated_subset() {
+        let repeated = outfits()
+            .subset("shirts:blue")
+            .subset("belts:leather");
+
+        assert_eq!(
+            Forest::unit(&["shirts:blue", "belts:leather", "pants:jeans"]),
+            repeated
+        );
+        assert_eq!(
+            repeated,
+            outfits().subset_all(&["shirts:blue", "belts:leather"])
+        );
+        assert_eq!(
+            repeated,
+            outfits().subset_all(&["belts:leather", "shirts:blue"])
+        );
+    }
+
+    #[test]
+    fn subset_any_matches_union_of_subsets() {
+        let unioned = outfits().subset("shirts:red")
+            .union(outfits().subset("belts:canvas"));
+
+        assert_eq!(
+            Forest::many(&[
+                vec!["shirts:blue", "belts:canvas", "pants:jeans"],
+                vec!["shirts:red", "belts:leather", "pants:slacks"],
+            ]),
+            unioned
+        );
+        assert_eq!(
+            unioned,
+            outfits().subset_any(&["shirts:red", "belts:canvas"])
+        );
+        assert_eq!(
+            unioned,
+            outfits().subset_any(&["shirts:red", "belts:canvas", "hats:fedora"])
+        );
+    }
+}
+
+#[cfg(test)]
+mod without_tests {
+    use super::super::Forest;
+
+    fn outfits() -> Forest<&'static str> {
+        Forest::many(&[
+            vec!["shirts:blue", "ties:silk", "pants:slacks"],
+            vec!["shirts:blue", "pants:jeans"],
+            vec!["shirts:red", "ties:silk", "pants:jeans"],
+            vec!["shirts:red", "pants:slacks"],
+        ])
+    }
+
+    #[test]
+    fn without_drops_trees_containing_element() {
+        assert_eq!(
+            Forest::many(&[
+                vec!["shirts:blue", "pants:jeans"],
+                vec!["shirts:red", "pants:slacks"],
+            ]),
+            outfits().without("ties:silk")
+        );
+    }
+
+    #[test]
+    fn without_of_unknown_element_returns_identity() {
+        assert_eq!(outfits(), outfits().without("hats:fedora"));
+        assert_eq!(Forest::<&str>::empty(), Forest::empty().without("ties:silk"));
+    }
+
+    #[test]
+    fn subset_and_without_reconstruct_forest() {
+        for element in &["shirts:blue", "ties:silk", "pants:jeans", "hats:fedora"] {
+            let with = outfits().subset(element);
+            let without = outfits().without(element);
+
+            assert_eq!(Forest::<&str>::empty(), with.clone().intersect(without.clone()));
+            assert_eq!(outfits(), with.union(without));
+        }
+    }
+}
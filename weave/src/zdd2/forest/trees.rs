@@ -1,3 +1,5 @@
+use hashbrown::HashSet;
+
 use super::node::Node;
 use super::node::NodeId;
 use super::Priority;
@@ -5,6 +7,7 @@ use super::Priority;
 pub fn trees(root: NodeId) -> Vec<Vec<Priority>> {
     let trees: Vec<Vec<Priority>> = {
         let mut trees = vec![];
+        let mut seen = HashSet::new();
 
         let mut queue: Vec<(Node, Vec<Priority>)> = vec![(Node::from(root), vec![])];
         while let Some((node, path)) = queue.pop() {
@@ -19,7 +22,15 @@ pub fn trees(root: NodeId) -> Vec<Vec<Priority>> {
                     path.push(id);
                     queue.push((high, path));
                 }
-                Node::Always => trees.push(path),
+                Node::Always => {
+                    let mut tree = path.clone();
+                    tree.sort();
+                    tree.dedup();
+
+                    if seen.insert(tree) {
+                        trees.push(path);
+                    }
+                }
                 Node::Never => {}
             };
         }
@@ -29,3 +40,30 @@ pub fn trees(root: NodeId) -> Vec<Vec<Priority>> {
 
     trees
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::node::Node;
+    use super::super::node::NodeId;
+    use super::super::Priority;
+    use super::trees;
+
+    #[test]
+    fn paths_reaching_the_same_tree_are_emitted_once() {
+        let inner = NodeId::from(Node::Branch(Priority(0), Node::NEVER, Node::ALWAYS));
+        let root = NodeId::from(Node::Branch(Priority(0), inner, Node::ALWAYS));
+
+        assert_eq!(vec![vec![Priority(0)]], trees(root));
+    }
+
+    #[test]
+    fn distinct_trees_are_all_emitted() {
+        let low = NodeId::from(Node::Branch(Priority(1), Node::ALWAYS, Node::ALWAYS));
+        let root = NodeId::from(Node::Branch(Priority(0), low, Node::ALWAYS));
+
+        assert_eq!(
+            vec![vec![Priority(0)], vec![Priority(1)], vec![]],
+            trees(root)
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use std::cmp::Ordering;
 use std::hash::Hash;
+use std::hash::Hasher;
 use std::iter::FromIterator;
 
 use hashbrown::HashMap;
@@ -16,6 +17,12 @@ pub struct Universe<T: Hash + Eq + Clone + Ord> {
     index: Vec<T>,
 }
 
+impl<T: Hash + Eq + Clone + Ord> Hash for Universe<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
+
 impl<T: Hash + Eq + Clone + Ord> Default for Universe<T> {
     fn default() -> Self {
         Universe {
@@ -90,13 +97,21 @@ impl<T: Hash + Eq + Clone + Ord> Universe<T> {
         self.index.get(id.0)
     }
 
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.index
+    }
+
     pub fn occurrences(&self) -> &HashMap<T, usize> {
         &self.occurrences
     }
 }
 
 impl<T: Hash + Eq + Clone + Ord> Universe<T> {
-    fn from_occurrences(occurrences: HashMap<T, usize>) -> Self {
+    pub fn from_occurrences(occurrences: HashMap<T, usize>) -> Self {
         let ordering: fn(&(&T, &usize), &(&T, &usize)) -> Ordering = |(name1, count1), (name2, count2)| Ord::cmp(count2, count1).then(Ord::cmp(name1, name2));
 
         let priority: HashMap<T, Priority> = occurrences.iter()
@@ -2,7 +2,11 @@ use std::hash::Hash;
 
 use types;
 
+pub use self::forest::CacheStats;
 pub use self::forest::Forest;
+pub use self::forest::Node;
+pub use self::forest::NodeId;
+pub use self::forest::Priority;
 pub use self::tree::Tree;
 
 mod forest;
@@ -68,6 +72,10 @@ impl<T: Hash + Eq + Clone + Ord + Sync + Send> types::Forest<T> for Forest<T> {
     fn subset_none(self, elements: &[T]) -> Self {
         Forest::subset_none(self, elements)
     }
+
+    fn subset_any(self, elements: &[T]) -> Self {
+        Forest::subset_any(self, elements)
+    }
 }
 
 impl<T: Hash + Eq + Clone + Ord + Sync + Send> types::Tree<T> for Tree<T> {
@@ -5,6 +5,7 @@ pub mod subset;
 pub mod subset_not;
 pub mod subset_all;
 pub mod subset_none;
+pub mod subset_any;
 pub mod union;
 
 macro_rules! intersect {
@@ -173,6 +174,21 @@ macro_rules! subset_none {
     };
 }
 
+macro_rules! subset_any {
+    ($forest:ty, $test_case:ident) => {
+
+        #[test]
+        fn $test_case() {
+            let (forest, elements, expected) = $crate::forest::subset_any::$test_case::<$forest>();
+
+            assert_eq!(
+                expected,
+                <$forest>::subset_any(forest, &elements)
+            );
+        }
+    };
+}
+
 macro_rules! subset_tests {
     ($forest:ty) => {
 
@@ -235,6 +251,21 @@ macro_rules! subset_tests {
 
             subset_none!($forest, many_forest_with_many_elements);
         }
+
+        #[cfg(test)]
+        mod subset_any_tests {
+            subset_any!($forest, empty_forest_with_single_element);
+
+            subset_any!($forest, unit_forest_with_empty_elements);
+
+            subset_any!($forest, unit_forest_with_one_element);
+
+            subset_any!($forest, unit_forest_with_partly_disjoint_elements);
+
+            subset_any!($forest, many_forest_with_one_element);
+
+            subset_any!($forest, many_forest_with_many_elements);
+        }
     };
 }
 
@@ -1,3 +1,4 @@
+extern crate serde_json;
 extern crate serde_test;
 extern crate weave;
 
@@ -19,6 +20,7 @@ mod zdd_tests {
 
 #[cfg(test)]
 mod serde_tests {
+    use serde_json;
     use serde_test::{assert_tokens, Token};
 
     use weave::zdd2::Forest;
@@ -98,4 +100,17 @@ mod serde_tests {
             Token::StructEnd,
         ]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn product_round_trip() {
+        let forest = Forest::unique(&[String::from("1-1"), String::from("1-2"), String::from("1-3")])
+            .product(Forest::unique(&[String::from("2-1"), String::from("2-2")]))
+            .product(Forest::unit(&[String::from("3-1")]));
+
+        let json = serde_json::to_string(&forest).expect("expected Forest to serialize");
+        let restored: Forest<String> = serde_json::from_str(&json).expect("expected Forest to deserialize");
+
+        assert_eq!(forest, restored);
+        assert_eq!(forest.trees(), restored.trees());
+    }
+}